@@ -0,0 +1,337 @@
+//! Parser of the Apple Password Rules syntax
+//!
+//! See <https://developer.apple.com/password-rules/> for the syntax.
+
+use crate::{Classifier, PasswordMaker};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Characters of the `special` class
+const SPECIAL: &str = "-~!@#$%^&*_+=`|(){}[:;\"'<>,.? ]";
+
+/// Character class in the rules
+#[derive(Debug, Clone, PartialEq)]
+enum CharacterClass {
+    Upper,
+    Lower,
+    Digit,
+    Special,
+    AsciiPrintable,
+    Custom(Vec<String>),
+}
+
+/// Create a password generator from Apple Password Rules
+///
+/// See [`PasswordMaker::from_apple_rules`] for how the rules are translated.
+///
+/// # Arguments
+///
+/// * `rules` - Password rules
+///
+/// # Returns
+///
+/// * Ok: Password generator
+/// * Err: Error message
+pub(crate) fn parse(rules: &str) -> Result<PasswordMaker, String> {
+    let mut required = Vec::new();
+    let mut allowed = Vec::new();
+    let mut min_length = None;
+    let mut max_length = None;
+
+    for rule in rules
+        .split(';')
+        .map(str::trim)
+        .filter(|rule| !rule.is_empty())
+    {
+        let (name, value) = rule.split_once(':').ok_or(format!(
+            "Invalid password rule: \"{}\". Rules must be \"name: value\".",
+            rule
+        ))?;
+        let value = value.trim();
+
+        match name.trim().to_ascii_lowercase().as_str() {
+            "required" => required.push(parse_classes(value)?),
+            "allowed" => allowed.extend(parse_classes(value)?),
+            "minlength" => min_length = Some(parse_length(value)?),
+            "maxlength" => max_length = Some(parse_length(value)?),
+            "max-consecutive" => {
+                return Err("The password rule \"max-consecutive\" is not supported.".to_string())
+            }
+            name => return Err(format!("Unknown password rule: \"{}\".", name)),
+        }
+    }
+
+    if let (Some(min), Some(max)) = (min_length, max_length) {
+        if max < min {
+            return Err(format!(
+                "minlength {} is greater than maxlength {} in the password rules.",
+                min, max
+            ));
+        }
+    }
+
+    let mut maker = PasswordMaker {
+        uppercase: Classifier::default(),
+        lowercase: Classifier::default(),
+        number: Classifier::default(),
+        symbol: Classifier::default(),
+        ..PasswordMaker::default()
+    };
+
+    if required.is_empty() && allowed.is_empty() {
+        allow(&mut maker, &CharacterClass::AsciiPrintable, false);
+    }
+    for classes in &required {
+        for class in classes {
+            allow(&mut maker, class, true);
+        }
+    }
+    for class in &allowed {
+        allow(&mut maker, class, false);
+    }
+
+    maker.length = maker.length.max(min_length.unwrap_or(0));
+    maker.length = maker.length.min(max_length.unwrap_or(u32::MAX));
+
+    Ok(maker)
+}
+
+/// Add the characters of a class to the candidates of the password generator
+///
+/// The characters are added to the candidates already allowed by other classes.
+///
+/// # Arguments
+///
+/// * `maker` - Password generator
+/// * `class` - Character class
+/// * `required` - Whether at least one character of the class is required
+fn allow(maker: &mut PasswordMaker, class: &CharacterClass, required: bool) {
+    let defaults = PasswordMaker::default();
+    let set = |classifier: &mut Classifier, candidates: Vec<String>| {
+        for candidate in candidates {
+            if !classifier.candidates.contains(&candidate) {
+                classifier.candidates.push(candidate);
+            }
+        }
+        if required {
+            classifier.minimum_count = classifier.minimum_count.max(1);
+        }
+    };
+
+    match class {
+        CharacterClass::Upper => set(&mut maker.uppercase, defaults.uppercase.candidates),
+        CharacterClass::Lower => set(&mut maker.lowercase, defaults.lowercase.candidates),
+        CharacterClass::Digit => set(&mut maker.number, defaults.number.candidates),
+        CharacterClass::Special => set(
+            &mut maker.symbol,
+            SPECIAL.chars().map(|c| c.to_string()).collect(),
+        ),
+        CharacterClass::AsciiPrintable => {
+            // Any printable character satisfies the requirement, so no minimum count is needed
+            for class in [
+                CharacterClass::Upper,
+                CharacterClass::Lower,
+                CharacterClass::Digit,
+            ] {
+                allow(maker, &class, false);
+            }
+            // The symbols and space
+            set(
+                &mut maker.symbol,
+                defaults
+                    .symbol
+                    .candidates
+                    .into_iter()
+                    .chain([" ".to_string()])
+                    .collect(),
+            );
+        }
+        CharacterClass::Custom(candidates) => maker.others.push(Classifier {
+            candidates: candidates.clone(),
+            minimum_count: required as u32,
+            ..Default::default()
+        }),
+    }
+}
+
+/// Parse a comma separated list of character classes
+///
+/// # Arguments
+///
+/// * `value` - List of character classes (e.g. `upper, digit, [-_]`)
+///
+/// # Returns
+///
+/// * Ok: List of character classes
+/// * Err: Error message
+fn parse_classes(value: &str) -> Result<Vec<CharacterClass>, String> {
+    let mut classes = Vec::new();
+    let mut rest = value.trim_start_matches([',', ' ']);
+
+    while !rest.is_empty() {
+        if let Some(custom) = rest.strip_prefix('[') {
+            // Custom character sets may contain commas, so read up to the closing bracket.
+            // "]" is a member if it is followed by another "]", as in "[-_]]"
+            let mut end = custom.find(']').ok_or(format!(
+                "Unclosed \"[\" in the password rule value \"{}\".",
+                value
+            ))?;
+            while custom[end + 1..].starts_with(']') {
+                end += 1;
+            }
+            let mut candidates: Vec<String> = Vec::new();
+            for grapheme in custom[..end].graphemes(true) {
+                if !candidates.iter().any(|c| c == grapheme) {
+                    candidates.push(grapheme.to_string());
+                }
+            }
+            classes.push(CharacterClass::Custom(candidates));
+            rest = &custom[end + 1..];
+        } else {
+            let end = rest.find([',', ' ']).unwrap_or(rest.len());
+            let class =
+                match rest[..end].to_ascii_lowercase().as_str() {
+                    "upper" => CharacterClass::Upper,
+                    "lower" => CharacterClass::Lower,
+                    "digit" => CharacterClass::Digit,
+                    "special" => CharacterClass::Special,
+                    "ascii-printable" => CharacterClass::AsciiPrintable,
+                    "unicode" => return Err(
+                        "The character class \"unicode\" is not supported in the password rules."
+                            .to_string(),
+                    ),
+                    class => return Err(format!("Unknown character class: \"{}\".", class)),
+                };
+            classes.push(class);
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start_matches([',', ' ']);
+    }
+
+    Ok(classes)
+}
+
+/// Parse a length in the rules
+///
+/// # Arguments
+///
+/// * `value` - Length
+///
+/// # Returns
+///
+/// * Ok: Length
+/// * Err: Error message
+fn parse_length(value: &str) -> Result<u32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid length in the password rules: \"{}\".", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_classes_list() {
+        assert_eq!(
+            parse_classes("upper, lower,digit special").unwrap(),
+            vec![
+                CharacterClass::Upper,
+                CharacterClass::Lower,
+                CharacterClass::Digit,
+                CharacterClass::Special
+            ]
+        );
+
+        // Custom character sets may contain commas and spaces, and duplicates are removed
+        assert_eq!(
+            parse_classes("[-, ,], ascii-printable").unwrap(),
+            vec![
+                CharacterClass::Custom(vec!["-".to_string(), ",".to_string(), " ".to_string()]),
+                CharacterClass::AsciiPrintable
+            ]
+        );
+
+        // "]" is a member if it is the last character
+        assert_eq!(
+            parse_classes("[-]], []], [a]]]").unwrap(),
+            vec![
+                CharacterClass::Custom(vec!["-".to_string(), "]".to_string()]),
+                CharacterClass::Custom(vec!["]".to_string()]),
+                CharacterClass::Custom(vec!["a".to_string(), "]".to_string()]),
+            ]
+        );
+
+        assert!(parse_classes("[abc").is_err());
+        assert!(parse_classes("unicode").is_err());
+        assert!(parse_classes("uper").is_err());
+    }
+
+    #[test]
+    fn parse_rules() {
+        let maker = parse(
+            "required: upper; required: lower; allowed: digit, [-_]; minlength: 20; maxlength: 24;",
+        )
+        .unwrap();
+
+        assert_eq!(maker.length, 20);
+        assert_eq!(maker.uppercase.candidates.len(), 26);
+        assert_eq!(maker.uppercase.minimum_count, 1);
+        assert_eq!(maker.lowercase.candidates.len(), 26);
+        assert_eq!(maker.lowercase.minimum_count, 1);
+        assert_eq!(maker.number.candidates.len(), 10);
+        assert_eq!(maker.number.minimum_count, 0);
+        assert!(maker.symbol.candidates.is_empty());
+        assert_eq!(maker.others.len(), 1);
+        assert_eq!(maker.others[0].candidates, vec!["-", "_"]);
+        assert_eq!(maker.others[0].minimum_count, 0);
+    }
+
+    #[test]
+    fn parse_rules_each_class() {
+        // Each class of a rule is required
+        let maker = parse("required: digit, special; maxlength: 8").unwrap();
+
+        assert_eq!(maker.length, 8);
+        assert_eq!(maker.number.minimum_count, 1);
+        assert_eq!(maker.symbol.candidates.len(), SPECIAL.len());
+        assert_eq!(maker.symbol.minimum_count, 1);
+        assert!(maker.uppercase.candidates.is_empty());
+        assert!(maker.lowercase.candidates.is_empty());
+    }
+
+    #[test]
+    fn parse_rules_default() {
+        // Without classes, all ASCII printable characters are allowed
+        let maker = parse("minlength: 32").unwrap();
+
+        assert_eq!(maker.length, 32);
+        assert_eq!(maker.uppercase.candidates.len(), 26);
+        assert_eq!(maker.lowercase.candidates.len(), 26);
+        assert_eq!(maker.number.candidates.len(), 10);
+        assert_eq!(maker.symbol.candidates.len(), 33);
+        assert!(maker.symbol.candidates.contains(&" ".to_string()));
+        assert_eq!(maker.uppercase.minimum_count, 0);
+        assert_eq!(maker.symbol.minimum_count, 0);
+    }
+
+    #[test]
+    fn parse_rules_space() {
+        // The special class includes space
+        let maker = parse("allowed: special").unwrap();
+        assert!(maker.symbol.candidates.contains(&" ".to_string()));
+
+        // ASCII printable characters are added to the special characters, including "/" and "\\"
+        let maker = parse("required: special; allowed: ascii-printable").unwrap();
+        assert_eq!(maker.symbol.candidates.len(), 33);
+        assert_eq!(maker.symbol.minimum_count, 1);
+    }
+
+    #[test]
+    fn parse_rules_err() {
+        assert!(parse("required upper").is_err());
+        assert!(parse("forbidden: upper").is_err());
+        assert!(parse("minlength: eight").is_err());
+        assert!(parse("minlength: 10; maxlength: 8").is_err());
+        assert!(parse("max-consecutive: 2").is_err());
+    }
+}
@@ -0,0 +1,34 @@
+//! Table of graphemes that look alike across scripts
+
+/// Groups of graphemes that are rendered identically or nearly so in common fonts
+///
+/// Each group has a Latin letter followed by its Cyrillic and Greek look-alikes.
+pub(crate) const CONFUSABLES: &[&[&str]] = &[
+    &["a", "\u{0430}"],
+    &["c", "\u{0441}"],
+    &["e", "\u{0435}"],
+    &["i", "\u{0456}"],
+    &["j", "\u{0458}"],
+    &["o", "\u{043E}", "\u{03BF}"],
+    &["p", "\u{0440}"],
+    &["s", "\u{0455}"],
+    &["x", "\u{0445}"],
+    &["y", "\u{0443}"],
+    &["A", "\u{0410}", "\u{0391}"],
+    &["B", "\u{0412}", "\u{0392}"],
+    &["C", "\u{0421}"],
+    &["E", "\u{0415}", "\u{0395}"],
+    &["H", "\u{041D}", "\u{0397}"],
+    &["I", "\u{0406}", "\u{0399}"],
+    &["J", "\u{0408}"],
+    &["K", "\u{041A}", "\u{039A}"],
+    &["M", "\u{041C}", "\u{039C}"],
+    &["N", "\u{039D}"],
+    &["O", "\u{041E}", "\u{039F}"],
+    &["P", "\u{0420}", "\u{03A1}"],
+    &["S", "\u{0405}"],
+    &["T", "\u{0422}", "\u{03A4}"],
+    &["X", "\u{0425}", "\u{03A7}"],
+    &["Y", "\u{03A5}"],
+    &["Z", "\u{0396}"],
+];
@@ -0,0 +1,312 @@
+use std::fmt;
+
+/// Error returned by the password generator
+///
+/// The messages of [`Display`](fmt::Display) are meant to be shown to users as they are.
+/// Match on the variants to handle each error programmatically.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordError {
+    /// No candidates for a character type, but the minimum number of characters is set to 1 or more
+    EmptyCandidatesWithMinimum {
+        /// Name of the character type (e.g. "Uppercases", "Other characters at index 0")
+        class: String,
+        /// Minimum number of characters of the character type
+        minimum: u32,
+    },
+    /// The total minimum number of characters for all types does not fit in `u32`
+    MinimumCountOverflow,
+    /// The total minimum number of characters for all types exceeds the password length
+    MinimumExceedsLength {
+        /// Total minimum number of characters
+        total: u32,
+        /// Minimum number of uppercases
+        uppercase: u32,
+        /// Minimum number of lowercases
+        lowercase: u32,
+        /// Minimum number of numbers
+        number: u32,
+        /// Minimum number of symbols
+        symbol: u32,
+        /// Total minimum number of other characters
+        others: u32,
+        /// Length of the password
+        length: u32,
+    },
+    /// No candidates for the password
+    NoCandidates,
+    /// Control characters in the candidates
+    ControlCharacters(Vec<char>),
+    /// The password length is 0
+    ZeroLength,
+    /// The password length exceeds the length limit
+    LengthExceedsLimit {
+        /// Length of the password
+        length: u32,
+        /// Upper limit of the password length
+        limit: u32,
+    },
+    /// Not enough unique passwords could be generated
+    UniqueExhausted {
+        /// Number of passwords requested
+        requested: usize,
+        /// Number of unique passwords generated before giving up
+        generated: usize,
+    },
+    /// The maximum number of characters of a type is less than its minimum number
+    MaximumBelowMinimum {
+        /// Name of the character type (e.g. "Uppercases", "Other characters at index 0")
+        class: String,
+        /// Minimum number of characters of the character type
+        minimum: u32,
+        /// Maximum number of characters of the character type
+        maximum: u32,
+    },
+    /// Every character type has a maximum number of characters, and they add up to less than the password length
+    MaximumsBelowLength {
+        /// Total maximum number of characters
+        total: u64,
+        /// Length of the password
+        length: u32,
+    },
+    /// No candidates are left to replace the characters over the maximum numbers
+    MaximumUnsatisfiable,
+    /// More unique passwords are requested than can be generated
+    KeyspaceTooSmall {
+        /// Number of passwords requested
+        requested: usize,
+        /// Number of passwords that can be generated
+        keyspace: u128,
+    },
+    /// The number of weights of a character type differs from the number of its candidates
+    WeightsLengthMismatch {
+        /// Name of the character type (e.g. "Uppercases", "Other characters at index 0")
+        class: String,
+        /// Number of weights
+        weights: usize,
+        /// Number of candidates
+        candidates: usize,
+    },
+    /// The Luhn-valid segment is shorter than 2 digits or does not fit in the password
+    InvalidLuhnSegment {
+        /// Index of the first character of the segment
+        start: u32,
+        /// Number of digits in the segment
+        length: u32,
+        /// Length of the password
+        password_length: u32,
+    },
+    /// No ASCII digits in the number candidates for the Luhn-valid segment
+    NoDigitsForLuhn,
+    /// The Luhn-valid segment is combined with a setting that its digits would break
+    LuhnSegmentConflict(String),
+    /// No words in the wordlist of the passphrase
+    EmptyWordlist,
+    /// The number of words of the passphrase is 0
+    ZeroWords,
+    /// The same character cannot be kept from appearing twice in a row
+    ConsecutiveRepeatsUnsatisfiable,
+    /// Ascending or descending sequences of three characters cannot be kept out of the password
+    SequencesUnsatisfiable,
+    /// The password length exceeds the number of distinct candidates, but each character can be used only once
+    LengthExceedsDistinctCandidates {
+        /// Length of the password
+        length: u32,
+        /// Number of distinct candidates
+        distinct: usize,
+    },
+    /// The minimum number of characters of a type exceeds its distinct candidates, but each character can be used only once
+    MinimumExceedsDistinctCandidates {
+        /// Name of the character type (e.g. "Uppercases", "Other characters at index 0")
+        class: String,
+        /// Minimum number of characters of the character type
+        minimum: u32,
+        /// Number of distinct candidates available for the character type
+        distinct: usize,
+    },
+    /// No password length reaches the requested entropy with the candidates
+    EntropyUnreachable,
+    /// The pattern has a placeholder of a character type without candidates
+    EmptyCandidatesForPattern {
+        /// Name of the character type (e.g. "Uppercases")
+        class: String,
+        /// Placeholder of the character type in the pattern
+        placeholder: String,
+    },
+    /// The password cannot avoid starting with "0"
+    LeadingZeroUnavoidable,
+    /// A candidate to add is not exactly one grapheme
+    NotSingleGrapheme(String),
+    /// Password rules cannot be parsed or are not supported
+    InvalidRules(String),
+    /// The encoding of `pool_encoding` is not supported
+    UnsupportedEncoding(String),
+}
+
+impl fmt::Display for PasswordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PasswordError::EmptyCandidatesWithMinimum { class, minimum } => write!(
+                f,
+                "{} is empty, but the minimum number of characters is set to {}. Please set the minimum number of characters to 0.",
+                class, minimum
+            ),
+            PasswordError::MinimumCountOverflow => write!(
+                f,
+                "Minimum counts overflow. The total minimum number of characters exceeds {}. Please set smaller minimum counts.",
+                u32::MAX
+            ),
+            PasswordError::MinimumExceedsLength {
+                total,
+                uppercase,
+                lowercase,
+                number,
+                symbol,
+                others,
+                length,
+            } => write!(
+                f,
+                "The total minimum number of characters is greater than the password length. The total minimum number of characters is {} (uppercases: {}, lowercases: {}, numbers: {}, symbols: {}, others: {}), but the password length is {}",
+                total, uppercase, lowercase, number, symbol, others, length
+            ),
+            PasswordError::NoCandidates => write!(
+                f,
+                "No candidates for the password. Please set the candidates for the password."
+            ),
+            PasswordError::ControlCharacters(characters) => write!(
+                f,
+                "The candidates contain control characters: {}. Please remove them from the candidates or allow control characters.",
+                characters
+                    .iter()
+                    .map(|c| format!("U+{:04X}", *c as u32))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            PasswordError::ZeroLength => write!(
+                f,
+                "The password length is 0. Please set the password length to 1 or more."
+            ),
+            PasswordError::LengthExceedsLimit { length, limit } => write!(
+                f,
+                "The password length is {}, which exceeds the length limit {}. Please set the password length to {} or less, or raise the length limit.",
+                length, limit, limit
+            ),
+            PasswordError::UniqueExhausted {
+                requested,
+                generated,
+            } => write!(
+                f,
+                "Could not generate {} unique passwords; only {} were generated before the attempts ran out. The search space is exhausted, so please increase the password length or add more candidates.",
+                requested, generated
+            ),
+            PasswordError::MaximumBelowMinimum {
+                class,
+                minimum,
+                maximum,
+            } => write!(
+                f,
+                "The maximum number of characters of {} is {}, which is less than the minimum number of characters {}. Please set the maximum number of characters to {} or more.",
+                class, maximum, minimum, minimum
+            ),
+            PasswordError::MaximumsBelowLength { total, length } => write!(
+                f,
+                "The total maximum number of characters is {}, but the password length is {}. Please raise the maximum numbers of characters, or shorten the password.",
+                total, length
+            ),
+            PasswordError::MaximumUnsatisfiable => write!(
+                f,
+                "No candidates are left to replace the characters over the maximum numbers of characters. Please raise the maximum numbers of characters, or avoid candidates that belong to multiple character types."
+            ),
+            PasswordError::KeyspaceTooSmall {
+                requested,
+                keyspace,
+            } => write!(
+                f,
+                "Cannot produce {} unique passwords; the keyspace is only {}. Please increase the password length or add more candidates.",
+                requested, keyspace
+            ),
+            PasswordError::WeightsLengthMismatch {
+                class,
+                weights,
+                candidates,
+            } => write!(
+                f,
+                "{} have {} weights, but {} candidates. Please set one weight for each candidate, or no weights at all.",
+                class, weights, candidates
+            ),
+            PasswordError::InvalidLuhnSegment {
+                start,
+                length,
+                password_length,
+            } => write!(
+                f,
+                "The Luhn-valid segment of {} digits from index {} does not fit in the password of length {}. Please set the segment length to 2 or more and keep the segment within the password.",
+                length, start, password_length
+            ),
+            PasswordError::NoDigitsForLuhn => write!(
+                f,
+                "No ASCII digits in the number candidates for the Luhn-valid segment. Please add digits to the number candidates."
+            ),
+            PasswordError::LuhnSegmentConflict(setting) => write!(
+                f,
+                "The Luhn-valid segment cannot be combined with {}, because its digits, including the computed check digit, are inserted after the other settings are applied. Please remove the segment or {}.",
+                setting, setting
+            ),
+            PasswordError::EmptyWordlist => write!(
+                f,
+                "The wordlist is empty. Please use a wordlist with at least one word."
+            ),
+            PasswordError::ZeroWords => write!(
+                f,
+                "The number of words is 0. Please set the number of words to 1 or more."
+            ),
+            PasswordError::ConsecutiveRepeatsUnsatisfiable => write!(
+                f,
+                "The same character cannot be kept from appearing twice in a row. Please add more candidates, or lower the minimum numbers of characters of the types with few candidates."
+            ),
+            PasswordError::SequencesUnsatisfiable => write!(
+                f,
+                "Ascending or descending sequences of three characters cannot be kept out of the password. Please add more candidates, or lower the minimum numbers of characters of the types with few candidates."
+            ),
+            PasswordError::LengthExceedsDistinctCandidates { length, distinct } => write!(
+                f,
+                "The password length is {}, but there are only {} distinct candidates to use once each. Please shorten the password, or add more candidates.",
+                length, distinct
+            ),
+            PasswordError::MinimumExceedsDistinctCandidates {
+                class,
+                minimum,
+                distinct,
+            } => write!(
+                f,
+                "The minimum number of characters of {} is {}, but there are only {} distinct candidates to use once each. Please lower the minimum number of characters, or add more candidates.",
+                class, minimum, distinct
+            ),
+            PasswordError::EntropyUnreachable => write!(
+                f,
+                "No password length reaches the requested entropy with the candidates. Please add more candidates, or lower the entropy."
+            ),
+            PasswordError::EmptyCandidatesForPattern { class, placeholder } => write!(
+                f,
+                "The pattern has the placeholder \"{}\", but {} is empty. Please add candidates, or put \"\\\" before the placeholder to use it as it is.",
+                placeholder, class
+            ),
+            PasswordError::LeadingZeroUnavoidable => write!(
+                f,
+                "The password cannot avoid starting with \"0\". Please add candidates other than \"0\"."
+            ),
+            PasswordError::NotSingleGrapheme(grapheme) => write!(
+                f,
+                "\"{}\" is not a single grapheme. Please add candidates one grapheme at a time.",
+                grapheme
+            ),
+            PasswordError::InvalidRules(message) => write!(f, "{}", message),
+            PasswordError::UnsupportedEncoding(name) => write!(
+                f,
+                "Unsupported encoding: {}. Please specify an encoding label such as \"utf-8\" or \"shift_jis\".",
+                name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PasswordError {}
@@ -1,20 +1,323 @@
-use indexmap::IndexSet;
-use rand::prelude::*;
+mod apple_rules;
+mod confusables;
+mod error;
+mod passphrase;
+mod pattern;
+mod token;
 
-#[cfg(test)]
-// Use a fixed seed random number generator during tests to ensure reproducibility
+use encoding_rs::Encoding;
+pub use error::PasswordError;
+use indexmap::{IndexMap, IndexSet};
+pub use passphrase::Passphrase;
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
 use rand_chacha::ChaCha20Rng;
+#[cfg(feature = "secrecy")]
+use secrecy::SecretString;
+use std::fmt;
+use unicode_segmentation::UnicodeSegmentation;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroizing;
+
+/// Buffer of the characters of a password during generation
+///
+/// With the `zeroize` feature, the buffer is wiped when it is dropped, even on errors.
+/// Only the characters in the buffer when it is dropped are wiped, not the ones replaced before.
+#[cfg(feature = "zeroize")]
+type Buffer = Zeroizing<Vec<String>>;
+#[cfg(not(feature = "zeroize"))]
+type Buffer = Vec<String>;
+
+/// Variation selectors that choose the text (VS15) or emoji (VS16) presentation
+const VARIATION_SELECTORS: [char; 2] = ['\u{FE0E}', '\u{FE0F}'];
 
-#[derive(Debug, Clone)]
+/// Maximum number of collisions per password in [`PasswordMaker::generate_unique`]
+const UNIQUE_COLLISIONS_PER_PASSWORD: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Settings for characters used in the password
+///
+/// The default has no candidates, no minimum or maximum count, and no weights.
 pub struct Classifier {
     /// Candidate characters
     pub candidates: Vec<String>,
     /// Minimum number of characters to include
     pub minimum_count: u32,
+    /// Maximum number of characters to include (no limit if `None`)
+    ///
+    /// Excess characters are replaced with characters of the other types after the minimum counts are met.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub maximum_count: Option<u32>,
+    /// Relative weight of each candidate (all candidates are equally likely if empty)
+    ///
+    /// Must have the same length as `candidates`. A candidate with a weight of 0 is never used.
+    ///
+    /// A duplicated candidate is used only once with the weight of its first occurrence, so listing it twice does not make it more likely.
+    /// Use the weights to bias the selection on purpose.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub weights: Vec<u32>,
+}
+
+impl Classifier {
+    /// Return whether the grapheme is one of the candidates
+    ///
+    /// The grapheme is compared with each candidate as a whole,
+    /// so a multi-codepoint grapheme such as "👨‍👩‍👦" matches only the same sequence.
+    ///
+    /// The candidates are public and can be changed at any time, so nothing is cached and this is O(n).
+    ///
+    /// # Arguments
+    ///
+    /// * `grapheme` - Grapheme to look up
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::Classifier;
+    ///
+    /// let classifier = Classifier {
+    ///     candidates: vec!["a".to_string(), "b".to_string()],
+    ///     minimum_count: 1,
+    ///     ..Default::default()
+    /// };
+    /// assert!(classifier.contains("a"));
+    /// assert!(!classifier.contains("c"));
+    /// ```
+    pub fn contains(&self, grapheme: &str) -> bool {
+        self.candidates.iter().any(|c| c == grapheme)
+    }
+
+    /// Add a grapheme to the candidates
+    ///
+    /// The grapheme is not added if it is already a candidate, so the candidates stay free of duplicates.
+    /// If the candidates are weighted, the grapheme gets a weight of 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `grapheme` - Grapheme to add
+    ///
+    /// # Returns
+    ///
+    /// * Ok: Whether the grapheme was added (`false` if it was already a candidate)
+    /// * Err: Error message
+    ///
+    /// # Errors
+    ///
+    /// * `grapheme` is not exactly one grapheme (e.g. empty, or "ab")
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::Classifier;
+    ///
+    /// let mut classifier = Classifier::default();
+    /// assert_eq!(classifier.push_candidate("👨‍👩‍👦"), Ok(true));
+    /// assert_eq!(classifier.push_candidate("👨‍👩‍👦"), Ok(false));
+    /// assert!(classifier.push_candidate("ab").is_err());
+    /// ```
+    pub fn push_candidate(&mut self, grapheme: &str) -> Result<bool, PasswordError> {
+        if grapheme.graphemes(true).count() != 1 {
+            return Err(PasswordError::NotSingleGrapheme(grapheme.to_string()));
+        }
+
+        if self.contains(grapheme) {
+            return Ok(false);
+        }
+
+        self.candidates.push(grapheme.to_string());
+        if !self.weights.is_empty() {
+            self.weights.push(1);
+        }
+        Ok(true)
+    }
+
+    /// Remove a grapheme from the candidates
+    ///
+    /// All occurrences are removed together with their weights. Only candidates equal to the whole grapheme are removed,
+    /// so removing "👨" does not affect "👨‍👩‍👦".
+    ///
+    /// # Arguments
+    ///
+    /// * `grapheme` - Grapheme to remove
+    ///
+    /// # Returns
+    ///
+    /// * Whether the grapheme was a candidate
+    pub fn remove_candidate(&mut self, grapheme: &str) -> bool {
+        let len = self.candidates.len();
+        if self.weights.len() == len {
+            let mut candidates = self.candidates.iter();
+            self.weights
+                .retain(|_| candidates.next().is_some_and(|c| c != grapheme));
+        }
+        self.candidates.retain(|c| c != grapheme);
+        self.candidates.len() != len
+    }
+
+    /// Remove all candidates
+    ///
+    /// The minimum count is not changed. Set it to 0 as well unless candidates are added again,
+    /// otherwise the password generation fails.
+    pub fn clear_candidates(&mut self) {
+        self.candidates.clear();
+        self.weights.clear();
+    }
+
+    /// Return the candidates paired with their weights
+    ///
+    /// All weights are 1 if `weights` is empty.
+    ///
+    /// # Returns
+    ///
+    /// * Iterator of pairs of a candidate and its weight
+    fn weighted_candidates(&self) -> impl Iterator<Item = (&String, u32)> {
+        self.candidates
+            .iter()
+            .enumerate()
+            .map(|(index, candidate)| (candidate, self.weights.get(index).copied().unwrap_or(1)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Number of characters of each type in a password
+pub struct Composition {
+    /// Number of lowercases
+    pub lowercase: usize,
+    /// Number of uppercases
+    pub uppercase: usize,
+    /// Number of numbers
+    pub number: usize,
+    /// Number of symbols
+    pub symbol: usize,
+    /// Number of other characters for each settings, in the same order as `PasswordMaker::others`
+    pub others: Vec<usize>,
+    /// Number of whitespaces
+    pub whitespace: usize,
+    /// Number of characters that are not candidates
+    pub unclassified: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Entropy of a password split by character type
+///
+/// Each character of the password is shared among the types in proportion to how often each type is chosen,
+/// so the bits of all the types add up to [`PasswordMaker::entropy`].
+pub struct EntropyBreakdown {
+    /// Bits from lowercases
+    pub lowercase: f64,
+    /// Bits from uppercases
+    pub uppercase: f64,
+    /// Bits from numbers
+    pub number: f64,
+    /// Bits from symbols
+    pub symbol: f64,
+    /// Bits from other characters for each settings, in the same order as `PasswordMaker::others`
+    pub others: Vec<f64>,
+    /// Bits from whitespaces
+    pub whitespace: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Generated password and its properties
+pub struct GeneratedPassword {
+    /// Password
+    pub password: String,
+    /// Number of graphemes in the password
+    pub length: usize,
+    /// Number of characters of each type
+    pub composition: Composition,
+    /// Strength of the password in bits (see [`PasswordMaker::entropy`])
+    pub entropy: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Position of a digit segment that passes the Luhn check
+///
+/// The last digit of the segment is the check digit, as in card numbers.
+/// This is meant for realistic but fake test identifiers, not for real card numbers.
+pub struct LuhnSegment {
+    /// Index of the first character of the segment in the password
+    pub start: u32,
+    /// Number of digits in the segment, including the check digit (2 or more)
+    pub length: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+/// Unit in which the candidates of the other characters are split and counted
+///
+/// Emoji and other characters may consist of several Unicode scalar values:
+///
+/// | Input | `Grapheme` | `Scalar` |
+/// |-------|------------|----------|
+/// | `"😀"` | `"😀"` | `"😀"` |
+/// | `"👨‍👩‍👦"` (three emoji joined by zero width joiners) | `"👨‍👩‍👦"` | `"👨"`, `"\u{200D}"`, `"👩"`, `"\u{200D}"`, `"👦"` |
+/// | `"🇯🇵"` (two regional indicators) | `"🇯🇵"` | `"🇯"`, `"🇵"` |
+/// | `"é"` (`"e"` and a combining acute accent) | `"é"` | `"e"`, `"\u{301}"` |
+pub enum GraphemeUnit {
+    /// Extended grapheme clusters, i.e. characters as users see them
+    ///
+    /// Composed emoji, flags, and accented characters are used as a whole,
+    /// and the minimum count counts them as one character each.
+    #[default]
+    Grapheme,
+    /// Unicode scalar values
+    ///
+    /// Matches systems that count the password length in code points.
+    /// The parts of composed emoji and flags become separate candidates, so the password may contain stray zero width joiners
+    /// and combining marks, and adjacent parts may render as a different character.
+    Scalar,
+}
+
+impl GraphemeUnit {
+    /// Split a text into units
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - Text to split
+    ///
+    /// # Returns
+    ///
+    /// * Units of the text
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::GraphemeUnit;
+    ///
+    /// assert_eq!(GraphemeUnit::Grapheme.split("a👨‍👩‍👦"), vec!["a", "👨‍👩‍👦"]);
+    /// assert_eq!(
+    ///     GraphemeUnit::Scalar.split("a👨‍👩‍👦"),
+    ///     vec!["a", "👨", "\u{200D}", "👩", "\u{200D}", "👦"]
+    /// );
+    /// ```
+    pub fn split(self, text: &str) -> Vec<String> {
+        match self {
+            GraphemeUnit::Grapheme => text.graphemes(true).map(str::to_string).collect(),
+            GraphemeUnit::Scalar => text.chars().map(String::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+/// Character types from which `exclude_similar` removes the similar characters
+pub enum SimilarScope {
+    /// All the character types, including the other characters
+    #[default]
+    AllClasses,
+    /// Only the lowercases, uppercases, numbers, and symbols
+    ///
+    /// The other characters are usually chosen on purpose, so they are used as they are
+    /// even if they are in `similar_characters`.
+    StandardClassesOnly,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 /// Password generator
 ///
 /// You can specify the following for the generated password:
@@ -23,13 +326,55 @@ pub struct Classifier {
 /// - Whether to include whitespace
 /// - Candidates for uppercase, lowercase, numbers, symbols, and other characters
 /// - Minimum number of characters for each type
+///
+/// With the `serde` feature, the settings can be serialized and deserialized, e.g. to store them in a config file.
+/// Omitted fields take their default values.
+/// Deserialized settings are not validated, so call [`PasswordMaker::validate`] to check them before use.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct PasswordMaker {
     /// Length of the password
     pub length: u32,
-    /// Exclude similar characters ('i', 'l', '1', 'o', '0', 'O') from the password
+    /// Upper limit of the password length
+    ///
+    /// Guards against allocating a huge password by mistake. Raise it to generate longer passwords.
+    pub length_limit: u32,
+    /// Exclude similar characters (`similar_characters`) from the password
     pub exclude_similar: bool,
+    /// Characters excluded from the password when `exclude_similar` is enabled
+    ///
+    /// Which characters look alike depends on the font, so replace them as needed (e.g. add "B" and "8").
+    pub similar_characters: Vec<String>,
+    /// Character types from which `exclude_similar` removes the similar characters
+    pub exclude_similar_scope: SimilarScope,
+    /// Characters excluded from the password regardless of their character types
+    ///
+    /// Useful for sites that forbid some characters (e.g. `"` and `\`) without rewriting the candidates.
+    /// The excluded characters are not used to meet the minimum counts either.
+    pub exclude_characters: Vec<String>,
     /// Include whitespace in the candidate characters for the password
     pub include_whitespace_in_candidate: bool,
+    /// Skip the step that ensures the minimum number of characters for each type
+    ///
+    /// The password is a pure uniform sample from the candidates, so it may not meet the minimum counts.
+    /// This is intended for analyzing the distribution of the candidates, not for real passwords.
+    pub raw_sample: bool,
+    /// Allow control characters (e.g. tab, newline, NUL, DEL) in the candidates
+    ///
+    /// Control characters make passwords hard to type and can break newline or null separated output,
+    /// so the settings are rejected if the candidates contain them, unless this is enabled.
+    pub allow_control_characters: bool,
+    /// Remove the variation selectors (U+FE0E, U+FE0F) from the candidates and `exclude_characters`
+    ///
+    /// An emoji with a variation selector (e.g. "❤️") renders differently from its base form ("❤"),
+    /// which makes the password hard to type. When enabled, the base form is used as the candidate instead,
+    /// and a candidate that consists only of variation selectors is removed.
+    pub strip_variation_selectors: bool,
+    /// Encoding in which all the candidates must be representable (no restriction if `None`)
+    ///
+    /// The encoding is given by its label (e.g. "shift_jis"). The candidates that cannot be converted to the encoding
+    /// without loss (e.g. emoji for "shift_jis") are removed, so the password survives systems that store it in that encoding.
+    pub pool_encoding: Option<String>,
     /// Settings for lowercases
     pub lowercase: Classifier,
     /// Settings for uppercases
@@ -40,9 +385,202 @@ pub struct PasswordMaker {
     pub symbol: Classifier,
     /// Settings for other characters
     pub others: Vec<Classifier>,
+    /// Give each of the other characters settings the same probability regardless of its number of candidates
+    ///
+    /// By default, each candidate is equally likely, so a settings with many candidates is chosen more often.
+    /// When enabled, the probability of the other characters as a whole is unchanged,
+    /// but it is split equally among the settings, and then equally among the candidates of each settings.
+    pub others_equal_weight: bool,
+    /// Unit in which the candidates of the other characters are split and counted
+    ///
+    /// With [`GraphemeUnit::Scalar`], each candidate of the other characters is split into its scalar values,
+    /// which inherit its weight, and the minimum counts of the other characters count scalar values.
+    /// The other character types are not affected.
+    pub others_unit: GraphemeUnit,
+    /// Seed of the random number generator
+    ///
+    /// When set, `generate` always generates the same password for the same settings,
+    /// so the password can be regenerated later for auditing and testing.
+    /// Anyone who knows the seed can regenerate the password, so do not use it for real secrets.
+    pub seed: Option<u64>,
+    /// Digit segment of the password that passes the Luhn check (no segment if `None`)
+    ///
+    /// The digits other than the check digit are chosen from the ASCII digits of the number candidates.
+    /// The check digit is computed, so it can be a digit that is not a candidate.
+    /// The segment cannot be combined with `unique_characters`, `no_consecutive_repeats`, `exclude_sequences`,
+    /// `no_leading_zero`, or `exclude_similar`, which it could break.
+    pub luhn_segment: Option<LuhnSegment>,
+    /// Forbid the same character twice in a row (e.g. "aa" or "11")
+    ///
+    /// After the other constraints are applied, each character equal to the previous one is rewritten
+    /// with a candidate of the same character types, or swapped with another character if there is none,
+    /// so the minimum and maximum numbers of characters still hold.
+    /// The digits of the Luhn-valid segment are not rewritten, so they may repeat.
+    pub no_consecutive_repeats: bool,
+    /// Forbid ascending or descending sequences of three characters (e.g. "abc", "321", "XYZ")
+    ///
+    /// Only ASCII digits, lowercases, and uppercases form sequences, and a sequence does not mix them (e.g. "9ab" is allowed).
+    /// The characters are rewritten in the same way as `no_consecutive_repeats`.
+    /// The digits of the Luhn-valid segment are not rewritten, so they may form sequences.
+    pub exclude_sequences: bool,
+    /// Use each character at most once in the password
+    ///
+    /// The characters are sampled without replacement from [`PasswordMaker::effective_pool`],
+    /// so the length and the minimum number of characters of each type must fit in the distinct candidates.
+    /// The weights only exclude the candidates with a weight of 0, and `others_equal_weight` is ignored.
+    /// The digits of the Luhn-valid segment are not made unique.
+    pub unique_characters: bool,
+    /// Forbid "0" as the first character, e.g. for PINs that must not start with 0
+    ///
+    /// A leading "0" is rewritten in the same way as `no_consecutive_repeats`.
+    /// The digits of the Luhn-valid segment are not rewritten, so a segment at the start may begin with "0".
+    pub no_leading_zero: bool,
+    /// Pattern that the password follows (e.g. "Aa0!aaaa")
+    ///
+    /// See [`PasswordMaker::from_pattern`] for the syntax. When set, `length` and the constraints are ignored.
+    pub pattern: Option<String>,
 }
 
 impl PasswordMaker {
+    /// Create a password generator of numeric PINs
+    ///
+    /// Only the numbers "0" to "9" are candidates, without a minimum count,
+    /// and the other character types have no candidates.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - Number of digits
+    ///
+    /// # Returns
+    ///
+    /// * Password generator
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let mut password_maker = PasswordMaker::pin(6);
+    /// let pin = password_maker.generate().unwrap();
+    /// assert_eq!(pin.len(), 6);
+    /// assert!(pin.chars().all(|c| c.is_ascii_digit()));
+    /// ```
+    pub fn pin(length: u32) -> PasswordMaker {
+        let default = PasswordMaker::default();
+
+        PasswordMaker {
+            length,
+            uppercase: Classifier::default(),
+            lowercase: Classifier::default(),
+            number: Classifier {
+                minimum_count: 0,
+                ..default.number
+            },
+            symbol: Classifier::default(),
+            ..default
+        }
+    }
+
+    /// Create a password generator of strong passwords for general use
+    ///
+    /// The password has 24 characters of all the character types, at least one of each,
+    /// and the similar characters are excluded so that it can be typed by hand.
+    ///
+    /// # Returns
+    ///
+    /// * Password generator
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let mut password_maker = PasswordMaker::strong();
+    /// let password = password_maker.generate().unwrap();
+    /// assert_eq!(password.len(), 24);
+    /// assert!(!password.contains(['i', 'l', '1', 'o', '0', 'O']));
+    /// ```
+    pub fn strong() -> PasswordMaker {
+        PasswordMaker {
+            length: 24,
+            exclude_similar: true,
+            ..PasswordMaker::default()
+        }
+    }
+
+    /// Create a password generator of Wi-Fi passphrases
+    ///
+    /// The password has 20 characters without the similar characters, quotes, and backslash,
+    /// which are easy to mistype on TVs and game consoles, or break configuration files.
+    ///
+    /// # Returns
+    ///
+    /// * Password generator
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let mut password_maker = PasswordMaker::wifi();
+    /// let password = password_maker.generate().unwrap();
+    /// assert_eq!(password.len(), 20);
+    /// assert!(!password.contains(['"', '\'', '`', '\\']));
+    /// ```
+    pub fn wifi() -> PasswordMaker {
+        PasswordMaker {
+            length: 20,
+            exclude_similar: true,
+            exclude_characters: ["\"", "'", "`", "\\"]
+                .iter()
+                .map(|c| c.to_string())
+                .collect(),
+            ..PasswordMaker::default()
+        }
+    }
+
+    /// Create a password generator from Apple Password Rules
+    ///
+    /// Sites publish their password requirements in this syntax, e.g.
+    /// `required: upper; required: lower; allowed: digit, [-_]; minlength: 8; maxlength: 20;`.
+    /// See <https://developer.apple.com/password-rules/> for the syntax.
+    ///
+    /// - `required` rules set the minimum count of each of their classes to 1.
+    /// - `allowed` rules add candidates without a minimum count.
+    /// - Custom character sets such as `[-_]` become other characters. Put `]` last to include it, as in `[-_]]`.
+    /// - `special` and `ascii-printable` include space.
+    /// - If there are neither `required` nor `allowed` rules, all ASCII printable characters are allowed.
+    /// - The length is 16, raised to `minlength` and lowered to `maxlength`.
+    ///
+    /// # Arguments
+    ///
+    /// * `rules` - Password rules
+    ///
+    /// # Returns
+    ///
+    /// * Ok: Password generator
+    /// * Err: Error message
+    ///
+    /// # Errors
+    ///
+    /// * Unknown or unsupported rules (`max-consecutive`) and classes (`unicode`)
+    /// * Invalid numbers in `minlength` or `maxlength`
+    /// * `minlength` is greater than `maxlength`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let mut password_maker =
+    ///     PasswordMaker::from_apple_rules("required: upper; required: digit; maxlength: 12;").unwrap();
+    /// let password = password_maker.generate().unwrap();
+    /// assert_eq!(password.len(), 12);
+    /// ```
+    pub fn from_apple_rules(rules: &str) -> Result<PasswordMaker, PasswordError> {
+        apple_rules::parse(rules).map_err(PasswordError::InvalidRules)
+    }
+
     /// Generate a password
     ///
     /// Generates a password according to the settings of the password generator.
@@ -62,7 +600,9 @@ impl PasswordMaker {
     /// * No candidates for a character type, but the minimum number of characters is set to 1 or more
     /// * The total minimum number of characters for all types exceeds the password length
     /// * No candidates for the password
+    /// * Control characters in the candidates, unless they are allowed
     /// * The password length is 0
+    /// * The password length exceeds the length limit
     ///
     /// # Examples
     ///
@@ -74,668 +614,5294 @@ impl PasswordMaker {
     /// println!("{}", password);
     /// ```
     ///
-    pub fn generate(&mut self) -> Result<String, String> {
-        // Return an error if validation fails
-        self.validate()?;
-
-        let candidates = self.candidates();
-
-        let mut rng = Self::create_rng();
-
-        // 上書き処理があるので、String ではなく Vec<String> を使う
-        let mut password: Vec<String> = (0..self.length)
-            .map(|_| candidates.choose(&mut rng).unwrap().to_string())
-            .collect();
-
-        // Ensure the minimum number of characters is met
-        // To maintain randomness, overwrite random positions with characters that meet the minimum count
-        self.overwrite_to_meet_minimum_count(&mut password);
-
-        Ok(password.concat())
+    pub fn generate(&mut self) -> Result<String, PasswordError> {
+        let mut rng = self.create_rng();
+        self.generate_with_rng(&mut rng)
     }
 
-    /// Return a list of candidate characters for the password according to the settings of the password generator
+    /// Generate passwords lazily
+    ///
+    /// The iterator is endless, so take as many passwords as needed.
+    /// Each password is generated only when requested, so the memory use does not depend on the number of passwords.
+    /// All passwords share one random number generator, which is seeded from `seed` if it is set.
     ///
     /// # Returns
     ///
-    /// * List of candidate characters for the password
+    /// * Iterator of the results of [`PasswordMaker::generate`]
     ///
     /// # Examples
     ///
     /// ```
     /// use password_maker::PasswordMaker;
     ///
-    /// let password_maker = PasswordMaker::default();
-    /// let candidates = password_maker.candidates();
-    /// println!("{:?}", candidates);
+    /// let mut password_maker = PasswordMaker::default();
+    /// for password in password_maker.generate_iter().take(3) {
+    ///     println!("{}", password.unwrap());
+    /// }
     /// ```
-    pub fn candidates(&self) -> Vec<String> {
-        let mut candidates = Vec::new();
-        candidates.extend(self.lowercase.candidates.clone());
-        candidates.extend(self.uppercase.candidates.clone());
-        candidates.extend(self.number.candidates.clone());
-        candidates.extend(self.symbol.candidates.clone());
-        for classifier in &self.others {
-            candidates.extend(classifier.candidates.clone());
-        }
-
-        if self.include_whitespace_in_candidate {
-            candidates.push(" ".to_string());
-        }
-
-        if self.exclude_similar {
-            candidates.retain(|c| !matches!(c.as_str(), "i" | "l" | "1" | "o" | "0" | "O"));
-        }
-
-        candidates
+    pub fn generate_iter(&mut self) -> impl Iterator<Item = Result<String, PasswordError>> + '_ {
+        let mut rng = self.create_rng();
+        std::iter::repeat_with(move || self.generate_with_rng(&mut rng))
     }
 
-    /// Create a random number generator
+    /// Generate a password that is wiped from memory when dropped
     ///
-    /// During unit tests, return a fixed seed random number generator to ensure reproducibility
+    /// Requires the `zeroize` feature. Useful for long-running processes that generate many credentials.
     ///
-    /// Outside of unit tests, return a random number generator with a different seed for each thread
+    /// The returned password and the buffer of its characters during generation are wiped.
+    /// Characters replaced during generation (e.g. to meet the minimum counts) and the characters of `pattern`
+    /// are freed without being wiped, so some characters of the password may remain in freed memory.
     ///
     /// # Returns
     ///
-    /// * Random number generator
-    fn create_rng() -> Box<dyn RngCore> {
-        #[cfg(test)]
-        {
-            // Use a fixed seed during unit tests to ensure reproducibility
-            // StdRng may change with version upgrades, so use ChaCha20Rng during tests to ensure future reproducibility
-            Box::new(ChaCha20Rng::seed_from_u64(0))
-        }
-        #[cfg(not(test))]
-        {
-            // Use random numbers outside of unit tests
-            Box::new(rand::thread_rng())
-        }
+    /// * Ok: Password
+    /// * Err: Error
+    ///
+    /// # Errors
+    ///
+    /// The same as [`PasswordMaker::generate`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let mut password_maker = PasswordMaker::default();
+    /// let password = password_maker.generate_zeroizing().unwrap();
+    /// assert_eq!(password.chars().count(), 16);
+    /// ```
+    #[cfg(feature = "zeroize")]
+    pub fn generate_zeroizing(&mut self) -> Result<Zeroizing<String>, PasswordError> {
+        self.generate().map(Zeroizing::new)
     }
 
-    /// Validate the settings of the password generator
+    /// Generate a password as a secret that is not printed by accident
     ///
-    /// Checks:
-    /// - No candidates for a character type, but the minimum number of characters is set to 1 or more
-    /// - The total minimum number of characters for all types exceeds the password length
-    /// - No candidates for the password
-    /// - The password length is 0
-    fn validate(&self) -> Result<(), String> {
-        // Check if the minimum number of characters for each parameter is not violated
-        let classifier = [
-            // Capitalize the first letter for error messages
-            (&self.uppercase, "Uppercases"),
-            (&self.lowercase, "Lowercases"),
-            (&self.number, "Numbers"),
-            (&self.symbol, "Symbols"),
-        ];
+    /// Requires the `secrecy` feature. The password is not shown by [`Debug`](std::fmt::Debug),
+    /// has to be read with [`ExposeSecret::expose_secret`](secrecy::ExposeSecret::expose_secret),
+    /// and is wiped from memory when dropped.
+    ///
+    /// # Returns
+    ///
+    /// * Ok: Password
+    /// * Err: Error
+    ///
+    /// # Errors
+    ///
+    /// The same as [`PasswordMaker::generate`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    /// use secrecy::ExposeSecret;
+    ///
+    /// let mut password_maker = PasswordMaker::default();
+    /// let password = password_maker.generate_secret().unwrap();
+    /// assert_eq!(password.expose_secret().chars().count(), 16);
+    /// ```
+    #[cfg(feature = "secrecy")]
+    pub fn generate_secret(&mut self) -> Result<SecretString, PasswordError> {
+        self.generate().map(SecretString::from)
+    }
 
-        for (index, classify) in self.others.iter().enumerate() {
-            if classify.candidates.is_empty() && 0 < classify.minimum_count {
-                return Err(format!(
-                    "Other characters at index {} is empty, but the minimum number of characters is set to {}. Please set the minimum number of characters to 0.",
-                    index, classify.minimum_count
-                ));
-            }
-        }
+    /// Generate a password together with its properties
+    ///
+    /// Saves analyzing the password again to display its length, composition, and strength.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - Random number generator
+    ///
+    /// # Returns
+    ///
+    /// * Ok: Password and its properties
+    /// * Err: Error
+    ///
+    /// # Errors
+    ///
+    /// * The same errors as [`PasswordMaker::generate`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    /// use rand::rngs::OsRng;
+    ///
+    /// let mut password_maker = PasswordMaker::default();
+    /// let generated = password_maker.generate_analyzed(&mut OsRng).unwrap();
+    /// assert_eq!(generated.length, 16);
+    /// assert!(1 <= generated.composition.uppercase);
+    /// ```
+    pub fn generate_analyzed<R: RngCore + ?Sized>(
+        &mut self,
+        rng: &mut R,
+    ) -> Result<GeneratedPassword, PasswordError> {
+        let password = self.generate_with_rng(rng)?;
 
-        for (classify, name) in classifier.iter() {
-            if classify.candidates.is_empty() && 0 < classify.minimum_count {
-                return Err(format!(
-                    "{} is empty, but the minimum number of characters is set to {}. Please set the minimum number of characters to 0.",
-                    name, classify.minimum_count
-                ));
+        Ok(GeneratedPassword {
+            length: password.graphemes(true).count(),
+            composition: self.composition(&password),
+            entropy: self.entropy(),
+            password,
+        })
+    }
+
+    /// Count the characters of each type in a password
+    ///
+    /// A character that is a candidate of several types is counted for the first of
+    /// lowercases, uppercases, numbers, symbols, other characters, and whitespace.
+    /// The password is split into the units of `others_unit`.
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - Password
+    ///
+    /// # Returns
+    ///
+    /// * Number of characters of each type
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let password_maker = PasswordMaker::default();
+    /// let composition = password_maker.composition("aB3!x");
+    /// assert_eq!(composition.lowercase, 2);
+    /// assert_eq!(composition.uppercase, 1);
+    /// assert_eq!(composition.number, 1);
+    /// assert_eq!(composition.symbol, 1);
+    /// ```
+    pub fn composition(&self, password: &str) -> Composition {
+        let normalized;
+        let maker = if !self.needs_normalizing() {
+            self
+        } else {
+            normalized = self.normalized();
+            &normalized
+        };
+        let mut composition = Composition {
+            others: vec![0; self.others.len()],
+            ..Composition::default()
+        };
+
+        for unit in &self.others_unit.split(password) {
+            if maker.lowercase.contains(unit) {
+                composition.lowercase += 1;
+            } else if maker.uppercase.contains(unit) {
+                composition.uppercase += 1;
+            } else if maker.number.contains(unit) {
+                composition.number += 1;
+            } else if maker.symbol.contains(unit) {
+                composition.symbol += 1;
+            } else if let Some(index) = maker.others.iter().position(|c| c.contains(unit)) {
+                composition.others[index] += 1;
+            } else if self.include_whitespace_in_candidate && unit == " " {
+                composition.whitespace += 1;
+            } else {
+                composition.unclassified += 1;
             }
         }
 
-        // Check if the total minimum number of characters is not violated
-        let total_min = self.lowercase.minimum_count
-            + self.uppercase.minimum_count
-            + self.number.minimum_count
-            + self.symbol.minimum_count
-            + self.others.iter().map(|c| c.minimum_count).sum::<u32>();
+        composition
+    }
 
-        if self.length < total_min {
-            return Err(format!("The total minimum number of characters is greater than the password length. The total minimum number of characters is {}, but the password length is {}", total_min, self.length));
+    /// Generate unique passwords
+    ///
+    /// Passwords that collide with already generated ones are regenerated.
+    /// If the candidates and length allow fewer passwords than requested, collisions never stop,
+    /// so an error is returned when a password collides 100 times.
+    /// Use [`PasswordMaker::generate_unique_with`] to generate the passwords in another way.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - Number of passwords
+    ///
+    /// # Returns
+    ///
+    /// * Ok: Unique passwords in the order they were generated
+    /// * Err: Error
+    ///
+    /// # Errors
+    ///
+    /// * The same errors as [`PasswordMaker::generate`]
+    /// * `count` exceeds [`PasswordMaker::keyspace`], checked before generating any password
+    /// * `count` unique passwords could not be generated within the attempts
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let mut password_maker = PasswordMaker::default();
+    /// let passwords = password_maker.generate_unique(10).unwrap();
+    /// assert_eq!(passwords.len(), 10);
+    /// ```
+    pub fn generate_unique(&mut self, count: usize) -> Result<Vec<String>, PasswordError> {
+        if 0 < count {
+            self.validate()?;
+            self.check_keyspace(count)?;
         }
 
-        // Check if there are candidates for the password
-        if self.candidates().is_empty() {
-            return Err(
-                "No candidates for the password. Please set the candidates for the password."
-                    .to_string(),
-            );
-        }
+        let mut rng = self.create_rng();
+        self.generate_unique_with(count, UNIQUE_COLLISIONS_PER_PASSWORD, |maker, _| {
+            maker.generate_with_rng(&mut rng)
+        })
+    }
 
-        // Check if the password length is 0
-        if self.length == 0 {
-            return Err(
-                "The password length is 0. Please set the password length to 1 or more."
-                    .to_string(),
-            );
+    /// Generate unique passwords with a function that generates each password
+    ///
+    /// The function is called with the index of the password to generate, and called again with the same index
+    /// while its password collides with an already generated one.
+    /// The settings are not validated, so validate them and check [`PasswordMaker::check_keyspace`] beforehand.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - Number of passwords
+    /// * `collisions_per_password` - Number of collisions of each password at which an error is returned
+    /// * `generate` - Function that generates the password at an index
+    ///
+    /// # Returns
+    ///
+    /// * Ok: Unique passwords in the order they were generated
+    /// * Err: Error
+    ///
+    /// # Errors
+    ///
+    /// * The errors of `generate`
+    /// * A password collides `collisions_per_password` times
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::{PasswordError, PasswordMaker};
+    ///
+    /// let mut password_maker = PasswordMaker::default();
+    /// let mut rng = password_maker.create_rng();
+    /// let passwords = password_maker
+    ///     .generate_unique_with(10, 100, |maker, _| {
+    ///         // Keep generating until the password starts with a lowercase
+    ///         loop {
+    ///             let password = maker.generate_with_rng(&mut rng)?;
+    ///             if password.starts_with(|c: char| c.is_ascii_lowercase()) {
+    ///                 return Ok::<_, PasswordError>(password);
+    ///             }
+    ///         }
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(passwords.len(), 10);
+    /// ```
+    pub fn generate_unique_with<E: From<PasswordError>>(
+        &mut self,
+        count: usize,
+        collisions_per_password: usize,
+        mut generate: impl FnMut(&mut PasswordMaker, usize) -> Result<String, E>,
+    ) -> Result<Vec<String>, E> {
+        let mut passwords = IndexSet::new();
+
+        while passwords.len() < count {
+            let index = passwords.len();
+            let mut collisions = 0;
+            while !passwords.insert(generate(self, index)?) {
+                collisions += 1;
+                if collisions >= collisions_per_password {
+                    return Err(PasswordError::UniqueExhausted {
+                        requested: count,
+                        generated: index,
+                    }
+                    .into());
+                }
+            }
         }
 
-        Ok(())
+        Ok(passwords.into_iter().collect())
     }
 
-    /// Update the password string to meet the minimum number of characters for each type
+    /// Generate a password reproducibly from a seed
     ///
-    /// To maintain randomness, overwrite random positions with characters that meet the minimum count
+    /// The same settings and seed always generate the same password, also when the candidates are weighted
+    /// (e.g. with `others_equal_weight`), because the candidates and their weights are always
+    /// in the order of [`PasswordMaker::candidates`].
+    ///
+    /// Anyone who knows the seed can regenerate the password, so do not use it for real secrets.
     ///
     /// # Arguments
     ///
-    /// * `password` - Password
-    fn overwrite_to_meet_minimum_count(&self, password: &mut [String]) {
-        // Number of characters to overwrite
-        let overwrite_count = std::cmp::min(
-            self.length,
-            self.lowercase.minimum_count
-                + self.uppercase.minimum_count
-                + self.number.minimum_count
-                + self.symbol.minimum_count
-                + self.others.iter().map(|c| c.minimum_count).sum::<u32>(),
-        );
+    /// * `seed` - Seed of the random number generator
+    ///
+    /// # Returns
+    ///
+    /// * Ok: Password
+    /// * Err: Error message
+    ///
+    /// # Errors
+    ///
+    /// Same as [`PasswordMaker::generate`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let mut password_maker = PasswordMaker::default();
+    /// let password = password_maker.generate_weighted_seeded(42).unwrap();
+    /// assert_eq!(password, password_maker.generate_weighted_seeded(42).unwrap());
+    /// ```
+    pub fn generate_weighted_seeded(&mut self, seed: u64) -> Result<String, PasswordError> {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        self.generate_with_rng(&mut rng)
+    }
 
-        // Randomly select characters to overwrite
-        let mut overwrite_chars =
-            self.unique_random_numbers(overwrite_count as usize, 0..password.len() as u32);
+    /// Generate a password with the given random number generator
+    ///
+    /// All random numbers used to generate the password are drawn from `rng`,
+    /// so a seeded generator makes the password reproducible, and any CSPRNG can be used as the source of randomness.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - Random number generator
+    ///
+    /// # Returns
+    ///
+    /// * Ok: Password
+    /// * Err: Error message
+    ///
+    /// # Errors
+    ///
+    /// Same as [`PasswordMaker::generate`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    /// use rand::rngs::OsRng;
+    ///
+    /// let mut password_maker = PasswordMaker::default();
+    /// let password = password_maker.generate_with_rng(&mut OsRng).unwrap();
+    /// println!("{}", password);
+    /// ```
+    pub fn generate_with_rng<R: RngCore + ?Sized>(
+        &mut self,
+        rng: &mut R,
+    ) -> Result<String, PasswordError> {
+        if self.needs_normalizing() {
+            // The normalized settings no longer have the encoding to check
+            self.pool_encoding()?;
+            return self.normalized().generate_with_rng(rng);
+        }
 
-        // Update each character type in order (the order can be changed without affecting functionality)
-        let mut classifier = vec![&self.uppercase, &self.lowercase, &self.number, &self.symbol];
-        for classify in &self.others {
-            classifier.push(classify);
+        // Return an error if validation fails
+        self.validate()?;
+
+        if let Some(pattern) = &self.pattern {
+            return Ok(self.fill_pattern(pattern, rng));
         }
 
-        for classify in classifier.iter() {
-            self.replace_characters(
-                password,
-                classify,
-                overwrite_chars
-                    .drain(0..classify.minimum_count as usize)
-                    .map(|x| x as usize)
-                    .collect(),
-            );
+        let password = match self.luhn_segment.clone() {
+            Some(segment) => self.characters_with_luhn_segment(&segment, rng)?,
+            None => self.characters(rng)?,
+        };
+
+        Ok(password.concat())
+    }
+
+    /// Generate the characters of a validated password
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - Random number generator
+    ///
+    /// # Returns
+    ///
+    /// * Ok: Characters of the password
+    /// * Err: Error
+    fn characters<R: RngCore + ?Sized>(&mut self, rng: &mut R) -> Result<Buffer, PasswordError> {
+        if self.unique_characters {
+            let mut password = Buffer::from(self.sample_unique_characters(rng)?);
+            // The counts are met by the sampling, and the rewriting keeps the characters unique
+            if self.no_consecutive_repeats || self.exclude_sequences || self.no_leading_zero {
+                self.rewrite_forbidden_runs(&mut password, rng)?;
+            }
+            return Ok(password);
         }
+
+        let candidates = self.effective_pool();
+
+        // 上書き処理があるので、String ではなく Vec<String> を使う
+        let password: Vec<String> = if self.others_equal_weight || self.is_weighted() {
+            // validate() ensures there are candidates, so the weights are not empty and all positive
+            let distribution = WeightedIndex::new(self.candidate_weights()).unwrap();
+            (0..self.length)
+                .map(|_| candidates[distribution.sample(rng)].clone())
+                .collect()
+        } else {
+            (0..self.length)
+                .map(|_| candidates.choose(rng).unwrap().to_string())
+                .collect()
+        };
+        let mut password = Buffer::from(password);
+        self.apply_constraints(&mut password, rng)?;
+
+        Ok(password)
     }
 
-    /// Overwrite characters in the password string
+    /// Sample distinct characters for a validated password
     ///
-    /// For example, if the password is "abcde" and overwrite_indexes is \[3, 1, 4\], it becomes "aXcXXe"
-    /// (X is a character randomly chosen from the classifier candidates)
+    /// The minimum number of characters of each type is taken first, and the rest is taken from the remaining candidates
+    /// without exceeding the maximum numbers. The characters are then shuffled.
     ///
     /// # Arguments
     ///
-    /// * `password` - Password
-    /// * `classifier` - Character type to replace
-    /// * `overwrite_indexes` - Indexes of characters to replace
+    /// * `rng` - Random number generator
     ///
-    /// # Panics
+    /// # Returns
     ///
-    /// * If the index of an element in overwrite_indexes is greater than the number of characters in the password
-    fn replace_characters(
+    /// * Ok: Characters of the password
+    /// * Err: Error
+    ///
+    /// # Errors
+    ///
+    /// * A type shares candidates with another type and not enough distinct candidates are left for its minimum count
+    /// * Not enough distinct candidates are left within the maximum numbers
+    fn sample_unique_characters<R: Rng + ?Sized>(
         &self,
-        password: &mut [String],
-        classifier: &Classifier,
-        overwrite_indexes: Vec<usize>,
-    ) {
-        let mut rng = Self::create_rng();
-        for index in overwrite_indexes {
-            // ここはユーザーの入力ミスなどで index が password.len() 以上になることはなく、
-            // なった場合はプログラムのバグなので panic しても問題ない
-            if password.len() <= index {
-                panic!(
-                    "Index out of range: index {} is greater than or equal to password length {}",
-                    index,
-                    password.len()
-                );
+        rng: &mut R,
+    ) -> Result<Vec<String>, PasswordError> {
+        let mut named = vec![
+            (&self.uppercase, "Uppercases".to_string()),
+            (&self.lowercase, "Lowercases".to_string()),
+            (&self.number, "Numbers".to_string()),
+            (&self.symbol, "Symbols".to_string()),
+        ];
+        for (index, classify) in self.others.iter().enumerate() {
+            named.push((classify, format!("Other characters at index {}", index)));
+        }
+
+        let mut pool = self.effective_pool();
+        pool.shuffle(rng);
+        let mut password: Vec<String> = Vec::with_capacity(self.length as usize);
+
+        if !self.raw_sample {
+            for (classify, name) in &named {
+                let minimum = classify.minimum_count as usize;
+                let mut taken = 0;
+                pool.retain(|candidate| {
+                    if taken < minimum && classify.contains(candidate) {
+                        taken += 1;
+                        password.push(candidate.clone());
+                        false
+                    } else {
+                        true
+                    }
+                });
+                if taken < minimum {
+                    return Err(PasswordError::MinimumExceedsDistinctCandidates {
+                        class: name.clone(),
+                        minimum: classify.minimum_count,
+                        distinct: taken,
+                    });
+                }
             }
+        }
 
-            let overwrite_char = classifier.candidates.choose(&mut rng).unwrap().clone();
-            password[index] = overwrite_char;
+        let mut counts: Vec<usize> = named
+            .iter()
+            .map(|(classify, _)| password.iter().filter(|c| classify.contains(c)).count())
+            .collect();
+        for candidate in pool {
+            if password.len() == self.length as usize {
+                break;
+            }
+            let within_maximum = self.raw_sample
+                || named.iter().zip(&counts).all(|((classify, _), &count)| {
+                    !classify.contains(&candidate)
+                        || classify
+                            .maximum_count
+                            .is_none_or(|maximum| count < maximum as usize)
+                });
+            if within_maximum {
+                for ((classify, _), count) in named.iter().zip(counts.iter_mut()) {
+                    if classify.contains(&candidate) {
+                        *count += 1;
+                    }
+                }
+                password.push(candidate);
+            }
         }
+        if password.len() < self.length as usize {
+            return Err(PasswordError::MaximumUnsatisfiable);
+        }
+
+        password.shuffle(rng);
+        Ok(password)
     }
 
-    /// Generate unique random numbers
-    /// The generated values are between 0 and max (exclusive)
+    /// Rewrite the randomly filled characters to meet the constraints of the settings
     ///
     /// # Arguments
     ///
-    /// * count: Number of random numbers to generate
-    /// * max: Maximum value of the generated random numbers
-    fn unique_random_numbers(&self, count: usize, range: std::ops::Range<u32>) -> Vec<u32> {
-        let mut rng = Self::create_rng();
-        let mut numbers = IndexSet::new();
+    /// * `password` - Characters of the password
+    /// * `rng` - Random number generator
+    ///
+    /// # Returns
+    ///
+    /// * Ok: The constraints are met
+    /// * Err: Error
+    fn apply_constraints<R: RngCore + ?Sized>(
+        &self,
+        password: &mut [String],
+        rng: &mut R,
+    ) -> Result<(), PasswordError> {
+        // Ensure the minimum number of characters is met
+        // To maintain randomness, overwrite random positions with characters that meet the minimum count
+        if !self.raw_sample {
+            self.overwrite_to_meet_minimum_count(password, rng);
+            self.overwrite_to_meet_maximum_count(password, rng)?;
+        }
 
-        while numbers.len() < count {
-            let num = rng.gen_range(range.clone());
-            numbers.insert(num);
+        // Rewrite the forbidden runs last so that the other passes do not reintroduce them
+        if self.no_consecutive_repeats || self.exclude_sequences || self.no_leading_zero {
+            self.rewrite_forbidden_runs(password, rng)?;
         }
 
-        numbers.into_iter().collect()
+        Ok(())
     }
-}
 
-impl Default for PasswordMaker {
-    /// Create a password generator with default settings
+    /// Generate a password whose characters are chosen by the caller instead of a random number generator
     ///
-    /// The default settings are as follows:
-    /// - length: 16
-    /// - exclude_similar: false
-    /// - include_whitespace_in_candidate: false
-    /// - lowercase_letters
-    ///   - candidates: a-z
-    ///   - min: 1
-    /// - uppercase_letters
-    ///   - candidates: A-Z
-    ///   - min: 1
-    /// - numbers:
-    ///   - candidates: 0-9
-    ///   - min: 1
-    /// - symbols:
-    ///   - candidates: ! " # $ % & ' ( ) * + , - . / : ; < = > ? @ \[ \ \] ^ _ \` { | } ~
-    ///   - min: 1
-    /// - other_characters:
-    ///   - candidates: None
-    ///   - min: 0
-    fn default() -> Self {
+    /// This is a testing API to exercise the constraints with a specific sequence of characters,
+    /// e.g. forcing repeats or a missing character type. Do not use it for real passwords.
+    ///
+    /// `picker` is called for each position with the candidates, in the order of [`PasswordMaker::candidates`],
+    /// and returns the index of the candidate to place there.
+    /// The constraints are then applied as in [`PasswordMaker::generate`],
+    /// with a random number generator of a fixed seed, so the result depends only on the picker.
+    /// `luhn_segment` is not inserted, the characters are not made unique by `unique_characters`, and `pattern` is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `picker` - Function that returns the index of the candidate for each position
+    ///
+    /// # Returns
+    ///
+    /// * Ok: Password
+    /// * Err: Error
+    ///
+    /// # Errors
+    ///
+    /// Same as [`PasswordMaker::generate`]
+    ///
+    /// # Panics
+    ///
+    /// * If the picker returns an index out of the candidates
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let password_maker = PasswordMaker {
+    ///     raw_sample: true,
+    ///     ..PasswordMaker::default()
+    /// };
+    /// // Always pick the first candidate
+    /// assert_eq!(password_maker.fill_deterministic(|_| 0).unwrap(), "a".repeat(16));
+    /// ```
+    pub fn fill_deterministic(
+        &self,
+        mut picker: impl FnMut(&[String]) -> usize,
+    ) -> Result<String, PasswordError> {
+        if self.needs_normalizing() {
+            self.pool_encoding()?;
+            return self.normalized().fill_deterministic(picker);
+        }
+
+        self.validate()?;
+
+        let candidates = self.effective_pool();
+        let mut password: Vec<String> = (0..self.length)
+            .map(|_| {
+                let index = picker(&candidates);
+                candidates
+                    .get(index)
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "Index out of range: the picker returned {} for {} candidates",
+                            index,
+                            candidates.len()
+                        )
+                    })
+                    .clone()
+            })
+            .collect();
+
+        self.apply_constraints(&mut password, &mut ChaCha20Rng::seed_from_u64(0))?;
+
+        Ok(password.concat())
+    }
+
+    /// Generate the characters of a validated password with a Luhn-valid segment
+    ///
+    /// The rest of the password is generated as a shorter password, and the segment is inserted into it.
+    /// The digits of the segment count toward the minimum and maximum numbers of numbers.
+    ///
+    /// # Arguments
+    ///
+    /// * `segment` - Position and length of the segment
+    /// * `rng` - Random number generator
+    ///
+    /// # Returns
+    ///
+    /// * Ok: Characters of the password
+    /// * Err: Error
+    fn characters_with_luhn_segment<R: RngCore + ?Sized>(
+        &mut self,
+        segment: &LuhnSegment,
+        rng: &mut R,
+    ) -> Result<Buffer, PasswordError> {
+        let mut rest = PasswordMaker {
+            length: self.length - segment.length,
+            number: Classifier {
+                minimum_count: self.number.minimum_count.saturating_sub(segment.length),
+                // validate() ensures the maximum is not less than the segment length
+                maximum_count: self.number.maximum_count.map(|m| m - segment.length),
+                ..self.number.clone()
+            },
+            luhn_segment: None,
+            ..self.clone()
+        };
+        let mut password = if rest.length == 0 {
+            Buffer::from(Vec::new())
+        } else {
+            rest.validate()?;
+            rest.characters(rng)?
+        };
+
+        // validate() ensures there is at least one digit
+        let digits: Vec<u32> = self.luhn_digits().map(|c| c.parse().unwrap()).collect();
+        let mut segment_digits: Vec<u32> = (1..segment.length)
+            .map(|_| *digits.choose(rng).unwrap())
+            .collect();
+        segment_digits.push(luhn_check_digit(&segment_digits));
+
+        let start = segment.start as usize;
+        password.splice(start..start, segment_digits.iter().map(|d| d.to_string()));
+
+        Ok(password)
+    }
+
+    /// Return the number candidates that can be used in a Luhn-valid segment
+    ///
+    /// Only the ASCII digits that are not excluded from the candidates are used.
+    fn luhn_digits(&self) -> impl Iterator<Item = &String> {
+        self.number
+            .weighted_candidates()
+            .filter(|(c, weight)| {
+                0 < *weight
+                    && !self.is_excluded(c, false)
+                    && c.len() == 1
+                    && c.bytes().all(|b| b.is_ascii_digit())
+            })
+            .map(|(c, _)| c)
+    }
+
+    /// Return a list of candidate characters for the password according to the settings of the password generator
+    ///
+    /// A character in the candidates of several character types (e.g. "-" in the symbols and the other characters)
+    /// is still one character, so it appears only once, at its first occurrence, and keeps the weight of that occurrence.
+    /// Use [`PasswordMaker::duplicate_candidates`] to find such characters.
+    ///
+    /// # Returns
+    ///
+    /// * List of candidate characters for the password
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let password_maker = PasswordMaker::default();
+    /// let candidates = password_maker.candidates();
+    /// println!("{:?}", candidates);
+    /// ```
+    pub fn candidates(&self) -> Vec<String> {
+        if self.needs_normalizing() {
+            return self.normalized().candidates();
+        }
+
+        self.grouped_candidates()
+            .into_iter()
+            .map(|(candidate, _, _)| candidate)
+            .collect()
+    }
+
+    /// Return whether the candidates must be normalized before use (see [`PasswordMaker::normalized`])
+    fn needs_normalizing(&self) -> bool {
+        self.others_unit != GraphemeUnit::Grapheme
+            || self.strip_variation_selectors
+            || self.pool_encoding.is_some()
+    }
+
+    /// Return the settings with the candidates in the form in which they are used
+    ///
+    /// The candidates of the other characters are split into `others_unit`,
+    /// the variation selectors are removed with `strip_variation_selectors`,
+    /// and the candidates that `pool_encoding` cannot represent are removed. The weights follow their candidates.
+    /// An unsupported encoding removes nothing, since [`PasswordMaker::validate`] reports it.
+    ///
+    /// The returned settings need no normalizing, so they are used as they are.
+    fn normalized(&self) -> PasswordMaker {
+        let encoding = self.pool_encoding().ok().flatten();
+        let strip = |candidate: &str| {
+            if self.strip_variation_selectors {
+                candidate.replace(VARIATION_SELECTORS, "")
+            } else {
+                candidate.to_string()
+            }
+        };
+        let normalize = |classifier: &Classifier, unit: Option<GraphemeUnit>| {
+            let (candidates, weights) = classifier
+                .weighted_candidates()
+                .flat_map(|(candidate, weight)| {
+                    let units = match unit {
+                        Some(unit) => unit.split(candidate),
+                        None => vec![candidate.clone()],
+                    };
+                    units.into_iter().map(move |unit| (unit, weight))
+                })
+                .map(|(candidate, weight)| (strip(&candidate), weight))
+                .filter(|(candidate, _)| {
+                    !candidate.is_empty()
+                        && encoding.is_none_or(|encoding| !encoding.encode(candidate).2)
+                })
+                .unzip::<_, _, Vec<_>, Vec<_>>();
+            Classifier {
+                candidates,
+                weights: if classifier.weights.is_empty() {
+                    vec![]
+                } else {
+                    weights
+                },
+                ..classifier.clone()
+            }
+        };
+
         PasswordMaker {
+            lowercase: normalize(&self.lowercase, None),
+            uppercase: normalize(&self.uppercase, None),
+            number: normalize(&self.number, None),
+            symbol: normalize(&self.symbol, None),
+            others: self
+                .others
+                .iter()
+                .map(|classifier| normalize(classifier, Some(self.others_unit)))
+                .collect(),
+            exclude_characters: self
+                .exclude_characters
+                .iter()
+                .map(|c| strip(c))
+                .filter(|c| !c.is_empty())
+                .collect(),
+            others_unit: GraphemeUnit::Grapheme,
+            strip_variation_selectors: false,
+            pool_encoding: None,
+            ..self.clone()
+        }
+    }
+
+    /// Return the encoding of `pool_encoding`
+    ///
+    /// # Returns
+    ///
+    /// * Ok: Encoding (`None` if `pool_encoding` is not set)
+    /// * Err: Error
+    ///
+    /// # Errors
+    ///
+    /// * The encoding is not supported
+    fn pool_encoding(&self) -> Result<Option<&'static Encoding>, PasswordError> {
+        self.pool_encoding
+            .as_ref()
+            .map(|name| {
+                Encoding::for_label_no_replacement(name.as_bytes())
+                    .ok_or_else(|| PasswordError::UnsupportedEncoding(name.clone()))
+            })
+            .transpose()
+    }
+
+    /// Return the candidate characters together with the index of the other characters settings they come from
+    ///
+    /// The order is the same as [`PasswordMaker::candidates`].
+    ///
+    /// # Returns
+    ///
+    /// * List of triples of a candidate, the index in `others` (`None` if it is not from `others`), and its weight
+    fn grouped_candidates(&self) -> Vec<(String, Option<usize>, u32)> {
+        let mut candidates = Vec::new();
+        for classifier in [&self.lowercase, &self.uppercase, &self.number, &self.symbol] {
+            candidates.extend(
+                classifier
+                    .weighted_candidates()
+                    .map(|(c, weight)| (c.clone(), None, weight)),
+            );
+        }
+        for (index, classifier) in self.others.iter().enumerate() {
+            candidates.extend(
+                classifier
+                    .weighted_candidates()
+                    .map(|(c, weight)| (c.clone(), Some(index), weight)),
+            );
+        }
+
+        if self.include_whitespace_in_candidate {
+            candidates.push((" ".to_string(), None, 1));
+        }
+
+        // A candidate with a weight of 0 is never used
+        candidates
+            .retain(|(c, group, weight)| 0 < *weight && !self.is_excluded(c, group.is_some()));
+
+        // A duplicate candidate would be chosen more often than the others
+        let mut seen = IndexSet::new();
+        candidates.retain(|(c, _, _)| seen.insert(c.clone()));
+
+        candidates
+    }
+
+    /// Return the distinct characters that can appear in the password
+    ///
+    /// This is the pool after all filters, from which the password is generated.
+    /// [`PasswordMaker::entropy`], [`PasswordMaker::keyspace`], and [`PasswordMaker::validate`] use the same pool,
+    /// and [`PasswordMaker::validate`] rejects it if it contains control characters, unless `allow_control_characters` is set.
+    /// The filters are applied in the following order:
+    /// 1. The candidates of the other characters are split into `others_unit`
+    /// 2. The variation selectors are removed if `strip_variation_selectors` is set
+    /// 3. The candidates that cannot be represented in `pool_encoding` are removed
+    /// 4. The candidates of all character types are joined (lowercase, uppercase, number, symbol, and others)
+    /// 5. The whitespace is added if `include_whitespace_in_candidate` is set
+    /// 6. The similar characters, `exclude_characters`, and the candidates with a weight of 0 are removed
+    /// 7. Duplicates are removed, keeping the first occurrence
+    ///
+    /// Without `pattern`, the characters are the same as [`PasswordMaker::candidates`].
+    /// With `pattern`, they are the candidates of the character types of the placeholders.
+    ///
+    /// # Returns
+    ///
+    /// * List of distinct characters in the order of [`PasswordMaker::candidates`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let password_maker = PasswordMaker {
+    ///     exclude_similar: true,
+    ///     include_whitespace_in_candidate: true,
+    ///     ..PasswordMaker::default()
+    /// };
+    /// let pool = password_maker.effective_pool();
+    /// assert!(pool.contains(&" ".to_string()));
+    /// assert!(!pool.contains(&"0".to_string()));
+    /// assert_eq!(pool.len(), 94 + 1 - 6);
+    /// ```
+    pub fn effective_pool(&self) -> Vec<String> {
+        if self.needs_normalizing() {
+            return self.normalized().effective_pool();
+        }
+        if let Some(pattern) = &self.pattern {
+            return self.pattern_pool(pattern);
+        }
+
+        self.candidates()
+    }
+
+    /// Return the characters in the candidates of more than one character type
+    ///
+    /// Such a character is used as one candidate, so it usually means a mistake in the settings,
+    /// e.g. "-" in both the symbols and the other characters.
+    /// The similar characters, `exclude_characters`, and the candidates with a weight of 0 are not reported.
+    ///
+    /// # Returns
+    ///
+    /// * List of the characters in the order of [`PasswordMaker::candidates`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::{Classifier, PasswordMaker};
+    ///
+    /// let password_maker = PasswordMaker {
+    ///     others: vec![Classifier {
+    ///         candidates: vec!["-".to_string(), "😺".to_string()],
+    ///         minimum_count: 0,
+    ///         ..Default::default()
+    ///     }],
+    ///     ..PasswordMaker::default()
+    /// };
+    /// assert_eq!(password_maker.duplicate_candidates(), vec!["-".to_string()]);
+    /// ```
+    pub fn duplicate_candidates(&self) -> Vec<String> {
+        if self.needs_normalizing() {
+            return self.normalized().duplicate_candidates();
+        }
+
+        // Number of the character types that have each candidate
+        let mut counts: IndexMap<&str, usize> = IndexMap::new();
+        for (classifier, other) in [&self.lowercase, &self.uppercase, &self.number, &self.symbol]
+            .into_iter()
+            .map(|classifier| (classifier, false))
+            .chain(self.others.iter().map(|classifier| (classifier, true)))
+        {
+            let distinct: IndexSet<&str> = classifier
+                .weighted_candidates()
+                .filter(|(c, weight)| 0 < *weight && !self.is_excluded(c, other))
+                .map(|(c, _)| c.as_str())
+                .collect();
+            for candidate in distinct {
+                *counts.entry(candidate).or_insert(0) += 1;
+            }
+        }
+
+        counts
+            .into_iter()
+            .filter(|(_, count)| 1 < *count)
+            .map(|(candidate, _)| candidate.to_string())
+            .collect()
+    }
+
+    /// Return the number of distinct characters that can appear in the password
+    ///
+    /// This is the length of [`PasswordMaker::effective_pool`], so the similar characters, `exclude_characters`,
+    /// and the candidates with a weight of 0 are not counted, and the whitespace is counted with `include_whitespace_in_candidate`.
+    /// A candidate given more than once is counted once, since it makes the character more likely but is still one character.
+    /// Useful to warn that the pool is too small for the length or the number of unique passwords.
+    ///
+    /// # Returns
+    ///
+    /// * Number of distinct characters
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let mut password_maker = PasswordMaker::default();
+    /// assert_eq!(password_maker.pool_size(), 94);
+    ///
+    /// // "-" is a symbol already
+    /// password_maker.symbol.candidates.push("-".to_string());
+    /// assert_eq!(password_maker.pool_size(), 94);
+    /// ```
+    pub fn pool_size(&self) -> usize {
+        self.effective_pool().len()
+    }
+
+    /// Return whether a filter removes the candidate from the pool
+    ///
+    /// # Arguments
+    ///
+    /// * `candidate` - Candidate character
+    /// * `other` - Whether the candidate belongs to the other characters
+    fn is_excluded(&self, candidate: &str, other: bool) -> bool {
+        let similar_applies = !other || self.exclude_similar_scope == SimilarScope::AllClasses;
+        (self.exclude_similar && similar_applies && self.is_similar(candidate))
+            || self.exclude_characters.iter().any(|c| c == candidate)
+    }
+
+    /// Return whether the candidate is one of the similar characters
+    ///
+    /// # Arguments
+    ///
+    /// * `candidate` - Candidate character
+    fn is_similar(&self, candidate: &str) -> bool {
+        self.similar_characters.iter().any(|c| c == candidate)
+    }
+
+    /// Return the pairs of candidates that look alike
+    ///
+    /// Mixing scripts can put visually identical graphemes in the candidates,
+    /// such as the Latin "a" (U+0061) and the Cyrillic "а" (U+0430).
+    /// Such passwords are hard to read and type correctly.
+    /// Look-alikes are looked up in a built-in table of Latin, Cyrillic, and Greek letters.
+    ///
+    /// # Returns
+    ///
+    /// * Pairs of look-alike candidates
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::{Classifier, PasswordMaker};
+    ///
+    /// let password_maker = PasswordMaker {
+    ///     others: vec![Classifier {
+    ///         candidates: vec!["\u{0430}".to_string()],
+    ///         minimum_count: 0,
+    ///         ..Default::default()
+    ///     }],
+    ///     ..PasswordMaker::default()
+    /// };
+    /// assert_eq!(
+    ///     password_maker.homoglyph_conflicts(),
+    ///     vec![("a".to_string(), "\u{0430}".to_string())]
+    /// );
+    /// ```
+    pub fn homoglyph_conflicts(&self) -> Vec<(String, String)> {
+        let candidates = self.effective_pool();
+        let mut conflicts = Vec::new();
+
+        for group in confusables::CONFUSABLES {
+            let present: Vec<&str> = group
+                .iter()
+                .copied()
+                .filter(|g| candidates.iter().any(|c| c == g))
+                .collect();
+            for (index, first) in present.iter().enumerate() {
+                for second in &present[index + 1..] {
+                    conflicts.push((first.to_string(), second.to_string()));
+                }
+            }
+        }
+
+        conflicts
+    }
+
+    /// Return whether the password contains any of the denied words
+    ///
+    /// The words are matched as substrings without regard to case, e.g. "Pass" denies "xpASSy".
+    /// Empty words are ignored. Use this to regenerate passwords that contain offensive or common words.
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - Password to check
+    /// * `words` - Denied words
+    ///
+    /// # Returns
+    ///
+    /// * true: The password contains a denied word
+    /// * false: The password contains none of the denied words
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let password_maker = PasswordMaker::default();
+    /// let words = vec!["pass".to_string()];
+    /// assert!(password_maker.contains_denied("x7PaSs!q", &words));
+    /// assert!(!password_maker.contains_denied("x7Pa-ss!q", &words));
+    /// ```
+    pub fn contains_denied(&self, password: &str, words: &[String]) -> bool {
+        contains_denied(password, words)
+    }
+
+    /// Return the total minimum number of characters for all types
+    ///
+    /// # Returns
+    ///
+    /// * Some: Total minimum number of characters
+    /// * None: The total does not fit in `u32`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let mut password_maker = PasswordMaker::default();
+    /// assert_eq!(password_maker.total_minimum_count(), Some(4));
+    ///
+    /// password_maker.uppercase.minimum_count = u32::MAX;
+    /// assert_eq!(password_maker.total_minimum_count(), None);
+    /// ```
+    pub fn total_minimum_count(&self) -> Option<u32> {
+        [&self.lowercase, &self.uppercase, &self.number, &self.symbol]
+            .into_iter()
+            .chain(&self.others)
+            .try_fold(0u32, |total, classifier| {
+                total.checked_add(classifier.minimum_count)
+            })
+    }
+
+    /// Return the number of passwords that can be generated
+    ///
+    /// Computed as `number of candidates ^ length`, with duplicate candidates counted once.
+    /// The minimum counts are not taken into account, so this is an upper bound.
+    ///
+    /// # Returns
+    ///
+    /// * Some: Number of passwords
+    /// * None: The number is too large for `u128`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::{Classifier, PasswordMaker};
+    ///
+    /// let password_maker = PasswordMaker {
+    ///     length: 3,
+    ///     uppercase: Classifier::default(),
+    ///     lowercase: Classifier {
+    ///         candidates: vec!["a".to_string(), "b".to_string()],
+    ///         minimum_count: 0,
+    ///         ..Default::default()
+    ///     },
+    ///     number: Classifier::default(),
+    ///     symbol: Classifier::default(),
+    ///     ..PasswordMaker::default()
+    /// };
+    /// assert_eq!(password_maker.keyspace(), Some(8));
+    ///
+    /// // 94 ^ 16 fits in u128, but 94 ^ 100 does not
+    /// let mut password_maker = PasswordMaker::default();
+    /// assert_eq!(password_maker.keyspace(), Some(94u128.pow(16)));
+    /// password_maker.length = 100;
+    /// assert_eq!(password_maker.keyspace(), None);
+    /// ```
+    pub fn keyspace(&self) -> Option<u128> {
+        if self.needs_normalizing() {
+            return self.normalized().keyspace();
+        }
+        if let Some(pattern) = &self.pattern {
+            return self.pattern_keyspace(pattern);
+        }
+
+        let pool_size = self.pool_size();
+        (pool_size as u128).checked_pow(self.length)
+    }
+
+    /// Check that `count` unique passwords can exist
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - Number of unique passwords
+    ///
+    /// # Returns
+    ///
+    /// * Ok: `count` does not exceed [`PasswordMaker::keyspace`]
+    /// * Err: Error
+    pub fn check_keyspace(&self, count: usize) -> Result<(), PasswordError> {
+        match self.keyspace() {
+            Some(keyspace) if keyspace < count as u128 => Err(PasswordError::KeyspaceTooSmall {
+                requested: count,
+                keyspace,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Return the strength of the password in bits
+    ///
+    /// Computed as `length * bits_per_character()`, which is `length * log2(number of candidates)` without weights,
+    /// with duplicate candidates counted once.
+    /// The candidates are those of [`PasswordMaker::effective_pool`].
+    /// With `pattern`, only the placeholders are random, so the entropy is the sum of the bits
+    /// of the character type of each placeholder.
+    /// The minimum counts are not taken into account.
+    ///
+    /// # Returns
+    ///
+    /// * Entropy in bits (0.0 if there are no candidates or the length is 0)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let password_maker = PasswordMaker::default();
+    /// // 16 characters from 94 candidates
+    /// assert!((password_maker.entropy() - 104.87).abs() < 0.01);
+    /// ```
+    pub fn entropy(&self) -> f64 {
+        if self.needs_normalizing() {
+            return self.normalized().entropy();
+        }
+        if let Some(pattern) = &self.pattern {
+            return self.pattern_entropy(pattern);
+        }
+
+        self.length as f64 * self.bits_per_character()
+    }
+
+    /// Return the strength of each character of the password in bits
+    ///
+    /// Computed as the min-entropy `-log2(probability of the most likely candidate)`,
+    /// so weighted candidates are not overestimated: a candidate that is chosen half of the time makes it 1 bit
+    /// however many other candidates there are. Without weights, this is `log2(number of candidates)`.
+    ///
+    /// # Returns
+    ///
+    /// * Entropy in bits (0.0 if there are no candidates)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let mut password_maker = PasswordMaker::default();
+    /// assert!((password_maker.bits_per_character() - 94f64.log2()).abs() < 1e-9);
+    ///
+    /// // "a" is chosen more often than all the other candidates together
+    /// password_maker.lowercase.weights = vec![1000];
+    /// password_maker.lowercase.weights.resize(26, 1);
+    /// assert!(password_maker.bits_per_character() < 1.0);
+    /// ```
+    pub fn bits_per_character(&self) -> f64 {
+        if self.needs_normalizing() {
+            return self.normalized().bits_per_character();
+        }
+
+        min_entropy(self.candidate_weights())
+    }
+
+    /// Return the entropy of the password split by character type
+    ///
+    /// The entropy of each character, [`PasswordMaker::bits_per_character`], is shared among the types
+    /// in proportion to the total weight of their candidates, which is the number of their candidates without weights.
+    /// With `pattern`, the entropy of each placeholder goes to its character type.
+    /// The bits of all the types add up to [`PasswordMaker::entropy`].
+    ///
+    /// # Returns
+    ///
+    /// * Bits from each character type
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let password_maker = PasswordMaker::default();
+    /// let breakdown = password_maker.entropy_breakdown();
+    /// // 10 of the 94 candidates are numbers
+    /// assert!((breakdown.number - password_maker.entropy() * 10.0 / 94.0).abs() < 1e-9);
+    /// ```
+    pub fn entropy_breakdown(&self) -> EntropyBreakdown {
+        if self.needs_normalizing() {
+            return self.normalized().entropy_breakdown();
+        }
+        if let Some(pattern) = &self.pattern {
+            return self.pattern_entropy_breakdown(pattern);
+        }
+
+        let mut breakdown = EntropyBreakdown {
+            others: vec![0.0; self.others.len()],
+            ..EntropyBreakdown::default()
+        };
+        let weights = self.candidate_weights();
+        let total = weights.iter().sum::<f64>();
+        let entropy = self.entropy();
+        for ((candidate, index, _), weight) in self.grouped_candidates().iter().zip(weights) {
+            // The candidates are in the order of the types, so the first type that contains one is its type
+            let part = match index {
+                Some(index) => &mut breakdown.others[*index],
+                None if self.lowercase.contains(candidate) => &mut breakdown.lowercase,
+                None if self.uppercase.contains(candidate) => &mut breakdown.uppercase,
+                None if self.number.contains(candidate) => &mut breakdown.number,
+                None if self.symbol.contains(candidate) => &mut breakdown.symbol,
+                None => &mut breakdown.whitespace,
+            };
+            *part += entropy * weight / total;
+        }
+
+        breakdown
+    }
+
+    /// Return the shortest length whose entropy is at least the given bits
+    ///
+    /// The entropy is computed in the same way as [`PasswordMaker::entropy`], so the length is
+    /// `ceil(bits / bits_per_character())`. The length is not shorter than the total minimum number of characters, nor 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `bits` - Entropy in bits
+    ///
+    /// # Returns
+    ///
+    /// * Ok: Length of the password
+    /// * Err: Error
+    ///
+    /// # Errors
+    ///
+    /// * No candidates for the password
+    /// * The total minimum number of characters for all types does not fit in `u32`
+    /// * Only one distinct candidate, or the length does not fit in `u32`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let mut password_maker = PasswordMaker::default();
+    /// password_maker.length = password_maker.length_for_entropy(80.0).unwrap();
+    /// assert_eq!(password_maker.length, 13);
+    /// assert!(80.0 <= password_maker.entropy());
+    /// ```
+    pub fn length_for_entropy(&self, bits: f64) -> Result<u32, PasswordError> {
+        let pool_size = self.pool_size();
+        if pool_size == 0 {
+            return Err(PasswordError::NoCandidates);
+        }
+        let total_min = self
+            .total_minimum_count()
+            .ok_or(PasswordError::MinimumCountOverflow)?;
+
+        let bits_per_character = self.bits_per_character();
+        let length = if bits <= 0.0 {
+            0.0
+        } else if bits_per_character <= 0.0 {
+            return Err(PasswordError::EntropyUnreachable);
+        } else {
+            (bits / bits_per_character).ceil()
+        };
+        if length.is_nan() || (u32::MAX as f64) < length {
+            return Err(PasswordError::EntropyUnreachable);
+        }
+
+        Ok((length as u32).max(total_min).max(1))
+    }
+
+    /// Return the selection weight of each candidate character
+    ///
+    /// The order is the same as [`PasswordMaker::candidates`].
+    /// Without `others_equal_weight`, the weights are those of [`Classifier::weights`] (1 if not set).
+    /// With it, each other characters settings gets the same share of the total weight of the other characters.
+    ///
+    /// # Returns
+    ///
+    /// * List of weights
+    fn candidate_weights(&self) -> Vec<f64> {
+        let candidates = self.grouped_candidates();
+        if !self.others_equal_weight {
+            return candidates
+                .iter()
+                .map(|(_, _, weight)| f64::from(*weight))
+                .collect();
+        }
+
+        // Sum the weights of each other characters settings after filtering
+        let mut sizes = vec![0.0; self.others.len()];
+        for (_, index, weight) in &candidates {
+            if let Some(index) = index {
+                sizes[*index] += f64::from(*weight);
+            }
+        }
+        let others_total = sizes.iter().sum::<f64>();
+        let non_empty = sizes.iter().filter(|&&size| 0.0 < size).count() as f64;
+
+        candidates
+            .iter()
+            .map(|(_, index, weight)| match index {
+                None => f64::from(*weight),
+                Some(index) => f64::from(*weight) * others_total / non_empty / sizes[*index],
+            })
+            .collect()
+    }
+
+    /// Split the password generator into `n` generators for parallel generation
+    ///
+    /// Each generator has the same settings and is paired with its own random number generator.
+    /// The random number generators are derived from `base_seed` and use different ChaCha streams,
+    /// so their outputs do not overlap and are reproducible from `base_seed`.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_seed` - Seed shared by all random number generators
+    /// * `n` - Number of generators
+    ///
+    /// # Returns
+    ///
+    /// * List of pairs of a password generator and a random number generator
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let password_maker = PasswordMaker::default();
+    /// let makers = password_maker.split_for_parallel(42, 4);
+    /// assert_eq!(makers.len(), 4);
+    /// ```
+    pub fn split_for_parallel(
+        &self,
+        base_seed: u64,
+        n: usize,
+    ) -> Vec<(PasswordMaker, ChaCha20Rng)> {
+        (0..n)
+            .map(|index| (self.clone(), stream_rng(base_seed, index as u64)))
+            .collect()
+    }
+
+    /// Generate passwords in parallel in a reproducible order
+    ///
+    /// The workers are assigned contiguous ranges of indexes, and the passwords are reassembled in index order.
+    /// Each password is generated with its own ChaCha stream of `base_seed` selected by its index,
+    /// so unlike [`PasswordMaker::split_for_parallel`], the output does not depend on the number of workers
+    /// and is the same byte for byte as generating with 1 worker.
+    /// It is also the same as generating the passwords one by one with [`PasswordMaker::create_rng_at`]
+    /// when `seed` is `base_seed`.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_seed` - Seed shared by all random number generators
+    /// * `count` - Number of passwords to generate
+    /// * `workers` - Number of threads (at least 1 is used)
+    ///
+    /// # Returns
+    ///
+    /// * Ok: List of passwords in index order
+    /// * Err: Error
+    ///
+    /// # Errors
+    ///
+    /// The same as [`PasswordMaker::generate`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let password_maker = PasswordMaker::default();
+    /// let serial = password_maker.generate_parallel(42, 100, 1).unwrap();
+    /// let parallel = password_maker.generate_parallel(42, 100, 4).unwrap();
+    /// assert_eq!(serial, parallel);
+    /// ```
+    pub fn generate_parallel(
+        &self,
+        base_seed: u64,
+        count: usize,
+        workers: usize,
+    ) -> Result<Vec<String>, PasswordError> {
+        self.validate()?;
+
+        let chunk = count.div_ceil(workers.max(1)).max(1);
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..count)
+                .step_by(chunk)
+                .map(|start| {
+                    let end = (start + chunk).min(count);
+                    let mut maker = self.clone();
+                    scope.spawn(move || {
+                        (start..end)
+                            .map(|index| {
+                                maker.generate_with_rng(&mut stream_rng(base_seed, index as u64))
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                })
+                .collect();
+
+            // Join in the order of the ranges so that the passwords are in index order
+            let mut passwords = Vec::with_capacity(count);
+            for handle in handles {
+                passwords.extend(handle.join().unwrap()?);
+            }
+            Ok(passwords)
+        })
+    }
+
+    /// Create a random number generator
+    ///
+    /// Return the random number generator that [`PasswordMaker::generate`] uses
+    ///
+    /// If `seed` is set, return a random number generator seeded with it
+    ///
+    /// During unit tests, return a fixed seed random number generator to ensure reproducibility
+    ///
+    /// Outside of unit tests, return a random number generator with a different seed for each thread
+    ///
+    /// Pass it to [`PasswordMaker::generate_with_rng`] to generate several passwords from one seed.
+    /// Each call of [`PasswordMaker::generate`] starts over from the seed, so it generates the same password every time.
+    ///
+    /// # Returns
+    ///
+    /// * Random number generator
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let mut password_maker = PasswordMaker {
+    ///     seed: Some(42),
+    ///     ..PasswordMaker::default()
+    /// };
+    /// let mut rng = password_maker.create_rng();
+    /// let first = password_maker.generate_with_rng(&mut rng).unwrap();
+    /// let second = password_maker.generate_with_rng(&mut rng).unwrap();
+    /// assert_ne!(first, second);
+    ///
+    /// // The same seed generates the same passwords again
+    /// let mut rng = password_maker.create_rng();
+    /// assert_eq!(password_maker.generate_with_rng(&mut rng).unwrap(), first);
+    /// ```
+    pub fn create_rng(&self) -> Box<dyn RngCore> {
+        create_rng(self.seed)
+    }
+
+    /// Create the random number generator of the password at `index` of a batch
+    ///
+    /// If `seed` is set, return a random number generator on the ChaCha stream `index` of the seed,
+    /// the same one that [`PasswordMaker::generate_parallel`] uses for the password at `index`.
+    /// The password at each index then does not depend on the passwords before it,
+    /// so a batch generated one by one is the same as one generated in parallel.
+    ///
+    /// If `seed` is not set, the same as [`PasswordMaker::create_rng`].
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the password in the batch
+    ///
+    /// # Returns
+    ///
+    /// * Random number generator
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let mut password_maker = PasswordMaker {
+    ///     seed: Some(42),
+    ///     ..PasswordMaker::default()
+    /// };
+    /// let parallel = password_maker.generate_parallel(42, 3, 2).unwrap();
+    /// for (index, password) in parallel.iter().enumerate() {
+    ///     let mut rng = password_maker.create_rng_at(index as u64);
+    ///     assert_eq!(&password_maker.generate_with_rng(&mut rng).unwrap(), password);
+    /// }
+    /// ```
+    pub fn create_rng_at(&self, index: u64) -> Box<dyn RngCore> {
+        match self.seed {
+            Some(seed) => Box::new(stream_rng(seed, index)),
+            None => self.create_rng(),
+        }
+    }
+
+    /// Restore all settings to the default values
+    ///
+    /// Useful to reuse a password generator for differently configured generations,
+    /// e.g. when a long-running application keeps a pool of password generators.
+    /// The vectors are cleared and refilled in place, so they keep the memory they have allocated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let mut password_maker = PasswordMaker {
+    ///     length: 32,
+    ///     exclude_similar: true,
+    ///     ..PasswordMaker::default()
+    /// };
+    /// password_maker.reset();
+    /// assert_eq!(password_maker, PasswordMaker::default());
+    /// ```
+    pub fn reset(&mut self) {
+        // Destructure without `..` so that a new field cannot be forgotten here
+        let PasswordMaker {
+            length,
+            length_limit,
+            exclude_similar,
+            similar_characters,
+            exclude_similar_scope,
+            exclude_characters,
+            include_whitespace_in_candidate,
+            raw_sample,
+            allow_control_characters,
+            strip_variation_selectors,
+            pool_encoding,
+            lowercase,
+            uppercase,
+            number,
+            symbol,
+            others,
+            others_equal_weight,
+            others_unit,
+            seed,
+            luhn_segment,
+            no_consecutive_repeats,
+            exclude_sequences,
+            unique_characters,
+            no_leading_zero,
+            pattern,
+        } = PasswordMaker::default();
+
+        self.length = length;
+        self.length_limit = length_limit;
+        self.exclude_similar = exclude_similar;
+        refill(&mut self.similar_characters, similar_characters);
+        self.exclude_similar_scope = exclude_similar_scope;
+        refill(&mut self.exclude_characters, exclude_characters);
+        self.include_whitespace_in_candidate = include_whitespace_in_candidate;
+        self.raw_sample = raw_sample;
+        self.allow_control_characters = allow_control_characters;
+        self.strip_variation_selectors = strip_variation_selectors;
+        self.pool_encoding = pool_encoding;
+        for (classifier, default) in [
+            (&mut self.lowercase, lowercase),
+            (&mut self.uppercase, uppercase),
+            (&mut self.number, number),
+            (&mut self.symbol, symbol),
+        ] {
+            let Classifier {
+                candidates,
+                minimum_count,
+                maximum_count,
+                weights,
+            } = default;
+            refill(&mut classifier.candidates, candidates);
+            classifier.minimum_count = minimum_count;
+            classifier.maximum_count = maximum_count;
+            refill(&mut classifier.weights, weights);
+        }
+        refill(&mut self.others, others);
+        self.others_equal_weight = others_equal_weight;
+        self.others_unit = others_unit;
+        self.seed = seed;
+        self.luhn_segment = luhn_segment;
+        self.no_consecutive_repeats = no_consecutive_repeats;
+        self.exclude_sequences = exclude_sequences;
+        self.unique_characters = unique_characters;
+        self.no_leading_zero = no_leading_zero;
+        self.pattern = pattern;
+    }
+
+    /// Validate the settings of the password generator
+    ///
+    /// Checks:
+    /// - The number of weights of a character type differs from the number of its candidates
+    /// - No candidates for a character type, but the minimum number of characters is set to 1 or more
+    /// - The total minimum number of characters for all types exceeds the password length
+    /// - No candidates for the password
+    /// - Control characters in the candidates
+    /// - The encoding of `pool_encoding` is not supported
+    /// - The password length is 0
+    /// - The password length exceeds the length limit
+    /// - The Luhn-valid segment does not fit in the password, or there are no digits for it
+    /// - The length or a minimum number of characters exceeds the distinct candidates with `unique_characters`
+    /// - A character forced by the minimum counts cannot be separated without sequences with `exclude_sequences`
+    ///
+    /// [`PasswordMaker::generate`] validates the settings itself,
+    /// so this is for checking settings in advance, e.g. after loading them from a config file.
+    ///
+    /// # Returns
+    ///
+    /// * Ok: The settings are valid
+    /// * Err: Error
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let password_maker = PasswordMaker {
+    ///     length: 0,
+    ///     ..PasswordMaker::default()
+    /// };
+    /// assert!(password_maker.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), PasswordError> {
+        // The candidates are checked in the form in which they are used
+        if self.needs_normalizing() {
+            self.pool_encoding()?;
+            return self.normalized().validate();
+        }
+
+        // Check if the minimum number of characters for each parameter is not violated
+        let classifier = [
+            // Capitalize the first letter for error messages
+            (&self.uppercase, "Uppercases"),
+            (&self.lowercase, "Lowercases"),
+            (&self.number, "Numbers"),
+            (&self.symbol, "Symbols"),
+        ];
+
+        // Check if each weight has its candidate
+        for (index, classify) in self.others.iter().enumerate() {
+            self.validate_weights(classify, &format!("Other characters at index {}", index))?;
+        }
+        for (classify, name) in classifier.iter() {
+            self.validate_weights(classify, name)?;
+        }
+
+        // The pattern decides the length and the types of the characters
+        match &self.pattern {
+            Some(pattern) => self.validate_pattern(pattern)?,
+            None => self.validate_minimum_counts(&classifier)?,
+        }
+
+        // Check if there are candidates for the password
+        let candidates = self.effective_pool();
+        if candidates.is_empty() {
+            return Err(PasswordError::NoCandidates);
+        }
+
+        // Check if there are no control characters in the candidates
+        if !self.allow_control_characters {
+            let mut control_characters: Vec<char> = Vec::new();
+            for c in candidates.iter().flat_map(|c| c.chars()) {
+                if c.is_control() && !control_characters.contains(&c) {
+                    control_characters.push(c);
+                }
+            }
+            if !control_characters.is_empty() {
+                return Err(PasswordError::ControlCharacters(control_characters));
+            }
+        }
+
+        let length = match &self.pattern {
+            Some(pattern) => pattern::pattern_length(pattern) as u32,
+            None => self.length,
+        };
+
+        // Check if the password length is 0
+        if length == 0 {
+            return Err(PasswordError::ZeroLength);
+        }
+
+        // Check if the password length is not too large
+        if self.length_limit < length {
+            return Err(PasswordError::LengthExceedsLimit {
+                length,
+                limit: self.length_limit,
+            });
+        }
+
+        // The pattern ignores the other settings
+        if self.pattern.is_some() {
+            return Ok(());
+        }
+
+        // Check if the Luhn-valid segment fits in the password and has digits to use
+        if let Some(segment) = &self.luhn_segment {
+            if segment.length < 2 || self.length < segment.start.saturating_add(segment.length) {
+                return Err(PasswordError::InvalidLuhnSegment {
+                    start: segment.start,
+                    length: segment.length,
+                    password_length: self.length,
+                });
+            }
+            if self.luhn_digits().next().is_none() {
+                return Err(PasswordError::NoDigitsForLuhn);
+            }
+            // The segment is not checked against these settings, so it could break them
+            for (setting, enabled) in [
+                ("unique_characters", self.unique_characters),
+                ("no_consecutive_repeats", self.no_consecutive_repeats),
+                ("exclude_sequences", self.exclude_sequences),
+                ("no_leading_zero", self.no_leading_zero),
+                ("exclude_similar", self.exclude_similar),
+            ] {
+                if enabled {
+                    return Err(PasswordError::LuhnSegmentConflict(setting.to_string()));
+                }
+            }
+            if let Some(maximum) = self.number.maximum_count {
+                if maximum < segment.length {
+                    return Err(PasswordError::MaximumBelowMinimum {
+                        class: "Numbers".to_string(),
+                        minimum: segment.length,
+                        maximum,
+                    });
+                }
+            }
+        }
+
+        // Check if there is a candidate other than "0" to start the password with
+        if self.no_leading_zero && self.effective_pool().iter().all(|c| c == "0") {
+            return Err(PasswordError::LeadingZeroUnavoidable);
+        }
+
+        // Check if there are enough candidates to avoid repeating a character
+        if self.no_consecutive_repeats && 2 <= self.length && self.pool_size() < 2 {
+            return Err(PasswordError::ConsecutiveRepeatsUnsatisfiable);
+        }
+
+        // Check if the maximum number of characters for each type is consistent
+        let mut named: Vec<(&Classifier, String)> = classifier
+            .iter()
+            .map(|(classify, name)| (*classify, name.to_string()))
+            .collect();
+        for (index, classify) in self.others.iter().enumerate() {
+            named.push((classify, format!("Other characters at index {}", index)));
+        }
+
+        for (classify, name) in &named {
+            if let Some(maximum) = classify.maximum_count {
+                if maximum < classify.minimum_count {
+                    return Err(PasswordError::MaximumBelowMinimum {
+                        class: name.clone(),
+                        minimum: classify.minimum_count,
+                        maximum,
+                    });
+                }
+            }
+        }
+
+        // Check if the distinct candidates are enough to use each character at most once
+        if self.unique_characters {
+            let pool = self.effective_pool();
+            if pool.len() < self.length as usize {
+                return Err(PasswordError::LengthExceedsDistinctCandidates {
+                    length: self.length,
+                    distinct: pool.len(),
+                });
+            }
+            for (classify, name) in &named {
+                let distinct = pool.iter().filter(|c| classify.contains(c)).count();
+                if distinct < classify.minimum_count as usize {
+                    return Err(PasswordError::MinimumExceedsDistinctCandidates {
+                        class: name.clone(),
+                        minimum: classify.minimum_count,
+                        distinct,
+                    });
+                }
+            }
+        }
+
+        // Check if the characters forced by the minimum counts can be kept out of sequences
+        if self.exclude_sequences
+            && self.no_consecutive_repeats
+            && self.sequences_unavoidable(&named)
+        {
+            return Err(PasswordError::SequencesUnsatisfiable);
+        }
+
+        // If every type has a maximum, the maximums must add up to the length
+        let available: Vec<&Classifier> = named
+            .iter()
+            .enumerate()
+            // The other characters follow the standard character types
+            .filter(|(index, (classify, _))| {
+                !self.is_empty_without_similar(classify, classifier.len() <= *index)
+            })
+            .map(|(_, (classify, _))| *classify)
+            .collect();
+        if !self.include_whitespace_in_candidate
+            && available
+                .iter()
+                .all(|classify| classify.maximum_count.is_some())
+        {
+            let total = available
+                .iter()
+                .filter_map(|classify| classify.maximum_count)
+                .map(u64::from)
+                .sum();
+            if total < u64::from(self.length) {
+                return Err(PasswordError::MaximumsBelowLength {
+                    total,
+                    length: self.length,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Return whether sequences cannot be avoided without repeating a character
+    ///
+    /// The minimum count of a type with a single candidate forces that candidate.
+    /// A character forced into every other position (e.g. "2" three times in five characters)
+    /// is separated only by the other characters, and if they are just the characters before and after it
+    /// (e.g. "1" and "3"), two of them meet around it and make a sequence (e.g. "123").
+    /// Other impossible settings are found while generating the password.
+    ///
+    /// # Arguments
+    ///
+    /// * `named` - Character types and their names
+    fn sequences_unavoidable(&self, named: &[(&Classifier, String)]) -> bool {
+        if self.raw_sample {
+            return false;
+        }
+
+        let pool = self.effective_pool();
+        let mut forced: IndexMap<&str, u64> = IndexMap::new();
+        for (classify, _) in named {
+            let mut distinct = pool.iter().filter(|c| classify.contains(c));
+            if let (Some(candidate), None) = (distinct.next(), distinct.next()) {
+                *forced.entry(candidate).or_default() += u64::from(classify.minimum_count);
+            }
+        }
+        let forced_count = |candidate: &str| forced.get(candidate).copied().unwrap_or(0);
+
+        forced.iter().any(|(&candidate, &count)| {
+            let &[byte] = candidate.as_bytes() else {
+                return false;
+            };
+            let (Some(before), Some(after)) = (byte.checked_sub(1), byte.checked_add(1)) else {
+                return false;
+            };
+            let (before, after) = ((before as char).to_string(), (after as char).to_string());
+            2 <= count
+                && 2 * count - 1 == u64::from(self.length)
+                && is_sequence(&before, candidate, &after)
+                && 0 < forced_count(&before)
+                && 0 < forced_count(&after)
+                && pool
+                    .iter()
+                    .all(|c| c == candidate || *c == before || *c == after)
+        })
+    }
+
+    /// Check that the minimum counts can be met
+    ///
+    /// # Arguments
+    ///
+    /// * `classifier` - Standard character types and their names
+    fn validate_minimum_counts(
+        &self,
+        classifier: &[(&Classifier, &str)],
+    ) -> Result<(), PasswordError> {
+        for (index, classify) in self.others.iter().enumerate() {
+            if self.is_empty_without_similar(classify, true) && 0 < classify.minimum_count {
+                return Err(PasswordError::EmptyCandidatesWithMinimum {
+                    class: self
+                        .class_name(classify, &format!("Other characters at index {}", index)),
+                    minimum: classify.minimum_count,
+                });
+            }
+        }
+
+        for (classify, name) in classifier.iter() {
+            if self.is_empty_without_similar(classify, false) && 0 < classify.minimum_count {
+                return Err(PasswordError::EmptyCandidatesWithMinimum {
+                    class: self.class_name(classify, name),
+                    minimum: classify.minimum_count,
+                });
+            }
+        }
+
+        // Check if the total minimum number of characters is not violated
+        // Very large minimum counts could wrap around and pass the check below
+        let total_min = self
+            .total_minimum_count()
+            .ok_or(PasswordError::MinimumCountOverflow)?;
+        let others_min = total_min
+            - self.lowercase.minimum_count
+            - self.uppercase.minimum_count
+            - self.number.minimum_count
+            - self.symbol.minimum_count;
+
+        if self.length < total_min {
+            // List the contribution of each type so that the user can see which minimum to reduce
+            return Err(PasswordError::MinimumExceedsLength {
+                total: total_min,
+                uppercase: self.uppercase.minimum_count,
+                lowercase: self.lowercase.minimum_count,
+                number: self.number.minimum_count,
+                symbol: self.symbol.minimum_count,
+                others: others_min,
+                length: self.length,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Return whether no candidates of the character type remain after excluding similar characters
+    ///
+    /// Candidates with a weight of 0 are also treated as excluded.
+    /// The minimum count is filled from the remaining candidates, so it cannot be met if none remain.
+    ///
+    /// # Arguments
+    ///
+    /// * `classifier` - Settings of the character type
+    /// * `other` - Whether the character type is one of the other characters
+    fn is_empty_without_similar(&self, classifier: &Classifier, other: bool) -> bool {
+        classifier
+            .weighted_candidates()
+            .all(|(c, weight)| weight == 0 || self.is_excluded(c, other))
+    }
+
+    /// Check that the weights of the character type match its candidates
+    ///
+    /// # Arguments
+    ///
+    /// * `classifier` - Settings of the character type
+    /// * `name` - Name of the character type
+    ///
+    /// # Errors
+    ///
+    /// * If the weights are set and their number differs from the number of candidates
+    fn validate_weights(&self, classifier: &Classifier, name: &str) -> Result<(), PasswordError> {
+        if !classifier.weights.is_empty() && classifier.weights.len() != classifier.candidates.len()
+        {
+            return Err(PasswordError::WeightsLengthMismatch {
+                class: name.to_string(),
+                weights: classifier.weights.len(),
+                candidates: classifier.candidates.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Return whether any character type has weighted candidates
+    fn is_weighted(&self) -> bool {
+        [&self.uppercase, &self.lowercase, &self.number, &self.symbol]
+            .into_iter()
+            .chain(&self.others)
+            .any(|classifier| !classifier.weights.is_empty())
+    }
+
+    /// Return the name of the character type for error messages
+    ///
+    /// If the candidates are not empty, they are empty only because the similar characters are excluded,
+    /// so the name says so.
+    ///
+    /// # Arguments
+    ///
+    /// * `classifier` - Settings of the character type
+    /// * `name` - Name of the character type
+    fn class_name(&self, classifier: &Classifier, name: &str) -> String {
+        if classifier.candidates.is_empty() {
+            name.to_string()
+        } else if self.exclude_characters.is_empty() {
+            format!("{} without similar characters", name)
+        } else {
+            format!("{} without excluded characters", name)
+        }
+    }
+
+    /// Update the password string to meet the minimum number of characters for each type
+    ///
+    /// To maintain randomness, overwrite random positions with characters that meet the minimum count
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - Password
+    /// * `rng` - Random number generator
+    fn overwrite_to_meet_minimum_count<R: Rng + ?Sized>(
+        &self,
+        password: &mut [String],
+        rng: &mut R,
+    ) {
+        // Number of characters to overwrite
+        let overwrite_count =
+            std::cmp::min(self.length, self.total_minimum_count().unwrap_or(u32::MAX));
+
+        // Randomly select characters to overwrite
+        let mut overwrite_chars =
+            self.unique_random_numbers(overwrite_count as usize, 0..password.len() as u32, rng);
+
+        // Update each character type in order (the order can be changed without affecting functionality)
+        let mut classifier = vec![
+            (&self.uppercase, false),
+            (&self.lowercase, false),
+            (&self.number, false),
+            (&self.symbol, false),
+        ];
+        for classify in &self.others {
+            classifier.push((classify, true));
+        }
+
+        for (classify, other) in classifier {
+            self.replace_characters(
+                password,
+                classify,
+                other,
+                overwrite_chars
+                    .drain(0..classify.minimum_count as usize)
+                    .map(|x| x as usize)
+                    .collect(),
+                rng,
+            );
+        }
+    }
+
+    /// Update the password string to meet the maximum number of characters for each type
+    ///
+    /// Random excess characters of each type are overwritten with characters that keep every type within its maximum
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - Password
+    /// * `rng` - Random number generator
+    ///
+    /// # Errors
+    ///
+    /// * No candidates are left to overwrite the excess characters with
+    ///   (only possible if a candidate belongs to multiple types)
+    fn overwrite_to_meet_maximum_count<R: Rng + ?Sized>(
+        &self,
+        password: &mut [String],
+        rng: &mut R,
+    ) -> Result<(), PasswordError> {
+        let mut classifier = vec![&self.uppercase, &self.lowercase, &self.number, &self.symbol];
+        for classify in &self.others {
+            classifier.push(classify);
+        }
+        if classifier
+            .iter()
+            .all(|classify| classify.maximum_count.is_none())
+        {
+            return Ok(());
+        }
+
+        let candidates = self.candidates();
+        let mut counts: Vec<usize> = classifier
+            .iter()
+            .map(|classify| password.iter().filter(|g| classify.contains(g)).count())
+            .collect();
+
+        for (index, classify) in classifier.iter().enumerate() {
+            let Some(maximum) = classify.maximum_count else {
+                continue;
+            };
+            let excess = counts[index].saturating_sub(maximum as usize);
+            if excess == 0 {
+                continue;
+            }
+
+            let positions: Vec<usize> = (0..password.len())
+                .filter(|&position| classify.contains(&password[position]))
+                .collect();
+            for &position in positions.choose_multiple(rng, excess) {
+                // Candidates that do not push any type over its maximum
+                let allowed: Vec<&String> = candidates
+                    .iter()
+                    .filter(|candidate| {
+                        classifier.iter().zip(&counts).all(|(classify, &count)| {
+                            !classify.contains(candidate)
+                                || classify
+                                    .maximum_count
+                                    .is_none_or(|maximum| count < maximum as usize)
+                        })
+                    })
+                    .collect();
+                let replacement = allowed
+                    .choose(rng)
+                    .ok_or(PasswordError::MaximumUnsatisfiable)?;
+
+                for (classify, count) in classifier.iter().zip(counts.iter_mut()) {
+                    if classify.contains(&password[position]) {
+                        *count -= 1;
+                    }
+                    if classify.contains(replacement) {
+                        *count += 1;
+                    }
+                }
+                password[position] = replacement.to_string();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rewrite the characters that end a forbidden run
+    ///
+    /// A run is forbidden if it is a character equal to the previous one (`no_consecutive_repeats`),
+    /// an ascending or descending sequence of three characters (`exclude_sequences`),
+    /// or "0" at the start of the password (`no_leading_zero`).
+    /// Each character that ends a forbidden run is overwritten with a candidate that belongs to the same character types
+    /// and makes no forbidden run, so the number of characters of each type does not change.
+    /// If there is no such candidate (e.g. a type with a single candidate),
+    /// it is swapped with a random character so that neither makes a forbidden run.
+    /// If the minimum count is not applied (`raw_sample`), any other candidate can be used.
+    /// With `unique_characters`, the candidates already in the password are not used.
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - Password
+    /// * `rng` - Random number generator
+    ///
+    /// # Errors
+    ///
+    /// * No candidate or character can replace the character without making a forbidden run
+    fn rewrite_forbidden_runs<R: Rng + ?Sized>(
+        &self,
+        password: &mut [String],
+        rng: &mut R,
+    ) -> Result<(), PasswordError> {
+        let mut classifier = vec![&self.uppercase, &self.lowercase, &self.number, &self.symbol];
+        for classify in &self.others {
+            classifier.push(classify);
+        }
+        let candidates = self.effective_pool();
+        let is_repeat = |password: &[String], position: usize| {
+            self.no_consecutive_repeats
+                && 0 < position
+                && password[position] == password[position - 1]
+        };
+        let is_sequence = |password: &[String], position: usize| {
+            self.exclude_sequences
+                && 1 < position
+                && is_sequence(
+                    &password[position - 2],
+                    &password[position - 1],
+                    &password[position],
+                )
+        };
+        let is_leading_zero = |password: &[String], position: usize| {
+            self.no_leading_zero && position == 0 && password[0] == "0"
+        };
+        // Whether the runs that contain the character at `changed` and end by `position` are allowed
+        // The runs that end after `position` are checked later in the loop
+        let is_fine = |password: &[String], changed: usize, position: usize| {
+            !is_leading_zero(password, changed)
+                && (changed..=position.min(changed + 2)).all(|p| {
+                    (changed + 1 < p || !is_repeat(password, p)) && !is_sequence(password, p)
+                })
+        };
+
+        for position in 0..password.len() {
+            if !is_repeat(password, position)
+                && !is_sequence(password, position)
+                && !is_leading_zero(password, position)
+            {
+                continue;
+            }
+            let leading_zero = is_leading_zero(password, position);
+
+            let current = password[position].clone();
+            let used: IndexSet<String> = if self.unique_characters {
+                password.iter().cloned().collect()
+            } else {
+                IndexSet::new()
+            };
+            let replacements: Vec<&String> = candidates
+                .iter()
+                .filter(|candidate| !used.contains(*candidate))
+                .filter(|candidate| {
+                    self.raw_sample
+                        || classifier.iter().all(|classify| {
+                            classify.contains(candidate) == classify.contains(&current)
+                        })
+                })
+                .filter(|candidate| {
+                    password[position] = candidate.to_string();
+                    is_fine(password, position, position)
+                })
+                .collect();
+            if let Some(replacement) = replacements.choose(rng) {
+                password[position] = replacement.to_string();
+                continue;
+            }
+            password[position] = current;
+
+            // Swap with another character
+            let mut others: Vec<usize> = (0..password.len())
+                .filter(|&other| password[other] != password[position])
+                .collect();
+            others.shuffle(rng);
+            let swapped = others.into_iter().find(|&other| {
+                password.swap(position, other);
+                let ok = is_fine(password, position, position)
+                    && (position < other || is_fine(password, other, position));
+                if !ok {
+                    password.swap(position, other);
+                }
+                ok
+            });
+            if swapped.is_none() {
+                // Sequences alone can be broken by repeating a character, so they are the cause if excluded
+                return Err(if leading_zero {
+                    PasswordError::LeadingZeroUnavoidable
+                } else if self.exclude_sequences {
+                    PasswordError::SequencesUnsatisfiable
+                } else {
+                    PasswordError::ConsecutiveRepeatsUnsatisfiable
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Overwrite characters in the password string
+    ///
+    /// For example, if the password is "abcde" and overwrite_indexes is \[3, 1, 4\], it becomes "aXcXXe"
+    /// (X is a character randomly chosen from the classifier candidates)
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - Password
+    /// * `classifier` - Character type to replace
+    /// * `other` - Whether the character type is one of the other characters
+    /// * `overwrite_indexes` - Indexes of characters to replace
+    /// * `rng` - Random number generator
+    ///
+    /// # Panics
+    ///
+    /// * If the index of an element in overwrite_indexes is greater than the number of characters in the password
+    fn replace_characters<R: Rng + ?Sized>(
+        &self,
+        password: &mut [String],
+        classifier: &Classifier,
+        other: bool,
+        overwrite_indexes: Vec<usize>,
+        rng: &mut R,
+    ) {
+        for index in overwrite_indexes {
+            // ここはユーザーの入力ミスなどで index が password.len() 以上になることはなく、
+            // なった場合はプログラムのバグなので panic しても問題ない
+            if password.len() <= index {
+                panic!(
+                    "Index out of range: index {} is greater than or equal to password length {}",
+                    index,
+                    password.len()
+                );
+            }
+
+            // Do not reintroduce the characters excluded from the candidates
+            let candidates = classifier
+                .weighted_candidates()
+                .filter(|(c, weight)| 0 < *weight && !self.is_excluded(c, other));
+            // Pick unweighted candidates as before so that seeded passwords stay the same
+            let overwrite_char = if classifier.weights.is_empty() {
+                candidates.choose(rng).unwrap().0.clone()
+            } else {
+                candidates
+                    .collect::<Vec<_>>()
+                    .choose_weighted(rng, |(_, weight)| *weight)
+                    .unwrap()
+                    .0
+                    .clone()
+            };
+            password[index] = overwrite_char;
+        }
+    }
+
+    /// Generate unique random numbers
+    /// The generated values are within the range
+    ///
+    /// Sampled with a partial shuffle, so it runs in O(count) even if count is close to the size of the range
+    ///
+    /// # Arguments
+    ///
+    /// * count: Number of random numbers to generate (must not exceed the size of the range)
+    /// * range: Range of the generated random numbers
+    /// * rng: Random number generator
+    fn unique_random_numbers<R: Rng + ?Sized>(
+        &self,
+        count: usize,
+        range: std::ops::Range<u32>,
+        rng: &mut R,
+    ) -> Vec<u32> {
+        rand::seq::index::sample(rng, range.len(), count)
+            .into_iter()
+            .map(|index| range.start + index as u32)
+            .collect()
+    }
+}
+
+impl Default for PasswordMaker {
+    /// Create a password generator with default settings
+    ///
+    /// The default settings are as follows:
+    /// - length: 16
+    /// - length_limit: 1,000,000
+    /// - exclude_similar: false
+    /// - similar_characters: i l 1 o 0 O
+    /// - exclude_similar_scope: AllClasses
+    /// - exclude_characters: (empty)
+    /// - include_whitespace_in_candidate: false
+    /// - raw_sample: false
+    /// - allow_control_characters: false
+    /// - strip_variation_selectors: false
+    /// - pool_encoding: None
+    /// - lowercase_letters
+    ///   - candidates: a-z
+    ///   - min: 1
+    ///   - max: None
+    /// - uppercase_letters
+    ///   - candidates: A-Z
+    ///   - min: 1
+    ///   - max: None
+    /// - numbers:
+    ///   - candidates: 0-9
+    ///   - min: 1
+    ///   - max: None
+    /// - symbols:
+    ///   - candidates: ! " # $ % & ' ( ) * + , - . / : ; < = > ? @ \[ \ \] ^ _ \` { | } ~
+    ///   - min: 1
+    ///   - max: None
+    /// - other_characters:
+    ///   - candidates: None
+    ///   - min: 0
+    ///   - max: None
+    /// - others_equal_weight: false
+    /// - others_unit: Grapheme
+    /// - seed: None
+    /// - luhn_segment: None
+    /// - no_consecutive_repeats: false
+    /// - exclude_sequences: false
+    /// - unique_characters: false
+    /// - no_leading_zero: false
+    /// - pattern: None
+    fn default() -> Self {
+        PasswordMaker {
+            length: 16,
+            length_limit: 1_000_000,
+            exclude_similar: false,
+            similar_characters: ["i", "l", "1", "o", "0", "O"]
+                .iter()
+                .map(|c| c.to_string())
+                .collect(),
+            exclude_similar_scope: SimilarScope::AllClasses,
+            exclude_characters: vec![],
+            // Whitespace is less commonly used in passwords compared to other symbols,
+            // and leading or trailing whitespace can cause input errors, so it is disabled by default.
+            include_whitespace_in_candidate: false,
+            raw_sample: false,
+            allow_control_characters: false,
+            strip_variation_selectors: false,
+            pool_encoding: None,
+            lowercase: Classifier {
+                candidates: ('a'..='z').map(|c| c.to_string()).collect(),
+                minimum_count: 1,
+                ..Default::default()
+            },
+            uppercase: Classifier {
+                candidates: ('A'..='Z').map(|c| c.to_string()).collect(),
+                minimum_count: 1,
+                ..Default::default()
+            },
+            number: Classifier {
+                candidates: (0..=9).map(|c| c.to_string()).collect(),
+                minimum_count: 1,
+                ..Default::default()
+            },
+            // Symbols are sorted in ascending order of ASCII values
+            symbol: Classifier {
+                candidates: "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~"
+                    .chars()
+                    .map(|c| c.to_string())
+                    .collect(),
+                minimum_count: 1,
+                ..Default::default()
+            },
+            others: vec![],
+            others_equal_weight: false,
+            others_unit: GraphemeUnit::Grapheme,
+            seed: None,
+            luhn_segment: None,
+            no_consecutive_repeats: false,
+            exclude_sequences: false,
+            unique_characters: false,
+            no_leading_zero: false,
+            pattern: None,
+        }
+    }
+}
+
+impl fmt::Display for PasswordMaker {
+    /// Show the effective settings to find out why passwords look the way they do
+    ///
+    /// Each character type is shown with the number of its candidates that remain after the filters,
+    /// not the candidates themselves, to keep the output compact.
+    /// Only the enabled flags are shown, and `exclude_similar` is shown with its scope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let password_maker = PasswordMaker {
+    ///     exclude_similar: true,
+    ///     ..PasswordMaker::default()
+    /// };
+    /// assert_eq!(
+    ///     password_maker.to_string(),
+    ///     "length: 16\n\
+    ///      flags: exclude_similar (all classes)\n\
+    ///      lowercase: 23 candidates, minimum 1\n\
+    ///      uppercase: 25 candidates, minimum 1\n\
+    ///      number: 8 candidates, minimum 1\n\
+    ///      symbol: 32 candidates, minimum 1\n\
+    ///      others: 0 types, 0 candidates, minimum 0"
+    /// );
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Count the candidates in the form in which they are used
+        let normalized = self.normalized();
+        let available = |classifier: &Classifier, other: bool| {
+            classifier
+                .weighted_candidates()
+                .filter(|(c, weight)| 0 < *weight && !normalized.is_excluded(c, other))
+                .count()
+        };
+
+        writeln!(f, "length: {}", self.length)?;
+        if let Some(pattern) = &self.pattern {
+            writeln!(f, "pattern: {}", pattern)?;
+        }
+        if let Some(encoding) = &self.pool_encoding {
+            writeln!(f, "pool_encoding: {}", encoding)?;
+        }
+
+        let exclude_similar = match self.exclude_similar_scope {
+            SimilarScope::AllClasses => "exclude_similar (all classes)",
+            SimilarScope::StandardClassesOnly => "exclude_similar (standard classes only)",
+        };
+        let flags: Vec<&str> = [
+            (exclude_similar, self.exclude_similar),
+            (
+                "include_whitespace_in_candidate",
+                self.include_whitespace_in_candidate,
+            ),
+            ("raw_sample", self.raw_sample),
+            ("allow_control_characters", self.allow_control_characters),
+            ("strip_variation_selectors", self.strip_variation_selectors),
+            ("others_equal_weight", self.others_equal_weight),
+            ("no_consecutive_repeats", self.no_consecutive_repeats),
+            ("exclude_sequences", self.exclude_sequences),
+            ("unique_characters", self.unique_characters),
+            ("no_leading_zero", self.no_leading_zero),
+        ]
+        .into_iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| name)
+        .collect();
+        if flags.is_empty() {
+            writeln!(f, "flags: none")?;
+        } else {
+            writeln!(f, "flags: {}", flags.join(", "))?;
+        }
+
+        for (name, classifier) in [
+            ("lowercase", &normalized.lowercase),
+            ("uppercase", &normalized.uppercase),
+            ("number", &normalized.number),
+            ("symbol", &normalized.symbol),
+        ] {
+            write!(
+                f,
+                "{}: {} candidates, minimum {}",
+                name,
+                available(classifier, false),
+                classifier.minimum_count
+            )?;
+            if let Some(maximum) = classifier.maximum_count {
+                write!(f, ", maximum {}", maximum)?;
+            }
+            writeln!(f)?;
+        }
+
+        write!(
+            f,
+            "others: {} types, {} candidates, minimum {}",
+            self.others.len(),
+            normalized
+                .others
+                .iter()
+                .map(|classifier| available(classifier, true))
+                .sum::<usize>(),
+            self.others.iter().map(|c| c.minimum_count).sum::<u32>()
+        )
+    }
+}
+
+/// Return the min-entropy of a choice with the given weights
+///
+/// # Arguments
+///
+/// * `weights` - Weight of each option
+///
+/// # Returns
+///
+/// * `-log2(probability of the most likely option)` in bits (0.0 if there are no options)
+fn min_entropy(weights: impl IntoIterator<Item = f64>) -> f64 {
+    let (total, most) = weights
+        .into_iter()
+        .fold((0.0, 0.0), |(total, most): (f64, f64), weight| {
+            (total + weight, most.max(weight))
+        });
+    if most <= 0.0 {
+        return 0.0;
+    }
+
+    (total / most).log2()
+}
+
+/// Replace the elements of a vector, keeping the memory it has allocated
+///
+/// # Arguments
+///
+/// * `vec` - Vector to refill
+/// * `elements` - New elements
+fn refill<T>(vec: &mut Vec<T>, elements: Vec<T>) {
+    vec.clear();
+    vec.extend(elements);
+}
+
+/// Return whether the text contains any of the words without regard to case
+///
+/// # Arguments
+///
+/// * `text` - Text to check
+/// * `words` - Words to look for (empty words are ignored)
+fn contains_denied(text: &str, words: &[String]) -> bool {
+    let text = text.to_lowercase();
+    words
+        .iter()
+        .filter(|word| !word.is_empty())
+        .any(|word| text.contains(&word.to_lowercase()))
+}
+
+/// Insert a separator every `size` graphemes of a password
+///
+/// Useful for license-key style formatting such as "XXXX-XXXX-XXXX".
+/// The password is split on grapheme boundaries, so characters that consist of several scalar values are not split.
+///
+/// # Arguments
+///
+/// * `password` - Password
+/// * `size` - Number of graphemes in each group (0 to leave the password as it is)
+/// * `separator` - Separator inserted between the groups
+///
+/// # Returns
+///
+/// * Grouped password
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(password_maker::group("ABCDEFGH", 4, "-"), "ABCD-EFGH");
+/// assert_eq!(password_maker::group("ABCDEFGHIJ", 4, "-"), "ABCD-EFGH-IJ");
+/// assert_eq!(password_maker::group("a👨‍👩‍👦bc", 2, " "), "a👨‍👩‍👦 bc");
+/// ```
+pub fn group(password: &str, size: usize, separator: &str) -> String {
+    if size == 0 {
+        return password.to_string();
+    }
+
+    password
+        .graphemes(true)
+        .collect::<Vec<_>>()
+        .chunks(size)
+        .map(|chunk| chunk.concat())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Create a random number generator seeded from `seed` if it is set
+///
+/// # Arguments
+///
+/// * `seed` - Seed of the random number generator
+///
+/// # Returns
+///
+/// * Random number generator
+pub(crate) fn create_rng(seed: Option<u64>) -> Box<dyn RngCore> {
+    if let Some(seed) = seed {
+        return Box::new(ChaCha20Rng::seed_from_u64(seed));
+    }
+
+    #[cfg(test)]
+    {
+        // Use a fixed seed during unit tests to ensure reproducibility
+        // StdRng may change with version upgrades, so use ChaCha20Rng during tests to ensure future reproducibility
+        Box::new(ChaCha20Rng::seed_from_u64(0))
+    }
+    #[cfg(not(test))]
+    {
+        // Use random numbers outside of unit tests
+        Box::new(rand::thread_rng())
+    }
+}
+
+/// Create the random number generator on the ChaCha stream `index` of the seed
+///
+/// # Arguments
+///
+/// * `seed` - Seed of the random number generator
+/// * `index` - Number of the stream
+fn stream_rng(seed: u64, index: u64) -> ChaCha20Rng {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    rng.set_stream(index);
+    rng
+}
+
+/// Return the check digit that makes the digits pass the Luhn check
+///
+/// # Arguments
+///
+/// * `payload` - Digits before the check digit
+///
+/// # Returns
+///
+/// * Check digit
+fn luhn_check_digit(payload: &[u32]) -> u32 {
+    // Double every second digit from the right, starting with the one next to the check digit
+    let sum: u32 = payload
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(index, &digit)| match index % 2 {
+            0 if 9 < digit * 2 => digit * 2 - 9,
+            0 => digit * 2,
+            _ => digit,
+        })
+        .sum();
+    (10 - sum % 10) % 10
+}
+
+/// Return whether three characters are an ascending or descending sequence
+///
+/// The characters must all be ASCII digits, all lowercases, or all uppercases.
+///
+/// # Arguments
+///
+/// * `first` - First character
+/// * `second` - Second character
+/// * `third` - Third character
+fn is_sequence(first: &str, second: &str, third: &str) -> bool {
+    let (&[first], &[second], &[third]) = (first.as_bytes(), second.as_bytes(), third.as_bytes())
+    else {
+        return false;
+    };
+    let kind = |c: u8| {
+        if c.is_ascii_digit() {
+            Some(0)
+        } else if c.is_ascii_lowercase() {
+            Some(1)
+        } else if c.is_ascii_uppercase() {
+            Some(2)
+        } else {
+            None
+        }
+    };
+    let step = i16::from(second) - i16::from(first);
+
+    kind(first).is_some()
+        && kind(first) == kind(second)
+        && kind(second) == kind(third)
+        && step.abs() == 1
+        && i16::from(third) - i16::from(second) == step
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test if a password that meets the conditions can be generated
+    // If the number of characters is small, it may not be possible to generate a password that meets the conditions,
+    // so set a large number of characters (1000) for tests other than length tests
+    const PASSWORD_LENGTH: u32 = 1000;
+
+    #[test]
+    fn classifier_contains() {
+        let classifier = Classifier {
+            candidates: ["a", "あ", "👨‍👩‍👦", "🇯🇵"]
+                .iter()
+                .map(|&c| c.to_string())
+                .collect(),
+            minimum_count: 0,
+            ..Default::default()
+        };
+
+        assert!(classifier.contains("a"));
+        assert!(classifier.contains("あ"));
+        assert!(classifier.contains("👨‍👩‍👦"));
+        assert!(classifier.contains("🇯🇵"));
+
+        assert!(!classifier.contains("b"));
+        assert!(!classifier.contains("A"));
+        assert!(!classifier.contains(""));
+        // Parts of a multi-codepoint grapheme are not candidates
+        assert!(!classifier.contains("👨"));
+        assert!(!classifier.contains("🇯"));
+        // Nor is a longer sequence containing a candidate
+        assert!(!classifier.contains("👨‍👩‍👦‍👦"));
+
+        let empty = Classifier::default();
+        assert!(!empty.contains("a"));
+    }
+
+    #[test]
+    fn classifier_push_candidate() {
+        let mut classifier = Classifier {
+            candidates: vec![],
+            minimum_count: 0,
+            ..Default::default()
+        };
+
+        assert_eq!(classifier.push_candidate("a"), Ok(true));
+        assert_eq!(classifier.push_candidate("👨‍👩‍👦"), Ok(true));
+        assert_eq!(classifier.push_candidate("🇯🇵"), Ok(true));
+        assert_eq!(classifier.push_candidate("が"), Ok(true));
+
+        // Duplicates are not added
+        assert_eq!(classifier.push_candidate("a"), Ok(false));
+        assert_eq!(classifier.push_candidate("👨‍👩‍👦"), Ok(false));
+
+        // Only a single grapheme can be added
+        assert!(classifier.push_candidate("").is_err());
+        assert!(classifier.push_candidate("ab").is_err());
+        assert!(classifier.push_candidate("🇯🇵🇺🇸").is_err());
+
+        assert_eq!(classifier.candidates, vec!["a", "👨‍👩‍👦", "🇯🇵", "が"]);
+    }
+
+    #[test]
+    fn classifier_remove_candidate() {
+        let mut classifier = Classifier {
+            candidates: ["a", "👨‍👩‍👦", "👨", "a"]
+                .iter()
+                .map(|&c| c.to_string())
+                .collect(),
+            minimum_count: 1,
+            ..Default::default()
+        };
+
+        // Removing a part of a multi-codepoint grapheme does not affect the grapheme
+        assert!(classifier.remove_candidate("👨"));
+        assert_eq!(classifier.candidates, vec!["a", "👨‍👩‍👦", "a"]);
+
+        // All occurrences are removed
+        assert!(classifier.remove_candidate("a"));
+        assert_eq!(classifier.candidates, vec!["👨‍👩‍👦"]);
+
+        // Removing a missing grapheme does nothing
+        assert!(!classifier.remove_candidate("b"));
+        assert_eq!(classifier.candidates, vec!["👨‍👩‍👦"]);
+
+        classifier.clear_candidates();
+        assert!(classifier.candidates.is_empty());
+        assert_eq!(classifier.minimum_count, 1);
+    }
+
+    #[test]
+    fn default() {
+        let mut password_maker = PasswordMaker::default();
+        let password = password_maker.generate().unwrap();
+        assert_eq!(password.chars().count(), 16);
+    }
+
+    #[test]
+    /// Test if a password with a length of 0 can be generated
+    /// By default, the minimum number of characters is set to 1, so an error occurs
+    /// Set the minimum number of characters to 0 for the test
+    fn empty() {
+        let mut password_maker = PasswordMaker {
+            length: 0,
+            ..PasswordMaker::default()
+        };
+
+        // By default, the minimum number of characters for uppercase, lowercase, numbers, and symbols is set to 1, so an error occurs
+        // Therefore, set the minimum number of characters to 0 for the test
+        password_maker.uppercase.minimum_count = 0;
+        password_maker.lowercase.minimum_count = 0;
+        password_maker.number.minimum_count = 0;
+        password_maker.symbol.minimum_count = 0;
+
+        let result = password_maker.generate();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn length() {
+        // Set the password length to 8 characters
+        let mut password_maker = PasswordMaker {
+            length: 8,
+            ..PasswordMaker::default()
+        };
+        let password = password_maker.generate().unwrap();
+        assert_eq!(password.chars().count(), 8);
+
+        // Set the password length to 32 characters
+        password_maker.length = 32;
+        let password = password_maker.generate().unwrap();
+        assert_eq!(password.chars().count(), 32);
+    }
+
+    #[test]
+    fn uppercases() {
+        // By default, include uppercases
+        let mut password_maker = PasswordMaker {
+            length: PASSWORD_LENGTH,
+            ..PasswordMaker::default()
+        };
+        let password = password_maker.generate().unwrap();
+        assert!(password.chars().any(|c| c.is_ascii_uppercase()));
+
+        // Do not include uppercases
+        password_maker.uppercase = Classifier {
+            candidates: vec![],
+            minimum_count: 0,
+            ..Default::default()
+        };
+        let password = password_maker.generate().unwrap();
+        assert!(password.chars().all(|c| !c.is_ascii_uppercase()));
+
+        // Specify the types of uppercases
+        password_maker.uppercase = Classifier {
+            candidates: vec![
+                'A'.to_string(),
+                'M'.to_string(),
+                'N'.to_string(),
+                'Z'.to_string(),
+            ],
+            minimum_count: 1,
+            ..Default::default()
+        };
+        let password = password_maker.generate().unwrap();
+        // Check if the types of uppercases are only those specified
+        // Specify the first, middle, and last letters of the alphabet
+        assert!(password.contains('A'));
+        assert!(password.contains('M'));
+        assert!(password.contains('N'));
+        assert!(password.contains('Z'));
+        // Check if other uppercases are not included
+        assert!(password.chars().all(|c| !matches!(
+            c,
+            'B' | 'C'
+                | 'D'
+                | 'E'
+                | 'F'
+                | 'G'
+                | 'H'
+                | 'I'
+                | 'J'
+                | 'K'
+                | 'L'
+                | 'O'
+                | 'P'
+                | 'Q'
+                | 'R'
+                | 'S'
+                | 'T'
+                | 'U'
+                | 'V'
+                | 'W'
+                | 'X'
+                | 'Y'
+        )));
+
+        // Ensure the minimum number of characters is met
+        // No candidates for uppercases, but the minimum number of characters is set to 1
+        password_maker.length = 4;
+        password_maker.uppercase.minimum_count = 1;
+        let password = password_maker.generate().unwrap();
+        assert!(password.chars().any(|c| c.is_ascii_uppercase()));
+    }
+    #[test]
+    fn lowercases() {
+        // Include lowercases by default
+        let mut password_maker = PasswordMaker {
+            length: PASSWORD_LENGTH,
+            ..PasswordMaker::default()
+        };
+        let password = password_maker.generate().unwrap();
+        assert!(password.chars().any(|c| c.is_ascii_lowercase()));
+
+        // Do not include lowercases
+        password_maker.lowercase = Classifier {
+            candidates: vec![],
+            minimum_count: 0,
+            ..Default::default()
+        };
+        let password = password_maker.generate().unwrap();
+        assert!(password.chars().all(|c| !c.is_ascii_lowercase()));
+
+        // Specify the types of lowercases
+        password_maker.lowercase = Classifier {
+            candidates: ['a', 'm', 'n', 'z']
+                .iter()
+                .map(|&c| c.to_string())
+                .collect(),
+            minimum_count: 1,
+            ..Default::default()
+        };
+        let password = password_maker.generate().unwrap();
+        // Check if the types of lowercases are only those specified
+        // Specify the first, middle, and last letters of the alphabet
+        assert!(password.contains('a'));
+        assert!(password.contains('m'));
+        assert!(password.contains('n'));
+        assert!(password.contains('z'));
+        // Check if other lowercases are not included
+        assert!(password.chars().all(|c| !matches!(
+            c,
+            'b' | 'c'
+                | 'd'
+                | 'e'
+                | 'f'
+                | 'g'
+                | 'h'
+                | 'i'
+                | 'j'
+                | 'k'
+                | 'l'
+                | 'o'
+                | 'p'
+                | 'q'
+                | 'r'
+                | 's'
+                | 't'
+                | 'u'
+                | 'v'
+                | 'w'
+                | 'x'
+                | 'y'
+        )));
+    }
+
+    #[test]
+    fn numbers() {
+        // Include numbers by default
+        let mut password_maker = PasswordMaker {
+            length: PASSWORD_LENGTH,
+            ..PasswordMaker::default()
+        };
+        let password = password_maker.generate().unwrap();
+        assert!(password.chars().any(|c| c.is_ascii_digit()));
+
+        // Do not include numbers
+        password_maker.number = Classifier {
+            candidates: vec![],
+            minimum_count: 0,
+            ..Default::default()
+        };
+        let password = password_maker.generate().unwrap();
+        assert!(password.chars().all(|c| !c.is_ascii_digit()));
+
+        // Specify the types of numbers
+        password_maker.number = Classifier {
+            candidates: ['0', '5', '9'].iter().map(|&c| c.to_string()).collect(),
+            minimum_count: 1,
+            ..Default::default()
+        };
+        let password = password_maker.generate().unwrap();
+        // Check if the types of numbers are only those specified
+        assert!(password.contains('0'));
+        assert!(password.contains('5'));
+        assert!(password.contains('9'));
+        // Check if other numbers are not included
+        assert!(password
+            .chars()
+            .all(|c| !matches!(c, '1' | '2' | '3' | '4' | '6' | '7' | '8')));
+
+        // Ensure the minimum number of characters is met
+
+        // Error case
+        // No candidates for numbers, but the minimum number of characters is set to 1
+        password_maker.number = Classifier {
+            candidates: vec![],
+            minimum_count: 1,
+            ..Default::default()
+        };
+        let password = password_maker.generate();
+        assert!(password.is_err());
+    }
+
+    #[test]
+    fn symbols() {
+        // Include symbols by default
+        let mut password_maker = PasswordMaker {
+            length: PASSWORD_LENGTH,
+            ..PasswordMaker::default()
+        };
+        let password = password_maker.generate().unwrap();
+        assert!(password.chars().any(|c| c.is_ascii_punctuation()));
+
+        // Do not include symbols
+        password_maker.symbol = Classifier {
+            candidates: vec![],
+            minimum_count: 0,
+            ..Default::default()
+        };
+        let password = password_maker.generate().unwrap();
+        assert!(password.chars().all(|c| !c.is_ascii_punctuation()));
+
+        // Specify the types of symbols
+        password_maker.symbol = Classifier {
+            candidates: ['!', '@', '~'].iter().map(|&c| c.to_string()).collect(),
+            minimum_count: 1,
+            ..Default::default()
+        };
+        let password = password_maker.generate().unwrap();
+        // Check if the types of symbols are only those specified
+        assert!(password.contains('!'));
+        assert!(password.contains('@'));
+        assert!(password.contains('~'));
+        // Check if other symbols are not included
+        assert!(password.chars().all(|c| !matches!(
+            c,
+            '"' | '#'
+                | '$'
+                | '%'
+                | '&'
+                | '\''
+                | '('
+                | ')'
+                | '*'
+                | '+'
+                | ','
+                | '-'
+                | '.'
+                | '/'
+                | ':'
+                | ';'
+                | '<'
+                | '='
+                | '>'
+                | '?'
+                | '['
+                | '\\'
+                | ']'
+                | '^'
+                | '_'
+                | '`'
+                | '{'
+                | '|'
+                | '}'
+        )));
+    }
+
+    #[test]
+    fn similar() {
+        // Do not include similar characters
+        let mut password_maker = PasswordMaker {
+            length: PASSWORD_LENGTH,
+            exclude_similar: true,
+            ..PasswordMaker::default()
+        };
+        let password = password_maker.generate().unwrap();
+        assert!(password
+            .chars()
+            .all(|c| !matches!(c, 'i' | 'l' | '1' | 'o' | '0' | 'O')));
+
+        // Include similar characters
+        password_maker.exclude_similar = false;
+        let password = password_maker.generate().unwrap();
+        assert!(password
+            .chars()
+            .any(|c| matches!(c, 'i' | 'l' | '1' | 'o' | '0' | 'O')));
+
+        // Include similar characters by default
+        // A long password makes it almost certain, whatever the random numbers are
+        let mut password_maker = PasswordMaker {
+            length: PASSWORD_LENGTH,
+            ..PasswordMaker::default()
+        };
+        let password = password_maker.generate().unwrap();
+        assert!(password
+            .chars()
+            .any(|c| matches!(c, 'i' | 'l' | '1' | 'o' | '0' | 'O')));
+    }
+
+    #[test]
+    fn similar_characters() {
+        // Replace the similar characters
+        let mut password_maker = PasswordMaker {
+            length: PASSWORD_LENGTH,
+            exclude_similar: true,
+            similar_characters: ["B", "8", "5", "S", "2", "Z"]
+                .iter()
+                .map(|c| c.to_string())
+                .collect(),
+            ..PasswordMaker::default()
+        };
+        let candidates = password_maker.candidates();
+        assert!(!candidates
+            .iter()
+            .any(|c| ["B", "8", "5", "S", "2", "Z"].contains(&c.as_str())));
+        assert!(candidates.contains(&"0".to_string()));
+        assert!(candidates.contains(&"l".to_string()));
+
+        let password = password_maker.generate().unwrap();
+        assert!(password
+            .chars()
+            .all(|c| !matches!(c, 'B' | '8' | '5' | 'S' | '2' | 'Z')));
+
+        // The similar characters are applied only when excluding them
+        password_maker.exclude_similar = false;
+        assert!(password_maker.candidates().contains(&"B".to_string()));
+    }
+
+    #[test]
+    fn similar_scope() {
+        let mut password_maker = PasswordMaker {
+            length: 32,
+            exclude_similar: true,
+            similar_characters: vec!["o".to_string(), "０".to_string()],
+            others: vec![Classifier {
+                candidates: vec!["０".to_string()],
+                minimum_count: 8,
+                ..Default::default()
+            }],
+            ..PasswordMaker::default()
+        };
+
+        // The other characters are filtered by default
+        assert!(!password_maker.candidates().contains(&"０".to_string()));
+        assert_eq!(
+            password_maker.validate(),
+            Err(PasswordError::EmptyCandidatesWithMinimum {
+                class: "Other characters at index 0 without similar characters".to_string(),
+                minimum: 8,
+            })
+        );
+
+        // Only the standard character types are filtered
+        password_maker.exclude_similar_scope = SimilarScope::StandardClassesOnly;
+        let candidates = password_maker.candidates();
+        assert!(candidates.contains(&"０".to_string()));
+        assert!(!candidates.contains(&"o".to_string()));
+        for seed in 0..20 {
+            let password = password_maker
+                .generate_with_rng(&mut ChaCha20Rng::seed_from_u64(seed))
+                .unwrap();
+            assert!(8 <= password.matches('０').count(), "{}", password);
+            assert!(!password.contains('o'), "{}", password);
+        }
+        let settings = password_maker.to_string();
+        assert!(
+            settings.contains("flags: exclude_similar (standard classes only)\n"),
+            "{}",
+            settings
+        );
+        assert!(settings.ends_with("others: 1 types, 1 candidates, minimum 8"));
+    }
+
+    #[test]
+    fn similar_minimum_count() {
+        // The characters overwritten to meet the minimum counts are not similar either
+        let mut password_maker = PasswordMaker {
+            length: 64,
+            exclude_similar: true,
+            uppercase: Classifier {
+                candidates: vec!["O".to_string(), "P".to_string()],
+                minimum_count: 16,
+                ..Default::default()
+            },
+            lowercase: Classifier {
+                candidates: vec![
+                    "i".to_string(),
+                    "l".to_string(),
+                    "o".to_string(),
+                    "x".to_string(),
+                ],
+                minimum_count: 16,
+                ..Default::default()
+            },
+            number: Classifier {
+                candidates: vec!["0".to_string(), "1".to_string(), "7".to_string()],
+                minimum_count: 16,
+                ..Default::default()
+            },
+            ..PasswordMaker::default()
+        };
+        for seed in 0..100 {
+            let password = password_maker
+                .generate_with_rng(&mut ChaCha20Rng::seed_from_u64(seed))
+                .unwrap();
+            assert!(password
+                .chars()
+                .all(|c| !matches!(c, 'i' | 'l' | '1' | 'o' | '0' | 'O')));
+        }
+
+        // No candidates remain after excluding similar characters, so the minimum count cannot be met
+        password_maker.number.candidates = vec!["0".to_string(), "1".to_string()];
+        let error = password_maker.generate().unwrap_err();
+        assert_eq!(
+            error,
+            PasswordError::EmptyCandidatesWithMinimum {
+                class: "Numbers without similar characters".to_string(),
+                minimum: 16,
+            }
+        );
+
+        // Without the exclusion, the similar characters can be used
+        password_maker.exclude_similar = false;
+        assert!(password_maker.generate().is_ok());
+    }
+
+    #[test]
+    fn exclude_characters() {
+        let mut password_maker = PasswordMaker {
+            length: 500,
+            include_whitespace_in_candidate: true,
+            exclude_characters: vec!["\"".to_string(), "\\".to_string(), " ".to_string()],
+            ..PasswordMaker::default()
+        };
+        let pool = password_maker.effective_pool();
+        assert_eq!(pool.len(), 94 + 1 - 3);
+        let password = password_maker.generate().unwrap();
+        assert!(!password.contains(['"', '\\', ' ']), "{}", password);
+
+        // The excluded characters are not used to meet the minimum counts
+        password_maker.symbol.candidates = vec!["\"".to_string(), "\\".to_string()];
+        assert_eq!(
+            password_maker.generate(),
+            Err(PasswordError::EmptyCandidatesWithMinimum {
+                class: "Symbols without excluded characters".to_string(),
+                minimum: 1,
+            })
+        );
+        password_maker.symbol.minimum_count = 0;
+        assert!(!password_maker.generate().unwrap().contains(['"', '\\']));
+    }
+
+    #[test]
+    fn whitespace() {
+        // Do not include whitespace
+        let mut password_maker = PasswordMaker {
+            length: PASSWORD_LENGTH,
+            include_whitespace_in_candidate: false,
+            ..PasswordMaker::default()
+        };
+        let password = password_maker.generate().unwrap();
+        assert!(!password.contains(' '));
+
+        // Include whitespace
+        password_maker.include_whitespace_in_candidate = true;
+        let password = password_maker.generate().unwrap();
+        assert!(password.contains(' '));
+    }
+
+    #[test]
+    fn raw_sample() {
+        // Require only uppercases so that the overwrite step changes the password
+        let mut password_maker = PasswordMaker {
+            length: 16,
+            ..PasswordMaker::default()
+        };
+        password_maker.uppercase.minimum_count = 16;
+        password_maker.lowercase.minimum_count = 0;
+        password_maker.number.minimum_count = 0;
+        password_maker.symbol.minimum_count = 0;
+
+        let password = password_maker.generate().unwrap();
+        assert!(password.chars().all(|c| c.is_ascii_uppercase()));
+
+        // Without the overwrite step, the password is the pure sample drawn with the same seed
+        password_maker.raw_sample = true;
+        let password = password_maker.generate().unwrap();
+
+        let candidates = password_maker.candidates();
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let expected: String = (0..16)
+            .map(|_| candidates.choose(&mut rng).unwrap().as_str())
+            .collect();
+        assert_eq!(password, expected);
+        assert!(!password.chars().all(|c| c.is_ascii_uppercase()));
+    }
+
+    #[test]
+    fn other_chars() {
+        // Do not include other characters
+        // For testing other characters, include only numbers, excluding alphabets and symbols
+        let mut password_maker = PasswordMaker {
+            length: PASSWORD_LENGTH,
+            uppercase: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            lowercase: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            symbol: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            ..PasswordMaker::default()
+        };
+        let password = password_maker.generate().unwrap();
+        assert!(password.chars().all(|c| c.is_ascii_digit()));
+
+        // Include other characters
+        // Include Variable-width characters (characters that are treated as one character in char type, such as ⌨️, are not included)
+        password_maker.others = vec![Classifier {
+            candidates: ['あ', '🍣', '！'].iter().map(|&c| c.to_string()).collect(),
+            minimum_count: 1,
+            ..Default::default()
+        }];
+        let password = password_maker.generate().unwrap();
+        assert!(password.contains('あ'));
+        assert!(password.contains('🍣'));
+        assert!(password.contains('！'));
+        assert!(password.chars().any(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn others_unit() {
+        let family = "👨‍👩‍👦";
+        let graphemes = PasswordMaker {
+            length: 20,
+            others: vec![Classifier {
+                candidates: vec![family.to_string()],
+                minimum_count: 10,
+                ..Default::default()
+            }],
+            ..PasswordMaker::default()
+        };
+        let scalars = PasswordMaker {
+            others_unit: GraphemeUnit::Scalar,
+            ..graphemes.clone()
+        };
+
+        assert_eq!(GraphemeUnit::Grapheme.split(family), vec![family]);
+        assert_eq!(
+            GraphemeUnit::Scalar.split(family),
+            vec!["👨", "\u{200D}", "👩", "\u{200D}", "👦"]
+        );
+
+        // The family is one candidate, or its five scalar values
+        let candidates = graphemes.candidates();
+        assert_eq!(candidates.len(), 94 + 1);
+        assert!(candidates.contains(&family.to_string()));
+        // The zero width joiners are one candidate
+        let candidates = scalars.candidates();
+        assert_eq!(candidates.len(), 94 + 4);
+        assert!(!candidates.contains(&family.to_string()));
+        assert!(candidates.contains(&"\u{200D}".to_string()));
+        assert_eq!(scalars.effective_pool().len(), 94 + 4);
+
+        // The minimum count counts whole families, or scalar values
+        let password = graphemes.clone().generate().unwrap();
+        assert!(password.matches(family).count() >= 10);
+        assert!(graphemes.composition(&password).others[0] >= 10);
+        let password = scalars.clone().generate().unwrap();
+        assert_eq!(password.chars().count(), 20);
+        assert!(scalars.composition(&password).others[0] >= 10);
+
+        // The scalar values inherit the weight of the candidate
+        let mut weighted = PasswordMaker {
+            length: 1000,
+            others: vec![Classifier {
+                candidates: vec![family.to_string(), "😀".to_string()],
+                minimum_count: 0,
+                weights: vec![0, 1],
+                ..Default::default()
+            }],
+            ..scalars
+        };
+        assert!(!weighted.generate().unwrap().contains('\u{200D}'));
+    }
+
+    #[test]
+    fn others_equal_weight() {
+        // A list of 2 candidates and a list of 2000 candidates, without other types
+        let mut password_maker = PasswordMaker {
+            length: PASSWORD_LENGTH,
+            uppercase: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            lowercase: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            number: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            symbol: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            others: vec![
+                Classifier {
+                    candidates: vec!["あ".to_string(), "い".to_string()],
+                    minimum_count: 0,
+                    ..Default::default()
+                },
+                Classifier {
+                    candidates: ('\u{4E00}'..'\u{55D0}').map(|c| c.to_string()).collect(),
+                    minimum_count: 0,
+                    ..Default::default()
+                },
+            ],
+            ..PasswordMaker::default()
+        };
+        let count_small =
+            |password: &str| password.chars().filter(|&c| c == 'あ' || c == 'い').count();
+
+        // Without equal weighting, the small list is chosen about 1 in 1000 times
+        let password = password_maker.generate().unwrap();
+        assert!(count_small(&password) < 50);
+
+        // With equal weighting, the small list is chosen about half of the time
+        password_maker.others_equal_weight = true;
+        let password = password_maker.generate().unwrap();
+        assert!(300 < count_small(&password));
+        assert_eq!(password.chars().count(), PASSWORD_LENGTH as usize);
+    }
+
+    #[test]
+    fn generate_with_rng() {
+        let mut password_maker = PasswordMaker::default();
+
+        // The same generator state generates the same password
+        let password = password_maker
+            .generate_with_rng(&mut ChaCha20Rng::seed_from_u64(1))
+            .unwrap();
+        let password2 = password_maker
+            .generate_with_rng(&mut ChaCha20Rng::seed_from_u64(1))
+            .unwrap();
+        assert_eq!(password, password2);
+        assert_eq!(password.chars().count(), 16);
+
+        // Consecutive passwords from one generator differ
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        let password = password_maker.generate_with_rng(&mut rng).unwrap();
+        let password2 = password_maker.generate_with_rng(&mut rng).unwrap();
+        assert_ne!(password, password2);
+
+        // Trait objects can also be used
+        let rng: &mut dyn RngCore = &mut ChaCha20Rng::seed_from_u64(1);
+        assert_eq!(password, password_maker.generate_with_rng(rng).unwrap());
+
+        // Errors are returned as with generate()
+        password_maker.length = 0;
+        assert!(password_maker
+            .generate_with_rng(&mut ChaCha20Rng::seed_from_u64(1))
+            .is_err());
+    }
+
+    #[test]
+    fn generate_analyzed() {
+        let mut password_maker = PasswordMaker {
+            length: 32,
+            include_whitespace_in_candidate: true,
+            others: vec![Classifier {
+                candidates: ['あ', '🍣', '！'].iter().map(|&c| c.to_string()).collect(),
+                minimum_count: 3,
+                ..Default::default()
+            }],
+            ..PasswordMaker::default()
+        };
+        let generated = password_maker
+            .generate_analyzed(&mut ChaCha20Rng::seed_from_u64(0))
+            .unwrap();
+
+        assert_eq!(
+            generated.password,
+            password_maker
+                .generate_with_rng(&mut ChaCha20Rng::seed_from_u64(0))
+                .unwrap()
+        );
+        assert_eq!(generated.length, 32);
+
+        // The composition sums to the length and meets the minimum counts
+        let composition = &generated.composition;
+        assert_eq!(
+            composition.lowercase
+                + composition.uppercase
+                + composition.number
+                + composition.symbol
+                + composition.others.iter().sum::<usize>()
+                + composition.whitespace
+                + composition.unclassified,
+            generated.length
+        );
+        assert_eq!(composition.unclassified, 0);
+        assert!(1 <= composition.lowercase);
+        assert!(1 <= composition.uppercase);
+        assert!(1 <= composition.number);
+        assert!(1 <= composition.symbol);
+        assert!(3 <= composition.others[0]);
+
+        assert_eq!(generated.entropy, password_maker.entropy());
+
+        // Invalid settings
+        password_maker.length = 0;
+        assert!(password_maker
+            .generate_analyzed(&mut ChaCha20Rng::seed_from_u64(0))
+            .is_err());
+    }
+
+    #[test]
+    fn composition() {
+        let password_maker = PasswordMaker {
+            others: vec![
+                Classifier {
+                    candidates: vec!["あ".to_string(), "a".to_string()],
+                    minimum_count: 0,
+                    ..Default::default()
+                },
+                Classifier {
+                    candidates: vec!["👨‍👩‍👦".to_string()],
+                    minimum_count: 0,
+                    ..Default::default()
+                },
+            ],
+            ..PasswordMaker::default()
+        };
+
+        // "a" is counted as a lowercase, and whitespace is not a candidate
+        let composition = password_maker.composition("aA1!あ👨‍👩‍👦👨‍👩‍👦 μ");
+        assert_eq!(
+            composition,
+            Composition {
+                lowercase: 1,
+                uppercase: 1,
+                number: 1,
+                symbol: 1,
+                others: vec![1, 2],
+                whitespace: 0,
+                unclassified: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn generate_unique() {
+        let mut password_maker = PasswordMaker {
+            length: 2,
+            uppercase: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            lowercase: Classifier {
+                candidates: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            number: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            symbol: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            ..PasswordMaker::default()
+        };
+
+        // All 9 passwords can be generated
+        let passwords = password_maker.generate_unique(9).unwrap();
+        assert_eq!(passwords.len(), 9);
+        assert_eq!(passwords.iter().collect::<IndexSet<_>>().len(), 9);
+
+        // Only 9 passwords exist, which is detected before generating
+        let error = password_maker.generate_unique(10).unwrap_err();
+        assert_eq!(
+            error,
+            PasswordError::KeyspaceTooSmall {
+                requested: 10,
+                keyspace: 9,
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Cannot produce 10 unique passwords; the keyspace is only 9. Please increase the password length or add more candidates."
+        );
+
+        // The minimum counts narrow the keyspace of 16 further: only "11" and the 6 passwords like "1a" and "a1" exist
+        let mut narrowed = PasswordMaker {
+            number: Classifier {
+                candidates: vec!["1".to_string()],
+                minimum_count: 1,
+                ..Default::default()
+            },
+            ..password_maker.clone()
+        };
+        let error = narrowed.generate_unique(8).unwrap_err();
+        assert_eq!(
+            error,
+            PasswordError::UniqueExhausted {
+                requested: 8,
+                generated: 7,
+            }
+        );
+        assert!(error.to_string().contains("search space is exhausted"));
+
+        assert_eq!(
+            password_maker.generate_unique(0).unwrap(),
+            Vec::<String>::new()
+        );
+
+        // Invalid settings
+        password_maker.length = 0;
+        assert_eq!(
+            password_maker.generate_unique(1),
+            Err(PasswordError::ZeroLength)
+        );
+    }
+
+    #[test]
+    fn generate_unique_with() {
+        let mut password_maker = PasswordMaker::default();
+
+        // A collided password is generated again with the same index
+        let mut calls = vec![];
+        let outputs = ["a", "a", "a", "b", "c"];
+        let passwords = password_maker
+            .generate_unique_with(3, 3, |_, index| {
+                calls.push(index);
+                Ok::<_, PasswordError>(outputs[calls.len() - 1].to_string())
+            })
+            .unwrap();
+        assert_eq!(passwords, vec!["a", "b", "c"]);
+        assert_eq!(calls, vec![0, 1, 1, 1, 2]);
+
+        // The number of collisions of each password is limited
+        let error = password_maker
+            .generate_unique_with(2, 3, |_, _| Ok::<_, PasswordError>("a".to_string()))
+            .unwrap_err();
+        assert_eq!(
+            error,
+            PasswordError::UniqueExhausted {
+                requested: 2,
+                generated: 1,
+            }
+        );
+
+        // The errors of the function are returned as they are
+        let error = password_maker
+            .generate_unique_with(2, 3, |_, _| Err(PasswordError::NoCandidates))
+            .unwrap_err();
+        assert_eq!(error, PasswordError::NoCandidates);
+    }
+
+    #[test]
+    fn fill_deterministic() {
+        let password_maker = PasswordMaker {
+            length: 8,
+            ..PasswordMaker::default()
+        };
+        let candidates = password_maker.candidates();
+        let index_of = |c: &str| candidates.iter().position(|x| x == c).unwrap();
+
+        // The sequence is placed as it is without the constraints
+        let sequence = ["a", "a", "3", "3", "x", "Y", "z", "!"].map(index_of);
+        let raw = PasswordMaker {
+            raw_sample: true,
+            ..password_maker.clone()
+        };
+        let mut picks = sequence.iter().copied();
+        assert_eq!(
+            raw.fill_deterministic(|_| picks.next().unwrap()).unwrap(),
+            "aa33xYz!"
+        );
+
+        // A sequence of lowercases only gets exactly one character of each other type,
+        // and the same picker always gives the same password
+        let password = password_maker.fill_deterministic(|_| 0).unwrap();
+        assert_eq!(password, password_maker.fill_deterministic(|_| 0).unwrap());
+        assert_eq!(
+            password.chars().filter(|c| c.is_ascii_uppercase()).count(),
+            1
+        );
+        assert_eq!(password.chars().filter(|c| c.is_ascii_digit()).count(), 1);
+        assert_eq!(
+            password
+                .chars()
+                .filter(|c| c.is_ascii_punctuation())
+                .count(),
+            1
+        );
+        assert!(password.chars().filter(|&c| c == 'a').count() >= 4);
+
+        // The maximum count rewrites the excess characters
+        let password_maker = PasswordMaker {
+            length: 8,
+            lowercase: Classifier {
+                maximum_count: Some(2),
+                ..PasswordMaker::default().lowercase
+            },
+            ..PasswordMaker::default()
+        };
+        let password = password_maker.fill_deterministic(|_| 0).unwrap();
+        assert_eq!(
+            password.chars().filter(|c| c.is_ascii_lowercase()).count(),
+            2
+        );
+
+        // The settings are validated before the picker is called
+        let password_maker = PasswordMaker {
+            length: 0,
+            ..PasswordMaker::default()
+        };
+        assert!(password_maker
+            .fill_deterministic(|_| unreachable!())
+            .is_err());
+    }
+
+    #[test]
+    fn no_consecutive_repeats() {
+        let has_repeats = |password: &str| {
+            let graphemes: Vec<&str> = password.graphemes(true).collect();
+            graphemes.windows(2).any(|pair| pair[0] == pair[1])
+        };
+
+        let mut password_maker = PasswordMaker {
+            length: 10_000,
+            no_consecutive_repeats: true,
+            ..PasswordMaker::default()
+        };
+        let password = password_maker.generate().unwrap();
+        assert!(!has_repeats(&password));
+
+        // A sequence of the same character is rewritten, keeping the minimum counts
+        let password_maker = PasswordMaker {
+            length: 64,
+            no_consecutive_repeats: true,
+            ..PasswordMaker::default()
+        };
+        let password = password_maker.fill_deterministic(|_| 0).unwrap();
+        assert!(!has_repeats(&password));
+        assert!(password.chars().any(|c| c.is_ascii_uppercase()));
+        assert!(password.chars().any(|c| c.is_ascii_digit()));
+        assert!(password.chars().any(|c| c.is_ascii_punctuation()));
+
+        // A type with a single candidate is swapped apart instead of rewritten
+        let password_maker = PasswordMaker {
+            length: 4,
+            lowercase: Classifier {
+                candidates: vec!["a".to_string()],
+                minimum_count: 2,
+                ..PasswordMaker::default().lowercase
+            },
+            uppercase: Classifier {
+                candidates: vec!["B".to_string()],
+                minimum_count: 2,
+                ..PasswordMaker::default().uppercase
+            },
+            number: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..PasswordMaker::default().number
+            },
+            symbol: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..PasswordMaker::default().symbol
+            },
+            no_consecutive_repeats: true,
+            ..PasswordMaker::default()
+        };
+        let password = password_maker.fill_deterministic(|_| 0).unwrap();
+        assert!(password == "aBaB" || password == "BaBa", "{}", password);
+
+        // Other characters with multiple graphemes per candidate are compared as graphemes
+        let mut password_maker = PasswordMaker {
+            length: 200,
+            others: vec![Classifier {
+                candidates: vec!["👨‍👩‍👦".to_string(), "é".to_string()],
+                minimum_count: 100,
+                ..Default::default()
+            }],
+            no_consecutive_repeats: true,
+            ..PasswordMaker::default()
+        };
+        assert!(!has_repeats(&password_maker.generate().unwrap()));
+    }
+
+    #[test]
+    fn no_consecutive_repeats_err() {
+        // A single candidate always repeats
+        let mut password_maker = PasswordMaker {
+            length: 2,
+            lowercase: Classifier {
+                candidates: vec!["a".to_string()],
+                minimum_count: 0,
+                ..PasswordMaker::default().lowercase
+            },
+            uppercase: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..PasswordMaker::default().uppercase
+            },
+            number: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..PasswordMaker::default().number
+            },
+            symbol: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..PasswordMaker::default().symbol
+            },
+            no_consecutive_repeats: true,
+            ..PasswordMaker::default()
+        };
+        assert_eq!(
+            password_maker.generate(),
+            Err(PasswordError::ConsecutiveRepeatsUnsatisfiable)
+        );
+
+        // A single character cannot repeat
+        password_maker.length = 1;
+        assert_eq!(password_maker.generate(), Ok("a".to_string()));
+
+        // Three "a" cannot be separated by one "B"
+        let password_maker = PasswordMaker {
+            length: 4,
+            lowercase: Classifier {
+                candidates: vec!["a".to_string()],
+                minimum_count: 3,
+                ..PasswordMaker::default().lowercase
+            },
+            uppercase: Classifier {
+                candidates: vec!["B".to_string()],
+                minimum_count: 1,
+                ..PasswordMaker::default().uppercase
+            },
+            ..password_maker
+        };
+        assert_eq!(
+            password_maker.fill_deterministic(|_| 0),
+            Err(PasswordError::ConsecutiveRepeatsUnsatisfiable)
+        );
+    }
+
+    #[test]
+    fn is_sequence() {
+        assert!(super::is_sequence("a", "b", "c"));
+        assert!(super::is_sequence("3", "2", "1"));
+        assert!(super::is_sequence("X", "Y", "Z"));
+
+        assert!(!super::is_sequence("a", "c", "e"));
+        assert!(!super::is_sequence("1", "2", "1"));
+        assert!(!super::is_sequence("a", "a", "a"));
+        // Different kinds of characters
+        assert!(!super::is_sequence("8", "9", ":"));
+        assert!(!super::is_sequence("Y", "Z", "["));
+        assert!(!super::is_sequence("b", "c", "D"));
+        // Not ASCII alphanumeric
+        assert!(!super::is_sequence("#", "$", "%"));
+        assert!(!super::is_sequence("あ", "ぃ", "い"));
+    }
+
+    #[test]
+    fn exclude_sequences() {
+        let has_sequences = |password: &str| {
+            let graphemes: Vec<&str> = password.graphemes(true).collect();
+            graphemes
+                .windows(3)
+                .any(|run| super::is_sequence(run[0], run[1], run[2]))
+        };
+
+        // A small numeric pool makes sequences frequent without the option
+        let numbers_only = PasswordMaker {
+            length: 1000,
+            uppercase: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..PasswordMaker::default().uppercase
+            },
+            lowercase: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..PasswordMaker::default().lowercase
+            },
+            number: Classifier {
+                candidates: ["1", "2", "3"].map(String::from).to_vec(),
+                ..PasswordMaker::default().number
+            },
+            symbol: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..PasswordMaker::default().symbol
+            },
+            ..PasswordMaker::default()
+        };
+        assert!(has_sequences(&numbers_only.clone().generate().unwrap()));
+
+        let mut password_maker = PasswordMaker {
+            exclude_sequences: true,
+            ..numbers_only.clone()
+        };
+        assert!(!has_sequences(&password_maker.generate().unwrap()));
+
+        // Together with no_consecutive_repeats
+        let mut password_maker = PasswordMaker {
+            no_consecutive_repeats: true,
+            ..password_maker
+        };
+        let password = password_maker.generate().unwrap();
+        assert!(!has_sequences(&password));
+        assert!(!password
+            .as_bytes()
+            .windows(2)
+            .any(|pair| pair[0] == pair[1]));
+
+        // The default candidates
+        let mut password_maker = PasswordMaker {
+            length: 10_000,
+            exclude_sequences: true,
+            ..PasswordMaker::default()
+        };
+        assert!(!has_sequences(&password_maker.generate().unwrap()));
+
+        // A sequence of picked characters is rewritten
+        let password_maker = PasswordMaker {
+            length: 3,
+            raw_sample: true,
+            exclude_sequences: true,
+            ..numbers_only
+        };
+        let mut picks = [0, 1, 2].into_iter();
+        let password = password_maker
+            .fill_deterministic(|_| picks.next().unwrap())
+            .unwrap();
+        assert!(password.starts_with("12"));
+        assert_ne!(password, "123");
+    }
+
+    #[test]
+    fn exclude_sequences_err() {
+        // "2" three times needs to be separated by "1" and "3" without repeats, which always makes "123" or "321"
+        let single = |candidate: &str, minimum_count| Classifier {
+            candidates: vec![candidate.to_string()],
+            minimum_count,
+            ..Default::default()
+        };
+        let password_maker = PasswordMaker {
+            length: 5,
+            uppercase: Classifier::default(),
+            lowercase: Classifier::default(),
+            number: Classifier::default(),
+            symbol: Classifier::default(),
+            others: vec![single("1", 1), single("2", 3), single("3", 1)],
+            no_consecutive_repeats: true,
+            exclude_sequences: true,
+            ..PasswordMaker::default()
+        };
+        assert_eq!(
+            password_maker.validate(),
+            Err(PasswordError::SequencesUnsatisfiable)
+        );
+        assert_eq!(
+            password_maker.fill_deterministic(|_| 0),
+            Err(PasswordError::SequencesUnsatisfiable)
+        );
+
+        // Repeating "2" avoids the sequences
+        let mut repeats_allowed = PasswordMaker {
+            no_consecutive_repeats: false,
+            ..password_maker.clone()
+        };
+        assert!(repeats_allowed.validate().is_ok());
+        assert!(repeats_allowed.generate().is_ok());
+
+        // A longer password has room to separate "1" and "3"
+        let mut longer = PasswordMaker {
+            length: 6,
+            ..password_maker
+        };
+        assert!(longer.validate().is_ok());
+        assert!(longer.generate().is_ok());
+    }
+
+    #[test]
+    fn unique_characters() {
+        let is_unique = |password: &str| {
+            let graphemes: Vec<&str> = password.graphemes(true).collect();
+            graphemes.iter().collect::<IndexSet<_>>().len() == graphemes.len()
+        };
+
+        let mut password_maker = PasswordMaker {
+            length: 20,
+            unique_characters: true,
+            ..PasswordMaker::default()
+        };
+        for _ in 0..100 {
+            assert!(is_unique(&password_maker.generate().unwrap()));
+        }
+
+        // All distinct candidates are used
+        let mut password_maker = PasswordMaker {
+            length: 94,
+            unique_characters: true,
+            ..PasswordMaker::default()
+        };
+        let password = password_maker.generate().unwrap();
+        assert!(is_unique(&password));
+        assert_eq!(
+            password.chars().collect::<IndexSet<_>>().len(),
+            password_maker.effective_pool().len()
+        );
+
+        // The minimum and maximum counts are met with distinct characters
+        let mut password_maker = PasswordMaker {
             length: 16,
-            exclude_similar: false,
-            // Whitespace is less commonly used in passwords compared to other symbols,
-            // and leading or trailing whitespace can cause input errors, so it is disabled by default.
-            include_whitespace_in_candidate: false,
+            number: Classifier {
+                minimum_count: 10,
+                ..PasswordMaker::default().number
+            },
+            uppercase: Classifier {
+                maximum_count: Some(1),
+                ..PasswordMaker::default().uppercase
+            },
+            others: vec![Classifier {
+                candidates: vec!["👨‍👩‍👦".to_string(), "あ".to_string()],
+                minimum_count: 2,
+                ..Default::default()
+            }],
+            unique_characters: true,
+            ..PasswordMaker::default()
+        };
+        let password = password_maker.generate().unwrap();
+        assert!(is_unique(&password));
+        let composition = password_maker.composition(&password);
+        assert_eq!(composition.number, 10);
+        assert_eq!(composition.uppercase, 1);
+        assert_eq!(composition.others, vec![2]);
+
+        // Together with the rewriting of the sequences
+        let mut password_maker = PasswordMaker {
+            length: 10,
+            uppercase: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..PasswordMaker::default().uppercase
+            },
             lowercase: Classifier {
-                candidates: ('a'..='z').map(|c| c.to_string()).collect(),
-                minimum_count: 1,
+                candidates: vec![],
+                minimum_count: 0,
+                ..PasswordMaker::default().lowercase
+            },
+            symbol: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..PasswordMaker::default().symbol
+            },
+            unique_characters: true,
+            exclude_sequences: true,
+            ..PasswordMaker::default()
+        };
+        for _ in 0..100 {
+            let password = password_maker.generate().unwrap();
+            assert!(is_unique(&password));
+            let digits: Vec<String> = password.chars().map(String::from).collect();
+            assert!(!digits
+                .windows(3)
+                .any(|run| super::is_sequence(&run[0], &run[1], &run[2])));
+        }
+    }
+
+    #[test]
+    fn unique_characters_err() {
+        // 95 characters from 94 distinct candidates
+        let mut password_maker = PasswordMaker {
+            length: 95,
+            unique_characters: true,
+            ..PasswordMaker::default()
+        };
+        assert_eq!(
+            password_maker.generate(),
+            Err(PasswordError::LengthExceedsDistinctCandidates {
+                length: 95,
+                distinct: 94,
+            })
+        );
+
+        // Duplicated candidates are counted once
+        password_maker.length = 3;
+        password_maker.number = Classifier {
+            candidates: ["1", "1", "2"].map(String::from).to_vec(),
+            minimum_count: 3,
+            ..PasswordMaker::default().number
+        };
+        password_maker.uppercase.minimum_count = 0;
+        password_maker.lowercase.minimum_count = 0;
+        password_maker.symbol.minimum_count = 0;
+        assert_eq!(
+            password_maker.generate(),
+            Err(PasswordError::MinimumExceedsDistinctCandidates {
+                class: "Numbers".to_string(),
+                minimum: 3,
+                distinct: 2,
+            })
+        );
+
+        // A candidate shared by two types is used for only one of them
+        password_maker.number.minimum_count = 2;
+        password_maker.others = vec![Classifier {
+            candidates: vec!["1".to_string()],
+            minimum_count: 1,
+            ..Default::default()
+        }];
+        assert_eq!(
+            password_maker.generate(),
+            Err(PasswordError::MinimumExceedsDistinctCandidates {
+                class: "Other characters at index 0".to_string(),
+                minimum: 1,
+                distinct: 0,
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of range")]
+    fn fill_deterministic_panic() {
+        let password_maker = PasswordMaker::default();
+        let _ = password_maker.fill_deterministic(|candidates| candidates.len());
+    }
+
+    #[test]
+    fn generate_iter() {
+        let mut password_maker = PasswordMaker {
+            length: 20,
+            ..PasswordMaker::default()
+        };
+
+        let passwords: Vec<String> = password_maker
+            .generate_iter()
+            .take(1000)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(passwords.len(), 1000);
+        assert!(passwords.iter().all(|password| password.len() == 20));
+
+        // The passwords share one random number generator, so they differ from each other
+        assert_eq!(passwords.iter().collect::<IndexSet<_>>().len(), 1000);
+
+        // Errors are yielded for each password
+        password_maker.length = 0;
+        assert!(password_maker
+            .generate_iter()
+            .take(3)
+            .all(|password| password.is_err()));
+    }
+
+    #[test]
+    #[cfg(feature = "zeroize")]
+    fn generate_zeroizing() {
+        let mut password_maker = PasswordMaker {
+            seed: Some(42),
+            ..PasswordMaker::default()
+        };
+
+        let password: Zeroizing<String> = password_maker.generate_zeroizing().unwrap();
+        assert_eq!(*password, password_maker.generate().unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "secrecy")]
+    fn generate_secret() {
+        use secrecy::ExposeSecret;
+
+        let mut password_maker = PasswordMaker {
+            seed: Some(42),
+            ..PasswordMaker::default()
+        };
+
+        let password = password_maker.generate_secret().unwrap();
+        assert_eq!(password.expose_secret(), password_maker.generate().unwrap());
+
+        // The password is not shown by Debug
+        assert!(!format!("{:?}", password).contains(password.expose_secret()));
+    }
+
+    /// Return whether the digits pass the Luhn check
+    fn is_luhn_valid(digits: &str) -> bool {
+        let sum: u32 = digits
+            .chars()
+            .rev()
+            .map(|c| c.to_digit(10).unwrap())
+            .enumerate()
+            .map(|(index, digit)| match index % 2 {
+                1 if 9 < digit * 2 => digit * 2 - 9,
+                1 => digit * 2,
+                _ => digit,
+            })
+            .sum();
+        sum % 10 == 0
+    }
+
+    #[test]
+    fn group() {
+        assert_eq!(super::group("ABCDEFGH", 4, "-"), "ABCD-EFGH");
+        assert_eq!(super::group("ABCDEFGHI", 4, "-"), "ABCD-EFGH-I");
+        assert_eq!(super::group("ABC", 4, "-"), "ABC");
+        assert_eq!(super::group("ABCD", 1, "::"), "A::B::C::D");
+        assert_eq!(super::group("ABCD", 0, "-"), "ABCD");
+        assert_eq!(super::group("", 4, "-"), "");
+
+        // Graphemes of several scalar values are not split
+        assert_eq!(super::group("👨‍👩‍👦👨‍👩‍👦🇯🇵é1", 2, "-"), "👨‍👩‍👦👨‍👩‍👦-🇯🇵é-1");
+    }
+
+    #[test]
+    fn luhn_check_digit() {
+        assert_eq!(super::luhn_check_digit(&[7, 9, 9, 2, 7, 3, 9, 8, 7, 1]), 3);
+        assert_eq!(super::luhn_check_digit(&[0]), 0);
+        assert!(is_luhn_valid("79927398713"));
+        assert!(!is_luhn_valid("79927398710"));
+    }
+
+    #[test]
+    fn luhn_segment() {
+        let mut password_maker = PasswordMaker {
+            length: 24,
+            luhn_segment: Some(LuhnSegment {
+                start: 4,
+                length: 16,
+            }),
+            ..PasswordMaker::default()
+        };
+
+        for _ in 0..100 {
+            let password = password_maker.generate().unwrap();
+            let characters: Vec<char> = password.chars().collect();
+            assert_eq!(characters.len(), 24);
+
+            // The designated segment passes the Luhn check
+            let segment: String = characters[4..20].iter().collect();
+            assert!(segment.chars().all(|c| c.is_ascii_digit()), "{}", password);
+            assert!(is_luhn_valid(&segment), "{}", password);
+
+            // The rest still meets the minimum counts
+            let rest: String = characters[..4].iter().chain(&characters[20..]).collect();
+            assert!(rest.chars().any(|c| c.is_ascii_uppercase()), "{}", password);
+            assert!(rest.chars().any(|c| c.is_ascii_lowercase()), "{}", password);
+            assert!(
+                rest.chars().any(|c| c.is_ascii_punctuation()),
+                "{}",
+                password
+            );
+        }
+
+        // The rest of the password is random
+        assert_ne!(
+            password_maker.generate_with_rng(&mut ChaCha20Rng::seed_from_u64(1)),
+            password_maker.generate_with_rng(&mut ChaCha20Rng::seed_from_u64(2))
+        );
+
+        // The segment can fill the whole password
+        let mut password_maker = PasswordMaker {
+            length: 16,
+            uppercase: Classifier {
+                minimum_count: 0,
+                ..PasswordMaker::default().uppercase
+            },
+            lowercase: Classifier {
+                minimum_count: 0,
+                ..PasswordMaker::default().lowercase
             },
+            symbol: Classifier {
+                minimum_count: 0,
+                ..PasswordMaker::default().symbol
+            },
+            luhn_segment: Some(LuhnSegment {
+                start: 0,
+                length: 16,
+            }),
+            ..PasswordMaker::default()
+        };
+        assert!(is_luhn_valid(&password_maker.generate().unwrap()));
+    }
+
+    #[test]
+    fn luhn_segment_conflicts() {
+        let segment = || PasswordMaker {
+            length: 24,
+            luhn_segment: Some(LuhnSegment {
+                start: 0,
+                length: 16,
+            }),
+            ..PasswordMaker::default()
+        };
+        let conflict = |setting: &str| Err(PasswordError::LuhnSegmentConflict(setting.to_string()));
+
+        let mut password_maker = PasswordMaker {
+            unique_characters: true,
+            ..segment()
+        };
+        assert_eq!(password_maker.generate(), conflict("unique_characters"));
+
+        let mut password_maker = PasswordMaker {
+            no_consecutive_repeats: true,
+            ..segment()
+        };
+        assert_eq!(
+            password_maker.generate(),
+            conflict("no_consecutive_repeats")
+        );
+
+        let mut password_maker = PasswordMaker {
+            exclude_sequences: true,
+            ..segment()
+        };
+        assert_eq!(password_maker.generate(), conflict("exclude_sequences"));
+
+        let mut password_maker = PasswordMaker {
+            no_leading_zero: true,
+            ..segment()
+        };
+        assert_eq!(password_maker.generate(), conflict("no_leading_zero"));
+
+        let mut password_maker = PasswordMaker {
+            exclude_similar: true,
+            ..segment()
+        };
+        assert_eq!(password_maker.generate(), conflict("exclude_similar"));
+
+        // The settings can be used without the segment
+        password_maker.luhn_segment = None;
+        assert!(password_maker.generate().is_ok());
+    }
+
+    #[test]
+    fn luhn_segment_err() {
+        let mut password_maker = PasswordMaker {
+            luhn_segment: Some(LuhnSegment {
+                start: 10,
+                length: 8,
+            }),
+            ..PasswordMaker::default()
+        };
+        assert_eq!(
+            password_maker.generate(),
+            Err(PasswordError::InvalidLuhnSegment {
+                start: 10,
+                length: 8,
+                password_length: 16,
+            })
+        );
+
+        password_maker.luhn_segment = Some(LuhnSegment {
+            start: 0,
+            length: 1,
+        });
+        assert!(matches!(
+            password_maker.generate(),
+            Err(PasswordError::InvalidLuhnSegment { .. })
+        ));
+
+        // No digits to use
+        password_maker.luhn_segment = Some(LuhnSegment {
+            start: 0,
+            length: 4,
+        });
+        password_maker.number = Classifier {
+            candidates: vec!["٣".to_string()],
+            minimum_count: 0,
+            ..Default::default()
+        };
+        assert_eq!(
+            password_maker.generate(),
+            Err(PasswordError::NoDigitsForLuhn)
+        );
+
+        // The digits of the segment exceed the maximum number of numbers
+        password_maker.number = Classifier {
+            maximum_count: Some(3),
+            ..PasswordMaker::default().number
+        };
+        assert!(matches!(
+            password_maker.generate(),
+            Err(PasswordError::MaximumBelowMinimum { .. })
+        ));
+    }
+
+    #[test]
+    fn seed() {
+        let mut password_maker = PasswordMaker {
+            length: 64,
+            seed: Some(42),
+            ..PasswordMaker::default()
+        };
+
+        // The same seed generates the same password
+        let password = password_maker.generate().unwrap();
+        assert_eq!(password, password_maker.generate().unwrap());
+        assert_eq!(
+            password,
+            password_maker
+                .generate_with_rng(&mut ChaCha20Rng::seed_from_u64(42))
+                .unwrap()
+        );
+
+        // A different seed generates a different password
+        password_maker.seed = Some(43);
+        assert_ne!(password, password_maker.generate().unwrap());
+    }
+
+    #[test]
+    fn generate_weighted_seeded() {
+        let mut password_maker = PasswordMaker {
+            length: 64,
+            others: vec![
+                Classifier {
+                    candidates: vec!["あ".to_string()],
+                    minimum_count: 1,
+                    ..Default::default()
+                },
+                Classifier {
+                    candidates: ('\u{4E00}'..'\u{55D0}').map(|c| c.to_string()).collect(),
+                    minimum_count: 1,
+                    ..Default::default()
+                },
+            ],
+            others_equal_weight: true,
+            ..PasswordMaker::default()
+        };
+
+        // The same seed generates the same password
+        let password = password_maker.generate_weighted_seeded(42).unwrap();
+        assert_eq!(
+            password,
+            password_maker.generate_weighted_seeded(42).unwrap()
+        );
+        assert_eq!(password.chars().count(), 64);
+
+        // A different seed generates a different password
+        assert_ne!(
+            password,
+            password_maker.generate_weighted_seeded(43).unwrap()
+        );
+
+        // Also reproducible without weights
+        password_maker.others_equal_weight = false;
+        let password = password_maker.generate_weighted_seeded(42).unwrap();
+        assert_eq!(
+            password,
+            password_maker.generate_weighted_seeded(42).unwrap()
+        );
+    }
+
+    #[test]
+    fn candidate_weights() {
+        let mut password_maker = PasswordMaker {
+            others: vec![
+                Classifier {
+                    candidates: vec!["あ".to_string()],
+                    minimum_count: 0,
+                    ..Default::default()
+                },
+                Classifier {
+                    candidates: vec!["い".to_string(), "う".to_string(), "え".to_string()],
+                    minimum_count: 0,
+                    ..Default::default()
+                },
+            ],
+            ..PasswordMaker::default()
+        };
+        let standard = password_maker.candidates().len() - 4;
+
+        // All weights are 1 by default
+        assert!(password_maker.candidate_weights().iter().all(|&w| w == 1.0));
+
+        // The total weight of the other characters (4) is split equally between the 2 lists
+        password_maker.others_equal_weight = true;
+        let weights = password_maker.candidate_weights();
+        assert!(weights[..standard].iter().all(|&w| w == 1.0));
+        assert_eq!(weights[standard..], [2.0, 2.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0]);
+
+        // The weights of the candidates are split in proportion within each list
+        password_maker.others[1].weights = vec![2, 1, 1];
+        let weights = password_maker.candidate_weights();
+        assert_eq!(weights[standard..], [2.5, 1.25, 0.625, 0.625]);
+    }
+
+    #[test]
+    fn generate_weighted_candidates() {
+        let mut password_maker = PasswordMaker {
+            length: 1000,
             uppercase: Classifier {
-                candidates: ('A'..='Z').map(|c| c.to_string()).collect(),
-                minimum_count: 1,
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            lowercase: Classifier {
+                candidates: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                minimum_count: 0,
+                weights: vec![98, 1, 1],
+                ..Default::default()
             },
             number: Classifier {
-                candidates: (0..=9).map(|c| c.to_string()).collect(),
-                minimum_count: 1,
+                candidates: vec!["0".to_string(), "1".to_string()],
+                minimum_count: 5,
+                weights: vec![0, 1],
+                ..Default::default()
             },
-            // Symbols are sorted in ascending order of ASCII values
             symbol: Classifier {
-                candidates: "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~"
-                    .chars()
-                    .map(|c| c.to_string())
-                    .collect(),
-                minimum_count: 1,
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
             },
-            others: vec![],
+            ..PasswordMaker::default()
+        };
+
+        // The heavily weighted character appears far more often than the others
+        let password = password_maker.generate().unwrap();
+        let a = password.chars().filter(|&c| c == 'a').count();
+        let b = password.chars().filter(|&c| c == 'b').count();
+        assert!(800 < a, "{}", password);
+        assert!(a > 10 * b, "{}", password);
+
+        // A character with a weight of 0 is never used, even to meet the minimum count
+        assert!(!password.contains('0'));
+        assert!(5 <= password.chars().filter(|&c| c == '1').count());
+        assert!(!password_maker.effective_pool().contains(&"0".to_string()));
+    }
+
+    #[test]
+    fn validate_weights_length() {
+        let mut password_maker = PasswordMaker::default();
+        password_maker.number.weights = vec![1, 2];
+        assert_eq!(
+            password_maker.validate(),
+            Err(PasswordError::WeightsLengthMismatch {
+                class: "Numbers".to_string(),
+                weights: 2,
+                candidates: 10,
+            })
+        );
+
+        // All weights are 0, so the minimum count cannot be met
+        password_maker.number.weights = vec![0; 10];
+        password_maker.number.minimum_count = 1;
+        assert!(matches!(
+            password_maker.validate(),
+            Err(PasswordError::EmptyCandidatesWithMinimum { .. })
+        ));
+    }
+
+    #[test]
+    fn weights_follow_candidates() {
+        let mut classifier = Classifier {
+            candidates: vec!["a".to_string(), "b".to_string(), "a".to_string()],
+            minimum_count: 0,
+            weights: vec![1, 2, 3],
+            ..Default::default()
+        };
+        assert_eq!(classifier.push_candidate("c"), Ok(true));
+        assert_eq!(classifier.weights, [1, 2, 3, 1]);
+        assert!(classifier.remove_candidate("a"));
+        assert_eq!(classifier.candidates, ["b", "c"]);
+        assert_eq!(classifier.weights, [2, 1]);
+        classifier.clear_candidates();
+        assert!(classifier.weights.is_empty());
+    }
+
+    #[test]
+    fn pin() {
+        let mut password_maker = PasswordMaker::pin(4);
+        for _ in 0..100 {
+            let pin = password_maker.generate().unwrap();
+            assert_eq!(pin.len(), 4);
+            assert!(pin.chars().all(|c| c.is_ascii_digit()));
+        }
+        assert_eq!(password_maker.effective_pool().len(), 10);
+
+        // A leading "0" is rewritten
+        let password_maker = PasswordMaker {
+            no_leading_zero: true,
+            ..PasswordMaker::pin(4)
+        };
+        let pin = password_maker.fill_deterministic(|_| 0).unwrap();
+        assert!(
+            pin.starts_with(|c: char| ('1'..='9').contains(&c)),
+            "{}",
+            pin
+        );
+        assert_eq!(&pin[1..], "000");
+        let mut password_maker = PasswordMaker {
+            length: 1,
+            ..password_maker
+        };
+        for _ in 0..100 {
+            assert_ne!(password_maker.generate().unwrap(), "0");
         }
+
+        // Only "0" cannot avoid a leading "0"
+        password_maker.number.candidates = vec!["0".to_string()];
+        assert_eq!(
+            password_maker.generate(),
+            Err(PasswordError::LeadingZeroUnavoidable)
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn strong() {
+        let mut password_maker = PasswordMaker::strong();
+        let mut rng = password_maker.create_rng();
+        for _ in 0..100 {
+            let password = password_maker.generate_with_rng(&mut rng).unwrap();
+            assert_eq!(password.len(), 24);
+            assert!(
+                !password.contains(['i', 'l', '1', 'o', '0', 'O']),
+                "{}",
+                password
+            );
+            assert!(password.chars().any(|c| c.is_ascii_uppercase()));
+            assert!(password.chars().any(|c| c.is_ascii_lowercase()));
+            assert!(password.chars().any(|c| c.is_ascii_digit()));
+            assert!(password.chars().any(|c| c.is_ascii_punctuation()));
+        }
+    }
 
-    // Test if a password that meets the conditions can be generated
-    // If the number of characters is small, it may not be possible to generate a password that meets the conditions,
-    // so set a large number of characters (1000) for tests other than length tests
-    const PASSWORD_LENGTH: u32 = 1000;
+    #[test]
+    fn wifi() {
+        let mut password_maker = PasswordMaker::wifi();
+        let mut rng = password_maker.create_rng();
+        for _ in 0..100 {
+            let password = password_maker.generate_with_rng(&mut rng).unwrap();
+            assert_eq!(password.len(), 20);
+            assert!(
+                !password.contains(['"', '\'', '`', '\\', 'i', 'l', '1', 'o', '0', 'O']),
+                "{}",
+                password
+            );
+        }
+        assert_eq!(password_maker.effective_pool().len(), 94 - 6 - 4);
+    }
 
     #[test]
-    fn default() {
-        let mut password_maker = PasswordMaker::default();
+    fn from_apple_rules() {
+        let mut password_maker = PasswordMaker::from_apple_rules(
+            "required: upper; required: lower; required: digit; allowed: [-_]; minlength: 20; maxlength: 24;",
+        )
+        .unwrap();
+
         let password = password_maker.generate().unwrap();
-        assert_eq!(password.chars().count(), 16);
+        assert_eq!(password.chars().count(), 20);
+        assert!(password.chars().any(|c| c.is_ascii_uppercase()));
+        assert!(password.chars().any(|c| c.is_ascii_lowercase()));
+        assert!(password.chars().any(|c| c.is_ascii_digit()));
+        assert!(password
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+
+        // Each class of a rule is required, and "]" can be a member of a custom character set
+        let mut password_maker =
+            PasswordMaker::from_apple_rules("required: upper, [-]]; maxlength: 2;").unwrap();
+        for _ in 0..10 {
+            let password = password_maker.generate().unwrap();
+            assert!(password.chars().any(|c| c.is_ascii_uppercase()));
+            assert!(password.chars().any(|c| c == '-' || c == ']'));
+        }
+
+        assert!(PasswordMaker::from_apple_rules("required: emoji").is_err());
     }
 
     #[test]
-    /// Test if a password with a length of 0 can be generated
-    /// By default, the minimum number of characters is set to 1, so an error occurs
-    /// Set the minimum number of characters to 0 for the test
-    fn empty() {
-        let mut password_maker = PasswordMaker {
-            length: 0,
+    fn homoglyph_conflicts() {
+        // No conflicts by default
+        assert!(PasswordMaker::default().homoglyph_conflicts().is_empty());
+
+        // Latin "a" and Cyrillic "а", and Latin "O", Cyrillic "О", and Greek "Ο"
+        let password_maker = PasswordMaker {
+            others: vec![Classifier {
+                candidates: vec![
+                    "\u{0430}".to_string(),
+                    "\u{041E}".to_string(),
+                    "\u{039F}".to_string(),
+                    "あ".to_string(),
+                ],
+                minimum_count: 0,
+                ..Default::default()
+            }],
             ..PasswordMaker::default()
         };
+        assert_eq!(
+            password_maker.homoglyph_conflicts(),
+            vec![
+                ("a".to_string(), "\u{0430}".to_string()),
+                ("O".to_string(), "\u{041E}".to_string()),
+                ("O".to_string(), "\u{039F}".to_string()),
+                ("\u{041E}".to_string(), "\u{039F}".to_string()),
+            ]
+        );
 
-        // By default, the minimum number of characters for uppercase, lowercase, numbers, and symbols is set to 1, so an error occurs
-        // Therefore, set the minimum number of characters to 0 for the test
-        password_maker.uppercase.minimum_count = 0;
-        password_maker.lowercase.minimum_count = 0;
-        password_maker.number.minimum_count = 0;
-        password_maker.symbol.minimum_count = 0;
+        // Excluded similar characters do not conflict
+        let password_maker = PasswordMaker {
+            exclude_similar: true,
+            ..password_maker
+        };
+        assert_eq!(
+            password_maker.homoglyph_conflicts(),
+            vec![
+                ("a".to_string(), "\u{0430}".to_string()),
+                ("\u{041E}".to_string(), "\u{039F}".to_string()),
+            ]
+        );
+    }
 
-        let result = password_maker.generate();
-        assert!(result.is_err());
+    #[test]
+    fn keyspace() {
+        let password_maker = PasswordMaker {
+            length: 2,
+            uppercase: Classifier::default(),
+            lowercase: Classifier {
+                candidates: vec!["a".to_string(), "b".to_string(), "a".to_string()],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            number: Classifier::default(),
+            symbol: Classifier::default(),
+            ..PasswordMaker::default()
+        };
+
+        // Duplicate candidates are counted once
+        assert_eq!(password_maker.keyspace(), Some(4));
+        assert_eq!(password_maker.check_keyspace(4), Ok(()));
+        assert_eq!(
+            password_maker.check_keyspace(5),
+            Err(PasswordError::KeyspaceTooSmall {
+                requested: 5,
+                keyspace: 4,
+            })
+        );
+
+        // Too large for u128
+        let password_maker = PasswordMaker {
+            length: 1000,
+            ..PasswordMaker::default()
+        };
+        assert_eq!(password_maker.keyspace(), None);
+        assert_eq!(password_maker.check_keyspace(usize::MAX), Ok(()));
+
+        // No candidates
+        let password_maker = PasswordMaker {
+            uppercase: Classifier::default(),
+            lowercase: Classifier::default(),
+            number: Classifier::default(),
+            symbol: Classifier::default(),
+            ..PasswordMaker::default()
+        };
+        assert_eq!(password_maker.keyspace(), Some(0));
     }
 
     #[test]
-    fn length() {
-        // Set the password length to 8 characters
-        let mut password_maker = PasswordMaker {
-            length: 8,
+    fn length_for_entropy() {
+        // 128 / log2(94) = 19.5...
+        let mut password_maker = PasswordMaker::default();
+        assert_eq!(password_maker.length_for_entropy(128.0), Ok(20));
+        password_maker.length = 20;
+        assert!(128.0 <= password_maker.entropy());
+        password_maker.length = 19;
+        assert!(password_maker.entropy() < 128.0);
+
+        // Exactly reachable: 8 characters from 16 candidates are 32 bits
+        let password_maker = PasswordMaker {
+            lowercase: Classifier {
+                candidates: "abcdefghijklmnop".chars().map(|c| c.to_string()).collect(),
+                minimum_count: 0,
+                ..Default::default()
+            },
+            uppercase: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            number: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            symbol: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
             ..PasswordMaker::default()
         };
-        let password = password_maker.generate().unwrap();
-        assert_eq!(password.chars().count(), 8);
+        assert_eq!(password_maker.length_for_entropy(32.0), Ok(8));
 
-        // Set the password length to 32 characters
-        password_maker.length = 32;
-        let password = password_maker.generate().unwrap();
-        assert_eq!(password.chars().count(), 32);
+        // The length is not shorter than the minimum counts
+        let mut password_maker = PasswordMaker::default();
+        password_maker.symbol.minimum_count = 30;
+        assert_eq!(password_maker.length_for_entropy(128.0), Ok(33));
+        assert_eq!(PasswordMaker::default().length_for_entropy(0.0), Ok(4));
+
+        // Unreachable
+        let mut password_maker = PasswordMaker::pin(4);
+        password_maker.number.candidates = vec!["7".to_string()];
+        assert_eq!(
+            password_maker.length_for_entropy(1.0),
+            Err(PasswordError::EntropyUnreachable)
+        );
+        assert_eq!(
+            PasswordMaker::default().length_for_entropy(f64::INFINITY),
+            Err(PasswordError::EntropyUnreachable)
+        );
+        assert_eq!(
+            PasswordMaker::default().length_for_entropy(f64::NAN),
+            Err(PasswordError::EntropyUnreachable)
+        );
+        password_maker.number.candidates.clear();
+        assert_eq!(
+            password_maker.length_for_entropy(1.0),
+            Err(PasswordError::NoCandidates)
+        );
     }
 
     #[test]
-    fn uppercases() {
-        // By default, include uppercases
-        let mut password_maker = PasswordMaker {
-            length: PASSWORD_LENGTH,
+    fn entropy() {
+        // 16 characters from 26 + 26 + 10 + 32 = 94 candidates
+        let password_maker = PasswordMaker::default();
+        assert!((password_maker.entropy() - 16.0 * 94f64.log2()).abs() < 1e-9);
+        assert!((password_maker.entropy() - 104.87).abs() < 0.01);
+
+        // Excluding similar characters and including whitespace change the candidates
+        let password_maker = PasswordMaker {
+            exclude_similar: true,
+            include_whitespace_in_candidate: true,
             ..PasswordMaker::default()
         };
-        let password = password_maker.generate().unwrap();
-        assert!(password.chars().any(|c| c.is_ascii_uppercase()));
+        assert!((password_maker.entropy() - 16.0 * 89f64.log2()).abs() < 1e-9);
 
-        // Do not include uppercases
-        password_maker.uppercase = Classifier {
-            candidates: vec![],
-            minimum_count: 0,
+        // Duplicate candidates are counted once
+        let password_maker = PasswordMaker {
+            others: vec![Classifier {
+                candidates: vec!["a".to_string(), "あ".to_string(), "あ".to_string()],
+                minimum_count: 0,
+                ..Default::default()
+            }],
+            ..PasswordMaker::default()
         };
-        let password = password_maker.generate().unwrap();
-        assert!(password.chars().all(|c| !c.is_ascii_uppercase()));
+        assert!((password_maker.entropy() - 16.0 * 95f64.log2()).abs() < 1e-9);
 
-        // Specify the types of uppercases
-        password_maker.uppercase = Classifier {
-            candidates: vec![
-                'A'.to_string(),
-                'M'.to_string(),
-                'N'.to_string(),
-                'Z'.to_string(),
-            ],
-            minimum_count: 1,
+        // The length is 0
+        let password_maker = PasswordMaker {
+            length: 0,
+            ..PasswordMaker::default()
         };
-        let password = password_maker.generate().unwrap();
-        // Check if the types of uppercases are only those specified
-        // Specify the first, middle, and last letters of the alphabet
-        assert!(password.contains('A'));
-        assert!(password.contains('M'));
-        assert!(password.contains('N'));
-        assert!(password.contains('Z'));
-        // Check if other uppercases are not included
-        assert!(password.chars().all(|c| !matches!(
-            c,
-            'B' | 'C'
-                | 'D'
-                | 'E'
-                | 'F'
-                | 'G'
-                | 'H'
-                | 'I'
-                | 'J'
-                | 'K'
-                | 'L'
-                | 'O'
-                | 'P'
-                | 'Q'
-                | 'R'
-                | 'S'
-                | 'T'
-                | 'U'
-                | 'V'
-                | 'W'
-                | 'X'
-                | 'Y'
-        )));
+        assert_eq!(password_maker.entropy(), 0.0);
+
+        // No candidates
+        let password_maker = PasswordMaker {
+            uppercase: Classifier::default(),
+            lowercase: Classifier::default(),
+            number: Classifier::default(),
+            symbol: Classifier::default(),
+            ..PasswordMaker::default()
+        };
+        assert_eq!(password_maker.entropy(), 0.0);
+    }
+
+    #[test]
+    fn entropy_weights() {
+        // One candidate is far more likely than the others, so each character is worth almost nothing
+        let mut password_maker = PasswordMaker::default();
+        password_maker.lowercase.weights = vec![1_000_000];
+        password_maker.lowercase.weights.resize(26, 1);
+        let expected = (1_000_093f64 / 1_000_000f64).log2();
+        assert!((password_maker.bits_per_character() - expected).abs() < 1e-12);
+        assert!((password_maker.entropy() - 16.0 * expected).abs() < 1e-9);
+        assert!(password_maker.entropy() < 1.0);
+        assert!(80.0 <= password_maker.length_for_entropy(80.0).unwrap() as f64 * expected);
+
+        // Equal weights are the same as no weights
+        password_maker.lowercase.weights = vec![3; 26];
+        password_maker.uppercase.weights = vec![3; 26];
+        password_maker.number.weights = vec![3; 10];
+        password_maker.symbol.weights = vec![3; 32];
+        assert!((password_maker.entropy() - 16.0 * 94f64.log2()).abs() < 1e-9);
+
+        // A candidate chosen half of the time makes each character 1 bit
+        let password_maker = PasswordMaker {
+            length: 8,
+            lowercase: Classifier {
+                candidates: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                minimum_count: 0,
+                weights: vec![2, 1, 1],
+                ..Default::default()
+            },
+            uppercase: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            number: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            symbol: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            ..PasswordMaker::default()
+        };
+        assert!((password_maker.entropy() - 8.0).abs() < 1e-9);
+        assert_eq!(password_maker.length_for_entropy(20.0), Ok(20));
 
-        // Ensure the minimum number of characters is met
-        // No candidates for uppercases, but the minimum number of characters is set to 1
-        password_maker.length = 4;
-        password_maker.uppercase.minimum_count = 1;
-        let password = password_maker.generate().unwrap();
-        assert!(password.chars().any(|c| c.is_ascii_uppercase()));
+        // The weights of the placeholders of a pattern are taken into account as well
+        let password_maker = PasswordMaker {
+            pattern: Some("aa-a".to_string()),
+            ..password_maker
+        };
+        assert!((password_maker.entropy() - 3.0).abs() < 1e-9);
     }
+
     #[test]
-    fn lowercases() {
-        // Include lowercases by default
+    fn entropy_breakdown() {
+        let total = |breakdown: &EntropyBreakdown| {
+            breakdown.lowercase
+                + breakdown.uppercase
+                + breakdown.number
+                + breakdown.symbol
+                + breakdown.others.iter().sum::<f64>()
+                + breakdown.whitespace
+        };
+
+        // Each type gets the share of its candidates
         let mut password_maker = PasswordMaker {
-            length: PASSWORD_LENGTH,
+            include_whitespace_in_candidate: true,
+            others: vec![Classifier {
+                // "a" is a lowercase already, so only "あ" is added
+                candidates: vec!["a".to_string(), "あ".to_string()],
+                minimum_count: 0,
+                ..Default::default()
+            }],
             ..PasswordMaker::default()
         };
-        let password = password_maker.generate().unwrap();
-        assert!(password.chars().any(|c| c.is_ascii_lowercase()));
+        let entropy = password_maker.entropy();
+        let breakdown = password_maker.entropy_breakdown();
+        assert!((breakdown.lowercase - entropy * 26.0 / 96.0).abs() < 1e-9);
+        assert!((breakdown.uppercase - entropy * 26.0 / 96.0).abs() < 1e-9);
+        assert!((breakdown.number - entropy * 10.0 / 96.0).abs() < 1e-9);
+        assert!((breakdown.symbol - entropy * 32.0 / 96.0).abs() < 1e-9);
+        assert_eq!(breakdown.others.len(), 1);
+        assert!((breakdown.others[0] - entropy / 96.0).abs() < 1e-9);
+        assert!((breakdown.whitespace - entropy / 96.0).abs() < 1e-9);
+        assert!((total(&breakdown) - entropy).abs() < 1e-9);
 
-        // Do not include lowercases
-        password_maker.lowercase = Classifier {
-            candidates: vec![],
-            minimum_count: 0,
-        };
-        let password = password_maker.generate().unwrap();
-        assert!(password.chars().all(|c| !c.is_ascii_lowercase()));
+        // The weights decide the shares, and the parts still add up to the entropy
+        password_maker.number.weights = vec![100; 10];
+        let breakdown = password_maker.entropy_breakdown();
+        assert!(breakdown.lowercase < breakdown.number);
+        assert!((total(&breakdown) - password_maker.entropy()).abs() < 1e-9);
 
-        // Specify the types of lowercases
-        password_maker.lowercase = Classifier {
-            candidates: ['a', 'm', 'n', 'z']
-                .iter()
-                .map(|&c| c.to_string())
-                .collect(),
-            minimum_count: 1,
-        };
-        let password = password_maker.generate().unwrap();
-        // Check if the types of lowercases are only those specified
-        // Specify the first, middle, and last letters of the alphabet
-        assert!(password.contains('a'));
-        assert!(password.contains('m'));
-        assert!(password.contains('n'));
-        assert!(password.contains('z'));
-        // Check if other lowercases are not included
-        assert!(password.chars().all(|c| !matches!(
-            c,
-            'b' | 'c'
-                | 'd'
-                | 'e'
-                | 'f'
-                | 'g'
-                | 'h'
-                | 'i'
-                | 'j'
-                | 'k'
-                | 'l'
-                | 'o'
-                | 'p'
-                | 'q'
-                | 'r'
-                | 's'
-                | 't'
-                | 'u'
-                | 'v'
-                | 'w'
-                | 'x'
-                | 'y'
-        )));
+        // Excluded types add nothing
+        password_maker.symbol.candidates.clear();
+        assert_eq!(password_maker.entropy_breakdown().symbol, 0.0);
+
+        // The placeholders of a pattern add their entropy to their types
+        let password_maker = PasswordMaker::from_pattern("Aa-00");
+        let breakdown = password_maker.entropy_breakdown();
+        assert!((breakdown.uppercase - 26f64.log2()).abs() < 1e-9);
+        assert!((breakdown.lowercase - 26f64.log2()).abs() < 1e-9);
+        assert!((breakdown.number - 2.0 * 10f64.log2()).abs() < 1e-9);
+        assert_eq!(breakdown.symbol, 0.0);
+        assert!((total(&breakdown) - password_maker.entropy()).abs() < 1e-9);
     }
 
     #[test]
-    fn numbers() {
-        // Include numbers by default
-        let mut password_maker = PasswordMaker {
-            length: PASSWORD_LENGTH,
+    fn effective_pool() {
+        // Duplicates are removed
+        let password_maker = PasswordMaker {
+            others: vec![Classifier {
+                candidates: vec!["a".to_string(), "あ".to_string(), "あ".to_string()],
+                minimum_count: 0,
+                ..Default::default()
+            }],
             ..PasswordMaker::default()
         };
-        let password = password_maker.generate().unwrap();
-        assert!(password.chars().any(|c| c.is_ascii_digit()));
+        let pool = password_maker.effective_pool();
+        assert_eq!(pool.len(), 95);
+        assert_eq!(pool.last(), Some(&"あ".to_string()));
+        assert_eq!(pool[..94], PasswordMaker::default().candidates()[..]);
 
-        // Do not include numbers
-        password_maker.number = Classifier {
-            candidates: vec![],
-            minimum_count: 0,
+        // The whitespace is added
+        let password_maker = PasswordMaker {
+            include_whitespace_in_candidate: true,
+            ..PasswordMaker::default()
         };
-        let password = password_maker.generate().unwrap();
-        assert!(password.chars().all(|c| !c.is_ascii_digit()));
+        assert!(password_maker.effective_pool().contains(&" ".to_string()));
 
-        // Specify the types of numbers
-        password_maker.number = Classifier {
-            candidates: ['0', '5', '9'].iter().map(|&c| c.to_string()).collect(),
-            minimum_count: 1,
+        // The similar characters are removed
+        let password_maker = PasswordMaker {
+            exclude_similar: true,
+            ..PasswordMaker::default()
         };
-        let password = password_maker.generate().unwrap();
-        // Check if the types of numbers are only those specified
-        assert!(password.contains('0'));
-        assert!(password.contains('5'));
-        assert!(password.contains('9'));
-        // Check if other numbers are not included
-        assert!(password
-            .chars()
-            .all(|c| !matches!(c, '1' | '2' | '3' | '4' | '6' | '7' | '8')));
+        let pool = password_maker.effective_pool();
+        assert_eq!(pool.len(), 88);
+        for similar in &password_maker.similar_characters {
+            assert!(!pool.contains(similar));
+        }
 
-        // Ensure the minimum number of characters is met
+        // The variation selectors are removed, and so is a candidate of only a variation selector
+        let password_maker = PasswordMaker {
+            others: vec![Classifier {
+                candidates: vec!["❤️".to_string(), "\u{FE0F}".to_string()],
+                minimum_count: 0,
+                ..Default::default()
+            }],
+            strip_variation_selectors: true,
+            ..PasswordMaker::default()
+        };
+        let pool = password_maker.effective_pool();
+        assert_eq!(pool.len(), 95);
+        assert_eq!(pool.last(), Some(&"❤".to_string()));
 
-        // Error case
-        // No candidates for numbers, but the minimum number of characters is set to 1
-        password_maker.number = Classifier {
-            candidates: vec![],
-            minimum_count: 1,
+        // The candidates that the encoding cannot represent are removed
+        let password_maker = PasswordMaker {
+            others: vec![Classifier {
+                candidates: vec!["あ".to_string(), "😀".to_string()],
+                minimum_count: 0,
+                ..Default::default()
+            }],
+            pool_encoding: Some("shift_jis".to_string()),
+            ..PasswordMaker::default()
         };
-        let password = password_maker.generate();
-        assert!(password.is_err());
-    }
+        let pool = password_maker.effective_pool();
+        assert_eq!(pool.len(), 95);
+        assert_eq!(pool.last(), Some(&"あ".to_string()));
 
-    #[test]
-    fn symbols() {
-        // Include symbols by default
+        // An unsupported encoding is reported
         let mut password_maker = PasswordMaker {
-            length: PASSWORD_LENGTH,
+            pool_encoding: Some("unknown".to_string()),
             ..PasswordMaker::default()
         };
-        let password = password_maker.generate().unwrap();
-        assert!(password.chars().any(|c| c.is_ascii_punctuation()));
+        assert_eq!(
+            password_maker.validate(),
+            Err(PasswordError::UnsupportedEncoding("unknown".to_string()))
+        );
+        assert!(password_maker.generate().is_err());
 
-        // Do not include symbols
-        password_maker.symbol = Classifier {
-            candidates: vec![],
-            minimum_count: 0,
+        // The passwords consist only of the pool, also after meeting the minimum counts
+        let mut password_maker = PasswordMaker {
+            length: 64,
+            exclude_similar: true,
+            include_whitespace_in_candidate: true,
+            strip_variation_selectors: true,
+            pool_encoding: Some("shift_jis".to_string()),
+            others: vec![Classifier {
+                candidates: vec!["あ\u{FE0E}".to_string(), "😀".to_string()],
+                minimum_count: 2,
+                ..Default::default()
+            }],
+            ..PasswordMaker::default()
         };
-        let password = password_maker.generate().unwrap();
-        assert!(password.chars().all(|c| !c.is_ascii_punctuation()));
+        password_maker.number.minimum_count = 10;
+        let pool = password_maker.effective_pool();
+        let mut rng = password_maker.create_rng();
+        for _ in 0..50 {
+            let password = password_maker.generate_with_rng(&mut rng).unwrap();
+            assert!(password
+                .graphemes(true)
+                .all(|grapheme| pool.iter().any(|c| c == grapheme)));
+        }
 
-        // Specify the types of symbols
-        password_maker.symbol = Classifier {
-            candidates: ['!', '@', '~'].iter().map(|&c| c.to_string()).collect(),
-            minimum_count: 1,
-        };
-        let password = password_maker.generate().unwrap();
-        // Check if the types of symbols are only those specified
-        assert!(password.contains('!'));
-        assert!(password.contains('@'));
-        assert!(password.contains('~'));
-        // Check if other symbols are not included
-        assert!(password.chars().all(|c| !matches!(
-            c,
-            '"' | '#'
-                | '$'
-                | '%'
-                | '&'
-                | '\''
-                | '('
-                | ')'
-                | '*'
-                | '+'
-                | ','
-                | '-'
-                | '.'
-                | '/'
-                | ':'
-                | ';'
-                | '<'
-                | '='
-                | '>'
-                | '?'
-                | '['
-                | '\\'
-                | ']'
-                | '^'
-                | '_'
-                | '`'
-                | '{'
-                | '|'
-                | '}'
-        )));
+        // The strength is computed from the pool
+        assert!((password_maker.entropy() - 64.0 * (pool.len() as f64).log2()).abs() < 1e-9);
     }
 
     #[test]
-    fn similar() {
-        // Do not include similar characters
-        let mut password_maker = PasswordMaker {
-            length: PASSWORD_LENGTH,
-            exclude_similar: true,
+    fn pool_size() {
+        assert_eq!(PasswordMaker::default().pool_size(), 94);
+
+        // Duplicate candidates are counted once, also across the character types
+        let password_maker = PasswordMaker {
+            uppercase: Classifier {
+                candidates: vec!["A".to_string(), "A".to_string(), "B".to_string()],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            others: vec![Classifier {
+                candidates: vec!["a".to_string(), "B".to_string(), "😺".to_string()],
+                minimum_count: 0,
+                ..Default::default()
+            }],
             ..PasswordMaker::default()
         };
-        let password = password_maker.generate().unwrap();
-        assert!(password
-            .chars()
-            .all(|c| !matches!(c, 'i' | 'l' | '1' | 'o' | '0' | 'O')));
-
-        // Include similar characters
-        password_maker.exclude_similar = false;
-        let password = password_maker.generate().unwrap();
-        assert!(password
-            .chars()
-            .any(|c| matches!(c, 'i' | 'l' | '1' | 'o' | '0' | 'O')));
+        assert_eq!(password_maker.pool_size(), 2 + 26 + 10 + 32 + 1);
 
-        // Include similar characters by default
-        let mut password_maker = PasswordMaker::default();
-        let password = password_maker.generate().unwrap();
-        assert!(password
-            .chars()
-            .any(|c| matches!(c, 'i' | 'l' | '1' | 'o' | '0' | 'O')));
+        // The filters are applied
+        let password_maker = PasswordMaker {
+            exclude_similar: true,
+            exclude_characters: vec!["\"".to_string(), "A".to_string()],
+            include_whitespace_in_candidate: true,
+            ..password_maker
+        };
+        assert_eq!(
+            password_maker.pool_size(),
+            1 + 26 - 3 + 10 - 2 + 32 - 1 + 1 + 1
+        );
     }
 
     #[test]
-    fn whitespace() {
-        // Do not include whitespace
-        let mut password_maker = PasswordMaker {
-            length: PASSWORD_LENGTH,
-            include_whitespace_in_candidate: false,
+    fn display() {
+        let password_maker = PasswordMaker {
+            length: 20,
+            no_leading_zero: true,
+            exclude_characters: vec!["a".to_string()],
+            symbol: Classifier {
+                maximum_count: Some(2),
+                ..PasswordMaker::default().symbol
+            },
+            others: vec![
+                Classifier {
+                    candidates: vec!["😺".to_string(), "🐶".to_string()],
+                    minimum_count: 1,
+                    ..Default::default()
+                },
+                Classifier {
+                    candidates: vec!["あ".to_string()],
+                    minimum_count: 2,
+                    ..Default::default()
+                },
+            ],
             ..PasswordMaker::default()
         };
-        let password = password_maker.generate().unwrap();
-        assert!(!password.contains(' '));
+        let display = password_maker.to_string();
+        assert!(display.contains("length: 20"), "{}", display);
+        assert!(display.contains("flags: no_leading_zero"), "{}", display);
+        for name in ["lowercase", "uppercase", "number", "symbol", "others"] {
+            assert!(display.contains(&format!("{}: ", name)), "{}", display);
+        }
+        assert!(display.contains("lowercase: 25 candidates, minimum 1\n"));
+        assert!(display.contains("symbol: 32 candidates, minimum 1, maximum 2\n"));
+        assert!(display.ends_with("others: 2 types, 3 candidates, minimum 3"));
+        // The candidates themselves are not shown
+        assert!(!display.contains("😺"));
+    }
 
-        // Include whitespace
-        password_maker.include_whitespace_in_candidate = true;
-        let password = password_maker.generate().unwrap();
-        assert!(password.contains(' '));
+    #[test]
+    fn contains_denied() {
+        let password_maker = PasswordMaker::default();
+        let words = vec!["cat".to_string(), "".to_string(), "ÄRGER".to_string()];
+        assert!(password_maker.contains_denied("xCaT9", &words));
+        assert!(password_maker.contains_denied("1ärger!", &words));
+        assert!(!password_maker.contains_denied("c-a-t", &words));
+        assert!(!password_maker.contains_denied("abc", &[]));
+        // An empty word does not deny everything
+        assert!(!password_maker.contains_denied("abc", &["".to_string()]));
     }
 
     #[test]
-    fn other_chars() {
-        // Do not include other characters
-        // For testing other characters, include only numbers, excluding alphabets and symbols
+    fn duplicate_candidates() {
+        assert!(PasswordMaker::default().duplicate_candidates().is_empty());
+
         let mut password_maker = PasswordMaker {
-            length: PASSWORD_LENGTH,
-            uppercase: Classifier {
-                candidates: vec![],
-                minimum_count: 0,
-            },
-            lowercase: Classifier {
-                candidates: vec![],
-                minimum_count: 0,
-            },
             symbol: Classifier {
-                candidates: vec![],
+                candidates: vec!["-".to_string(), "_".to_string(), "-".to_string()],
                 minimum_count: 0,
+                ..Default::default()
             },
+            others: vec![
+                Classifier {
+                    candidates: vec!["😺".to_string(), "_".to_string(), "a".to_string()],
+                    minimum_count: 0,
+                    ..Default::default()
+                },
+                Classifier {
+                    candidates: vec!["😺".to_string(), "-".to_string()],
+                    minimum_count: 0,
+                    ..Default::default()
+                },
+            ],
             ..PasswordMaker::default()
         };
-        let password = password_maker.generate().unwrap();
-        assert!(password.chars().all(|c| c.is_ascii_digit()));
+        // A duplicate within one character type is not reported
+        assert_eq!(password_maker.duplicate_candidates(), ["a", "-", "_", "😺"]);
 
-        // Include other characters
-        // Include Variable-width characters (characters that are treated as one character in char type, such as ⌨️, are not included)
-        password_maker.others = vec![Classifier {
-            candidates: ['あ', '🍣', '！'].iter().map(|&c| c.to_string()).collect(),
-            minimum_count: 1,
-        }];
-        let password = password_maker.generate().unwrap();
-        assert!(password.contains('あ'));
-        assert!(password.contains('🍣'));
-        assert!(password.contains('！'));
-        assert!(password.chars().any(|c| c.is_ascii_digit()));
+        // The pool has no repeats
+        let candidates = password_maker.candidates();
+        assert_eq!(candidates.len(), 26 + 26 + 10 + 2 + 1);
+        assert_eq!(
+            candidates.iter().collect::<IndexSet<_>>().len(),
+            candidates.len()
+        );
+        assert_eq!(candidates, password_maker.effective_pool());
+
+        // The excluded characters are not reported
+        password_maker.exclude_characters = vec!["-".to_string()];
+        assert_eq!(password_maker.duplicate_candidates(), ["a", "_", "😺"]);
     }
 
     #[test]
@@ -766,6 +5932,7 @@ mod tests {
             uppercase: Classifier {
                 candidates: vec![],
                 minimum_count: 0,
+                ..Default::default()
             },
             ..PasswordMaker::default()
         };
@@ -779,6 +5946,7 @@ mod tests {
             others: vec![Classifier {
                 candidates: ['あ', '🍣', '！'].iter().map(|&c| c.to_string()).collect(),
                 minimum_count: 1,
+                ..Default::default()
             }],
             ..PasswordMaker::default()
         };
@@ -800,6 +5968,37 @@ mod tests {
         assert!(candidates.contains(&"！".to_string()));
     }
 
+    #[test]
+    fn reset() {
+        let mut password_maker = PasswordMaker {
+            length: 64,
+            exclude_similar: true,
+            include_whitespace_in_candidate: true,
+            ..PasswordMaker::default()
+        };
+        password_maker.symbol.candidates = vec!["-".to_string()];
+        password_maker.number.minimum_count = 5;
+        password_maker.others.push(Classifier {
+            candidates: vec!["あ".to_string()],
+            minimum_count: 1,
+            ..Default::default()
+        });
+        password_maker.seed = Some(42);
+        password_maker
+            .exclude_characters
+            .extend((0..100).map(|i| i.to_string()));
+        assert_ne!(password_maker, PasswordMaker::default());
+
+        let capacity = password_maker.exclude_characters.capacity();
+        let candidates = password_maker.lowercase.candidates.as_ptr();
+        password_maker.reset();
+        assert_eq!(password_maker, PasswordMaker::default());
+
+        // The vectors are refilled in place
+        assert_eq!(password_maker.exclude_characters.capacity(), capacity);
+        assert_eq!(password_maker.lowercase.candidates.as_ptr(), candidates);
+    }
+
     #[test]
     fn validate_uppercase_letter() {
         // Normal case
@@ -819,6 +6018,7 @@ mod tests {
                     uppercase: Classifier {
                         candidates: vec![],
                         minimum_count: 0,
+                        ..Default::default()
                     },
                     ..PasswordMaker::default()
                 };
@@ -835,6 +6035,7 @@ mod tests {
                     uppercase: Classifier {
                         candidates: vec![],
                         minimum_count: 1,
+                        ..Default::default()
                     },
                     ..PasswordMaker::default()
                 };
@@ -848,6 +6049,7 @@ mod tests {
                     uppercase: Classifier {
                         candidates: vec![],
                         minimum_count: 2,
+                        ..Default::default()
                     },
                     ..PasswordMaker::default()
                 };
@@ -876,6 +6078,7 @@ mod tests {
                     lowercase: Classifier {
                         candidates: vec![],
                         minimum_count: 0,
+                        ..Default::default()
                     },
                     ..PasswordMaker::default()
                 };
@@ -892,6 +6095,7 @@ mod tests {
                     lowercase: Classifier {
                         candidates: vec![],
                         minimum_count: 1,
+                        ..Default::default()
                     },
                     ..PasswordMaker::default()
                 };
@@ -905,6 +6109,7 @@ mod tests {
                     lowercase: Classifier {
                         candidates: vec![],
                         minimum_count: 2,
+                        ..Default::default()
                     },
                     ..PasswordMaker::default()
                 };
@@ -933,6 +6138,7 @@ mod tests {
                     number: Classifier {
                         candidates: vec![],
                         minimum_count: 0,
+                        ..Default::default()
                     },
                     ..PasswordMaker::default()
                 };
@@ -949,6 +6155,7 @@ mod tests {
                     number: Classifier {
                         candidates: vec![],
                         minimum_count: 1,
+                        ..Default::default()
                     },
                     ..PasswordMaker::default()
                 };
@@ -962,6 +6169,7 @@ mod tests {
                     number: Classifier {
                         candidates: vec![],
                         minimum_count: 2,
+                        ..Default::default()
                     },
                     ..PasswordMaker::default()
                 };
@@ -990,6 +6198,7 @@ mod tests {
                     symbol: Classifier {
                         candidates: vec![],
                         minimum_count: 0,
+                        ..Default::default()
                     },
                     ..PasswordMaker::default()
                 };
@@ -1006,6 +6215,7 @@ mod tests {
                     symbol: Classifier {
                         candidates: vec![],
                         minimum_count: 1,
+                        ..Default::default()
                     },
                     ..PasswordMaker::default()
                 };
@@ -1019,6 +6229,7 @@ mod tests {
                     symbol: Classifier {
                         candidates: vec![],
                         minimum_count: 2,
+                        ..Default::default()
                     },
                     ..PasswordMaker::default()
                 };
@@ -1038,6 +6249,7 @@ mod tests {
                     others: vec![Classifier {
                         candidates: ['あ', '🍣', '！'].iter().map(|&c| c.to_string()).collect(),
                         minimum_count: 1,
+                        ..Default::default()
                     }],
                     ..PasswordMaker::default()
                 };
@@ -1051,6 +6263,7 @@ mod tests {
                     others: vec![Classifier {
                         candidates: vec![],
                         minimum_count: 0,
+                        ..Default::default()
                     }],
                     ..PasswordMaker::default()
                 };
@@ -1067,6 +6280,7 @@ mod tests {
                     others: vec![Classifier {
                         candidates: vec![],
                         minimum_count: 1,
+                        ..Default::default()
                     }],
                     ..PasswordMaker::default()
                 };
@@ -1080,6 +6294,7 @@ mod tests {
                     others: vec![Classifier {
                         candidates: vec![],
                         minimum_count: 2,
+                        ..Default::default()
                     }],
                     ..PasswordMaker::default()
                 };
@@ -1097,6 +6312,7 @@ mod tests {
                 others: vec![Classifier {
                     candidates: ['あ', '🍣', '！'].iter().map(|&c| c.to_string()).collect(),
                     minimum_count: 1,
+                    ..Default::default()
                 }],
                 ..PasswordMaker::default()
             };
@@ -1150,6 +6366,358 @@ mod tests {
         }
     }
 
+    #[test]
+    fn validate_total_message() {
+        // The error message includes the minimum number of characters for each type
+        let password_maker = PasswordMaker {
+            length: 5,
+            others: vec![
+                Classifier {
+                    candidates: ['あ', '🍣', '！'].iter().map(|&c| c.to_string()).collect(),
+                    minimum_count: 1,
+                    ..Default::default()
+                },
+                Classifier {
+                    candidates: ['α', 'β'].iter().map(|&c| c.to_string()).collect(),
+                    minimum_count: 2,
+                    ..Default::default()
+                },
+            ],
+            ..PasswordMaker::default()
+        };
+
+        let error = password_maker.validate().unwrap_err();
+        assert_eq!(
+            error,
+            PasswordError::MinimumExceedsLength {
+                total: 7,
+                uppercase: 1,
+                lowercase: 1,
+                number: 1,
+                symbol: 1,
+                others: 3,
+                length: 5,
+            }
+        );
+
+        let message = error.to_string();
+        assert!(message.contains("The total minimum number of characters is 7"));
+        assert!(
+            message.contains("(uppercases: 1, lowercases: 1, numbers: 1, symbols: 1, others: 3)")
+        );
+        assert!(message.contains("but the password length is 5"));
+    }
+
+    #[test]
+    fn validate_error() {
+        // Errors can be handled by their variants, and the messages are kept
+        let password_maker = PasswordMaker {
+            uppercase: Classifier {
+                candidates: vec![],
+                minimum_count: 2,
+                ..Default::default()
+            },
+            ..PasswordMaker::default()
+        };
+        let error = password_maker.validate().unwrap_err();
+        assert_eq!(
+            error,
+            PasswordError::EmptyCandidatesWithMinimum {
+                class: "Uppercases".to_string(),
+                minimum: 2,
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Uppercases is empty, but the minimum number of characters is set to 2. Please set the minimum number of characters to 0."
+        );
+
+        let password_maker = PasswordMaker {
+            length: 0,
+            uppercase: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            lowercase: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            number: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            symbol: Classifier {
+                candidates: vec![],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            ..PasswordMaker::default()
+        };
+        assert_eq!(password_maker.validate(), Err(PasswordError::NoCandidates));
+
+        let password_maker = PasswordMaker {
+            length: 0,
+            uppercase: Classifier {
+                candidates: vec!["A".to_string()],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            ..password_maker
+        };
+        assert_eq!(password_maker.validate(), Err(PasswordError::ZeroLength));
+    }
+
+    #[test]
+    fn validate_control_characters() {
+        let mut password_maker = PasswordMaker {
+            others: vec![Classifier {
+                candidates: vec!["a".to_string(), "\t".to_string(), "\u{7F}".to_string()],
+                minimum_count: 0,
+                ..Default::default()
+            }],
+            ..PasswordMaker::default()
+        };
+
+        // Rejected by default, naming the code points
+        let error = password_maker.validate().unwrap_err();
+        assert_eq!(
+            error,
+            PasswordError::ControlCharacters(vec!['\t', '\u{7F}'])
+        );
+        assert!(error.to_string().contains("U+0009, U+007F"));
+
+        // Allowed explicitly
+        password_maker.allow_control_characters = true;
+        assert!(password_maker.validate().is_ok());
+
+        // Whitespace is not a control character
+        let password_maker = PasswordMaker {
+            include_whitespace_in_candidate: true,
+            ..PasswordMaker::default()
+        };
+        assert!(password_maker.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_maximum_count() {
+        // The maximum is less than the minimum
+        let mut password_maker = PasswordMaker::default();
+        password_maker.symbol.minimum_count = 3;
+        password_maker.symbol.maximum_count = Some(2);
+        let error = password_maker.validate().unwrap_err();
+        assert_eq!(
+            error,
+            PasswordError::MaximumBelowMinimum {
+                class: "Symbols".to_string(),
+                minimum: 3,
+                maximum: 2,
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "The maximum number of characters of Symbols is 2, which is less than the minimum number of characters 3. Please set the maximum number of characters to 3 or more."
+        );
+
+        password_maker.symbol.maximum_count = Some(3);
+        assert!(password_maker.validate().is_ok());
+
+        // Every type has a maximum, and the maximums cannot fill the password
+        let mut password_maker = PasswordMaker {
+            length: 10,
+            ..PasswordMaker::default()
+        };
+        for classify in [
+            &mut password_maker.uppercase,
+            &mut password_maker.lowercase,
+            &mut password_maker.number,
+            &mut password_maker.symbol,
+        ] {
+            classify.maximum_count = Some(2);
+        }
+        assert_eq!(
+            password_maker.validate(),
+            Err(PasswordError::MaximumsBelowLength {
+                total: 8,
+                length: 10,
+            })
+        );
+
+        // Types without candidates do not count
+        password_maker.length = 8;
+        assert!(password_maker.validate().is_ok());
+        password_maker.symbol.candidates.clear();
+        password_maker.symbol.minimum_count = 0;
+        assert_eq!(
+            password_maker.validate(),
+            Err(PasswordError::MaximumsBelowLength {
+                total: 6,
+                length: 8,
+            })
+        );
+
+        // The whitespace has no maximum
+        password_maker.include_whitespace_in_candidate = true;
+        assert!(password_maker.validate().is_ok());
+    }
+
+    #[test]
+    fn generate_maximum_count() {
+        let mut password_maker = PasswordMaker {
+            length: 32,
+            ..PasswordMaker::default()
+        };
+        password_maker.symbol.maximum_count = Some(2);
+        password_maker.number.minimum_count = 3;
+        password_maker.number.maximum_count = Some(3);
+
+        let mut rng = password_maker.create_rng();
+        for _ in 0..200 {
+            let password = password_maker.generate_with_rng(&mut rng).unwrap();
+            let composition = password_maker.composition(&password);
+            assert!(1 <= composition.symbol && composition.symbol <= 2);
+            assert_eq!(composition.number, 3);
+            assert_eq!(password.chars().count(), 32);
+        }
+
+        // A maximum of 0 excludes the type
+        password_maker.symbol.minimum_count = 0;
+        password_maker.symbol.maximum_count = Some(0);
+        for _ in 0..50 {
+            let password = password_maker.generate_with_rng(&mut rng).unwrap();
+            assert_eq!(password_maker.composition(&password).symbol, 0);
+        }
+
+        // The maximums fill the password exactly
+        let mut password_maker = PasswordMaker {
+            length: 8,
+            ..PasswordMaker::default()
+        };
+        for classify in [
+            &mut password_maker.uppercase,
+            &mut password_maker.lowercase,
+            &mut password_maker.number,
+            &mut password_maker.symbol,
+        ] {
+            classify.maximum_count = Some(2);
+        }
+        let password = password_maker.generate().unwrap();
+        let composition = password_maker.composition(&password);
+        assert_eq!(
+            (
+                composition.uppercase,
+                composition.lowercase,
+                composition.number,
+                composition.symbol
+            ),
+            (2, 2, 2, 2)
+        );
+    }
+
+    #[test]
+    fn validate_minimum_count_overflow() {
+        // The total wraps around to 3 with plain addition
+        let password_maker = PasswordMaker {
+            uppercase: Classifier {
+                candidates: vec!["A".to_string()],
+                minimum_count: u32::MAX,
+                ..Default::default()
+            },
+            others: vec![Classifier {
+                candidates: vec!["あ".to_string()],
+                minimum_count: u32::MAX,
+                ..Default::default()
+            }],
+            ..PasswordMaker::default()
+        };
+        let error = password_maker.validate().unwrap_err();
+        assert_eq!(error, PasswordError::MinimumCountOverflow);
+        assert!(error.to_string().contains("Minimum counts overflow"));
+
+        let mut password_maker = password_maker;
+        assert_eq!(
+            password_maker.generate(),
+            Err(PasswordError::MinimumCountOverflow)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        let password_maker = PasswordMaker::default();
+        let json = serde_json::to_string(&password_maker).unwrap();
+        let deserialized: PasswordMaker = serde_json::from_str(&json).unwrap();
+
+        for (classifier, deserialized) in [
+            (&password_maker.lowercase, &deserialized.lowercase),
+            (&password_maker.uppercase, &deserialized.uppercase),
+            (&password_maker.number, &deserialized.number),
+            (&password_maker.symbol, &deserialized.symbol),
+        ] {
+            assert_eq!(classifier.candidates, deserialized.candidates);
+            assert_eq!(classifier.minimum_count, deserialized.minimum_count);
+        }
+        assert_eq!(password_maker.length, deserialized.length);
+        assert_eq!(password_maker.exclude_similar, deserialized.exclude_similar);
+        assert_eq!(
+            password_maker.similar_characters,
+            deserialized.similar_characters
+        );
+
+        // Omitted fields take their default values, and the settings are not validated
+        let deserialized: PasswordMaker = serde_json::from_str(
+            r#"{"length": 0, "number": {"candidates": ["7"], "minimum_count": 2}}"#,
+        )
+        .unwrap();
+        assert_eq!(deserialized.number.candidates, vec!["7"]);
+        assert_eq!(deserialized.number.minimum_count, 2);
+        assert_eq!(deserialized.symbol.candidates.len(), 32);
+        assert!(deserialized.validate().is_err());
+    }
+
+    #[test]
+    fn validate_length_limit() {
+        // The password length is equal to the limit
+        {
+            let password_maker = PasswordMaker {
+                length: 1_000_000,
+                ..PasswordMaker::default()
+            };
+            assert!(password_maker.validate().is_ok());
+        }
+
+        // The password length exceeds the limit
+        {
+            let password_maker = PasswordMaker {
+                length: 1_000_001,
+                ..PasswordMaker::default()
+            };
+            let error = password_maker.validate().unwrap_err();
+            assert_eq!(
+                error,
+                PasswordError::LengthExceedsLimit {
+                    length: 1_000_001,
+                    limit: 1_000_000,
+                }
+            );
+            assert!(error
+                .to_string()
+                .contains("exceeds the length limit 1000000"));
+        }
+
+        // The limit can be raised
+        {
+            let password_maker = PasswordMaker {
+                length: 1_000_001,
+                length_limit: 2_000_000,
+                ..PasswordMaker::default()
+            };
+            assert!(password_maker.validate().is_ok());
+        }
+    }
+
     #[test]
     fn validate_candidates() {
         // Test if there are candidates for the password
@@ -1167,22 +6735,27 @@ mod tests {
                 password_maker.uppercase = Classifier {
                     candidates: vec![],
                     minimum_count: 0,
+                    ..Default::default()
                 };
                 password_maker.lowercase = Classifier {
                     candidates: vec![],
                     minimum_count: 0,
+                    ..Default::default()
                 };
                 password_maker.number = Classifier {
                     candidates: vec![],
                     minimum_count: 0,
+                    ..Default::default()
                 };
                 password_maker.symbol = Classifier {
                     candidates: vec![],
                     minimum_count: 0,
+                    ..Default::default()
                 };
                 password_maker.others = vec![Classifier {
                     candidates: vec![],
                     minimum_count: 0,
+                    ..Default::default()
                 }];
                 let result = password_maker.validate();
                 assert!(result.is_err());
@@ -1201,7 +6774,8 @@ mod tests {
 
             let password_maker = PasswordMaker::default();
 
-            password_maker.overwrite_to_meet_minimum_count(&mut password);
+            password_maker
+                .overwrite_to_meet_minimum_count(&mut password, &mut password_maker.create_rng());
 
             assert!(password
                 .iter()
@@ -1230,6 +6804,7 @@ mod tests {
                 others: vec![Classifier {
                     candidates: ['あ', '🍣', '！'].iter().map(|&c| c.to_string()).collect(),
                     minimum_count: 1,
+                    ..Default::default()
                 }],
                 ..PasswordMaker::default()
             };
@@ -1241,7 +6816,8 @@ mod tests {
             for classifier in &mut password_maker.others {
                 classifier.minimum_count = 0;
             }
-            password_maker.overwrite_to_meet_minimum_count(&mut password);
+            password_maker
+                .overwrite_to_meet_minimum_count(&mut password, &mut password_maker.create_rng());
 
             assert!(!password
                 .iter()
@@ -1267,7 +6843,8 @@ mod tests {
             for classifier in &mut password_maker.others {
                 classifier.minimum_count = 1;
             }
-            password_maker.overwrite_to_meet_minimum_count(&mut password);
+            password_maker
+                .overwrite_to_meet_minimum_count(&mut password, &mut password_maker.create_rng());
 
             assert!(password
                 .iter()
@@ -1304,11 +6881,18 @@ mod tests {
             others: vec![Classifier {
                 candidates: ['あ', '🍣', '！'].iter().map(|&c| c.to_string()).collect(),
                 minimum_count: 1, // 引数で上書き数を指定するため、値はなんでもよい
+                ..Default::default()
             }],
             ..PasswordMaker::default()
         };
         for classifier in &password_maker.others {
-            password_maker.replace_characters(&mut password, classifier, vec![0, 4, 2]);
+            password_maker.replace_characters(
+                &mut password,
+                classifier,
+                true,
+                vec![0, 4, 2],
+                &mut password_maker.create_rng(),
+            );
         }
 
         // The number of characters does not change
@@ -1350,25 +6934,120 @@ mod tests {
             others: vec![Classifier {
                 candidates: ['あ', '🍣', '！'].iter().map(|&c| c.to_string()).collect(),
                 minimum_count: 1, // 引数で上書き数を指定するため、値はなんでもよい
+                ..Default::default()
             }],
             ..PasswordMaker::default()
         };
-        password_maker.replace_characters(&mut password, &password_maker.others[0], vec![5]);
+        password_maker.replace_characters(
+            &mut password,
+            &password_maker.others[0],
+            true,
+            vec![5],
+            &mut password_maker.create_rng(),
+        );
+    }
+
+    #[test]
+    fn split_for_parallel() {
+        let password_maker = PasswordMaker {
+            length: 8,
+            ..PasswordMaker::default()
+        };
+
+        let makers = password_maker.split_for_parallel(42, 8);
+        assert_eq!(makers.len(), 8);
+
+        // The settings are copied to each generator
+        assert!(makers.iter().all(|(maker, _)| maker.length == 8));
+
+        // Each stream produces a different first draw
+        let first_draws: Vec<u64> = makers
+            .into_iter()
+            .map(|(_, mut rng)| rng.next_u64())
+            .collect();
+        assert_eq!(
+            first_draws
+                .iter()
+                .collect::<std::collections::HashSet<_>>()
+                .len(),
+            8
+        );
+
+        // Each stream is reproducible from the base seed
+        let again: Vec<u64> = password_maker
+            .split_for_parallel(42, 8)
+            .into_iter()
+            .map(|(_, mut rng)| rng.next_u64())
+            .collect();
+        assert_eq!(first_draws, again);
+
+        // A different base seed produces different streams
+        let other: Vec<u64> = password_maker
+            .split_for_parallel(43, 8)
+            .into_iter()
+            .map(|(_, mut rng)| rng.next_u64())
+            .collect();
+        assert_ne!(first_draws, other);
+    }
+
+    #[test]
+    fn generate_parallel() {
+        let password_maker = PasswordMaker::default();
+
+        // The output is the same for any number of workers
+        let serial = password_maker.generate_parallel(42, 50, 1).unwrap();
+        assert_eq!(serial.len(), 50);
+        for workers in [2, 4, 7, 50, 100] {
+            assert_eq!(
+                password_maker.generate_parallel(42, 50, workers).unwrap(),
+                serial
+            );
+        }
+        assert_eq!(password_maker.generate_parallel(42, 50, 0).unwrap(), serial);
+
+        // The passwords differ from each other and from another seed
+        assert_eq!(serial.iter().collect::<IndexSet<_>>().len(), 50);
+        assert_ne!(password_maker.generate_parallel(43, 50, 4).unwrap(), serial);
+
+        // Each password is the same as the one generated with the random number generator of its index
+        let mut seeded = PasswordMaker {
+            seed: Some(42),
+            ..PasswordMaker::default()
+        };
+        for (index, password) in serial.iter().enumerate() {
+            let mut rng = seeded.create_rng_at(index as u64);
+            assert_eq!(&seeded.generate_with_rng(&mut rng).unwrap(), password);
+        }
+
+        assert!(password_maker
+            .generate_parallel(42, 0, 4)
+            .unwrap()
+            .is_empty());
+
+        let password_maker = PasswordMaker {
+            length: 0,
+            ..PasswordMaker::default()
+        };
+        assert!(matches!(
+            password_maker.generate_parallel(42, 10, 4),
+            Err(PasswordError::MinimumExceedsLength { .. })
+        ));
     }
 
     #[test]
     fn unique_random_numbers() {
         let password_maker = PasswordMaker::default();
+        let mut rng = password_maker.create_rng();
 
         // Generate 0 random numbers
         {
-            let numbers = password_maker.unique_random_numbers(0, 0..100);
+            let numbers = password_maker.unique_random_numbers(0, 0..100, &mut rng);
             assert_eq!(numbers.len(), 0);
         }
 
         // Generate 1 random number
         {
-            let numbers = password_maker.unique_random_numbers(1, 0..100);
+            let numbers = password_maker.unique_random_numbers(1, 0..100, &mut rng);
             assert_eq!(numbers.len(), 1);
             // Check if the value is within the range
             assert!(numbers[0] < 100);
@@ -1376,7 +7055,7 @@ mod tests {
 
         // Generate 10 random numbers
         {
-            let numbers = password_maker.unique_random_numbers(10, 0..100);
+            let numbers = password_maker.unique_random_numbers(10, 0..100, &mut rng);
             assert_eq!(numbers.len(), 10);
             // Check for duplicates
             assert_eq!(
@@ -1389,5 +7068,32 @@ mod tests {
             // Check if all values are within the range
             assert!(numbers.iter().all(|&x| x < 100));
         }
+
+        // Generate nearly all numbers in the range
+        {
+            let numbers = password_maker.unique_random_numbers(999, 0..1000, &mut rng);
+            assert_eq!(numbers.len(), 999);
+            assert_eq!(
+                numbers
+                    .iter()
+                    .collect::<std::collections::HashSet<_>>()
+                    .len(),
+                999
+            );
+            assert!(numbers.iter().all(|&x| x < 1000));
+        }
+
+        // Generate all numbers in the range
+        {
+            let mut numbers = password_maker.unique_random_numbers(1000, 0..1000, &mut rng);
+            numbers.sort();
+            assert_eq!(numbers, (0..1000).collect::<Vec<u32>>());
+        }
+
+        // The range does not have to start at 0
+        {
+            let numbers = password_maker.unique_random_numbers(5, 10..15, &mut rng);
+            assert!(numbers.iter().all(|&x| (10..15).contains(&x)));
+        }
     }
 }
@@ -0,0 +1,321 @@
+//! Generator of diceware-style passphrases
+
+use crate::PasswordError;
+use indexmap::IndexSet;
+use rand::prelude::*;
+
+/// Passphrase generator
+///
+/// Generates passphrases of words randomly chosen from a wordlist, e.g. "correct-horse-battery-staple".
+/// Passphrases are easier to memorize than passwords of the same strength.
+/// No wordlist is built in, so set `words` to a list of many distinct words, such as the EFF large wordlist.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Passphrase {
+    /// Words to choose from
+    ///
+    /// Duplicated words are counted once, so they do not make a word more likely.
+    pub words: Vec<String>,
+    /// Number of words in the passphrase
+    pub word_count: u32,
+    /// Separator between the words
+    pub separator: String,
+    /// Seed of the random number generator
+    ///
+    /// Anyone who knows the seed can regenerate the passphrase, so do not use it for real secrets.
+    pub seed: Option<u64>,
+}
+
+impl Passphrase {
+    /// Create a passphrase generator of memorable passphrases
+    ///
+    /// The passphrase has 6 words joined with "-".
+    /// With the 7,776 words of the EFF large wordlist, it has about 77.5 bits of entropy.
+    ///
+    /// # Arguments
+    ///
+    /// * `words` - Words to choose from
+    ///
+    /// # Returns
+    ///
+    /// * Passphrase generator
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::Passphrase;
+    ///
+    /// let passphrase = Passphrase::memorable(
+    ///     ["correct", "horse", "battery", "staple"]
+    ///         .iter()
+    ///         .map(|w| w.to_string())
+    ///         .collect(),
+    /// );
+    /// let phrase = passphrase.generate().unwrap();
+    /// assert_eq!(phrase.split('-').count(), 6);
+    /// ```
+    pub fn memorable(words: Vec<String>) -> Passphrase {
+        Passphrase {
+            words,
+            word_count: 6,
+            ..Passphrase::default()
+        }
+    }
+
+    /// Generate a passphrase
+    ///
+    /// # Returns
+    ///
+    /// * Ok: Passphrase
+    /// * Err: Error
+    ///
+    /// # Errors
+    ///
+    /// * The wordlist is empty
+    /// * The number of words is 0
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::Passphrase;
+    ///
+    /// let passphrase = Passphrase {
+    ///     words: ["correct", "horse", "battery", "staple"]
+    ///         .iter()
+    ///         .map(|w| w.to_string())
+    ///         .collect(),
+    ///     word_count: 4,
+    ///     ..Passphrase::default()
+    /// };
+    /// let phrase = passphrase.generate().unwrap();
+    /// assert_eq!(phrase.split('-').count(), 4);
+    /// ```
+    pub fn generate(&self) -> Result<String, PasswordError> {
+        let mut rng = self.create_rng();
+        self.generate_with_rng(&mut rng)
+    }
+
+    /// Generate a passphrase with the specified random number generator
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - Random number generator
+    ///
+    /// # Returns
+    ///
+    /// * Ok: Passphrase
+    /// * Err: Error
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Passphrase::generate`]
+    pub fn generate_with_rng<R: RngCore + ?Sized>(
+        &self,
+        rng: &mut R,
+    ) -> Result<String, PasswordError> {
+        self.validate()?;
+
+        let words = self.distinct_words();
+        let phrase: Vec<&str> = (0..self.word_count)
+            .map(|_| *words.choose(rng).unwrap())
+            .collect();
+        Ok(phrase.join(&self.separator))
+    }
+
+    /// Validate the settings of the passphrase generator
+    ///
+    /// # Returns
+    ///
+    /// * Ok: The settings are valid
+    /// * Err: Error
+    pub fn validate(&self) -> Result<(), PasswordError> {
+        if self.words.is_empty() {
+            return Err(PasswordError::EmptyWordlist);
+        }
+        if self.word_count == 0 {
+            return Err(PasswordError::ZeroWords);
+        }
+        Ok(())
+    }
+
+    /// Return whether the passphrase contains any of the denied words
+    ///
+    /// Same as [`PasswordMaker::contains_denied`](crate::PasswordMaker::contains_denied).
+    ///
+    /// # Arguments
+    ///
+    /// * `passphrase` - Passphrase to check
+    /// * `words` - Denied words
+    ///
+    /// # Returns
+    ///
+    /// * true: The passphrase contains a denied word
+    /// * false: The passphrase contains none of the denied words
+    pub fn contains_denied(&self, passphrase: &str, words: &[String]) -> bool {
+        crate::contains_denied(passphrase, words)
+    }
+
+    /// Return the strength of the passphrase in bits
+    ///
+    /// Computed as `word_count * log2(number of distinct words)`.
+    /// This assumes that the attacker knows the wordlist and the separator.
+    ///
+    /// # Returns
+    ///
+    /// * Entropy in bits (0.0 if there are no words)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::Passphrase;
+    ///
+    /// let passphrase = Passphrase {
+    ///     words: (0..7776).map(|i| i.to_string()).collect(),
+    ///     word_count: 5,
+    ///     ..Passphrase::default()
+    /// };
+    /// // 5 words from the 7776 words of a diceware list
+    /// assert!((passphrase.entropy() - 64.62).abs() < 0.01);
+    /// ```
+    pub fn entropy(&self) -> f64 {
+        let words = self.distinct_words().len();
+        if words == 0 {
+            return 0.0;
+        }
+        self.word_count as f64 * (words as f64).log2()
+    }
+
+    /// Create a random number generator
+    ///
+    /// Return the random number generator that [`Passphrase::generate`] uses.
+    /// If `seed` is set, it is seeded from `seed`.
+    ///
+    /// # Returns
+    ///
+    /// * Random number generator
+    pub fn create_rng(&self) -> Box<dyn RngCore> {
+        crate::create_rng(self.seed)
+    }
+
+    /// Return the distinct words in the order of `words`
+    fn distinct_words(&self) -> Vec<&str> {
+        self.words
+            .iter()
+            .map(String::as_str)
+            .collect::<IndexSet<_>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+impl Default for Passphrase {
+    /// Create a passphrase generator with the default settings
+    ///
+    /// - words: empty
+    /// - word_count: 5
+    /// - separator: "-"
+    /// - seed: None
+    fn default() -> Self {
+        Passphrase {
+            words: vec![],
+            word_count: 5,
+            separator: "-".to_string(),
+            seed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words() -> Vec<String> {
+        ["correct", "horse", "battery", "staple", "apple", "river"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn generate() {
+        let passphrase = Passphrase {
+            words: words(),
+            word_count: 5,
+            separator: " ".to_string(),
+            ..Passphrase::default()
+        };
+
+        let phrase = passphrase.generate().unwrap();
+        let chosen: Vec<&str> = phrase.split(' ').collect();
+        assert_eq!(chosen.len(), 5);
+        assert!(chosen.iter().all(|w| words().contains(&w.to_string())));
+
+        // An empty separator joins the words directly
+        let passphrase = Passphrase {
+            separator: String::new(),
+            word_count: 3,
+            words: vec!["ab".to_string()],
+            ..Passphrase::default()
+        };
+        assert_eq!(passphrase.generate().unwrap(), "ababab");
+    }
+
+    #[test]
+    fn memorable() {
+        let passphrase = Passphrase::memorable(words());
+        for _ in 0..10 {
+            let phrase = passphrase.generate().unwrap();
+            let chosen: Vec<&str> = phrase.split('-').collect();
+            assert_eq!(chosen.len(), 6);
+            assert!(chosen.iter().all(|w| words().contains(&w.to_string())));
+        }
+    }
+
+    #[test]
+    fn generate_seeded() {
+        let passphrase = Passphrase {
+            words: words(),
+            seed: Some(42),
+            ..Passphrase::default()
+        };
+        assert_eq!(passphrase.generate(), passphrase.generate());
+
+        // One random number generator generates different passphrases
+        let mut rng = passphrase.create_rng();
+        let phrases: IndexSet<String> = (0..10)
+            .map(|_| passphrase.generate_with_rng(&mut rng).unwrap())
+            .collect();
+        assert!(1 < phrases.len());
+    }
+
+    #[test]
+    fn generate_err() {
+        let passphrase = Passphrase::default();
+        assert_eq!(passphrase.generate(), Err(PasswordError::EmptyWordlist));
+
+        let passphrase = Passphrase {
+            words: words(),
+            word_count: 0,
+            ..Passphrase::default()
+        };
+        assert_eq!(passphrase.generate(), Err(PasswordError::ZeroWords));
+    }
+
+    #[test]
+    fn entropy() {
+        let mut passphrase = Passphrase {
+            words: vec!["a".to_string(), "b".to_string()],
+            word_count: 4,
+            ..Passphrase::default()
+        };
+        assert_eq!(passphrase.entropy(), 4.0);
+
+        // Duplicated words are counted once
+        passphrase.words.push("a".to_string());
+        assert_eq!(passphrase.entropy(), 4.0);
+
+        passphrase.words.clear();
+        assert_eq!(passphrase.entropy(), 0.0);
+    }
+}
@@ -0,0 +1,449 @@
+//! Generation of passwords that follow a pattern such as "Aa0!aaaa"
+
+use crate::{Classifier, EntropyBreakdown, PasswordError, PasswordMaker};
+use indexmap::IndexMap;
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Character that makes the next character of a pattern a literal
+const ESCAPE: &str = "\\";
+
+/// Element of a pattern
+#[derive(Debug, PartialEq)]
+enum Element<'a> {
+    /// Placeholder replaced with a random character of a type ("A", "a", "0", or "!")
+    Placeholder(&'a str),
+    /// Character copied as it is
+    Literal(&'a str),
+}
+
+impl PasswordMaker {
+    /// Create a password generator that follows a pattern
+    ///
+    /// Each placeholder of the pattern is replaced with a random candidate of its character type,
+    /// and the other characters are copied as they are.
+    ///
+    /// - `A`: uppercase
+    /// - `a`: lowercase
+    /// - `0`: number
+    /// - `!`: symbol
+    ///
+    /// Put `\` before a placeholder to copy it as it is (e.g. `\A`).
+    /// The length is the number of characters of the pattern, and the minimum and maximum numbers of characters,
+    /// `luhn_segment`, and the options that rewrite the password (e.g. `no_consecutive_repeats`) are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Pattern of the password
+    ///
+    /// # Returns
+    ///
+    /// * Password generator
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let mut password_maker = PasswordMaker::from_pattern("Aa0!-aaaa");
+    /// let password = password_maker.generate().unwrap();
+    /// assert_eq!(password.len(), 9);
+    /// assert_eq!(&password[4..5], "-");
+    /// ```
+    pub fn from_pattern(pattern: &str) -> PasswordMaker {
+        PasswordMaker {
+            length: pattern_length(pattern) as u32,
+            pattern: Some(pattern.to_string()),
+            ..PasswordMaker::default()
+        }
+    }
+
+    /// Generate a password that follows the validated pattern
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Pattern of the password
+    /// * `rng` - Random number generator
+    ///
+    /// # Returns
+    ///
+    /// * Password
+    pub(crate) fn fill_pattern<R: RngCore + ?Sized>(&self, pattern: &str, rng: &mut R) -> String {
+        elements(pattern)
+            .into_iter()
+            .map(|element| match element {
+                Element::Placeholder(placeholder) => {
+                    let (classifier, _) = self.placeholder_classifier(placeholder).unwrap();
+                    let (candidates, weights): (Vec<&String>, Vec<u32>) = classifier
+                        .weighted_candidates()
+                        .filter(|(c, weight)| 0 < *weight && !self.is_excluded(c, false))
+                        .unzip();
+                    // validate_pattern() ensures there are candidates with positive weights
+                    let distribution = WeightedIndex::new(weights).unwrap();
+                    candidates[distribution.sample(rng)].as_str()
+                }
+                Element::Literal(literal) => literal,
+            })
+            .collect()
+    }
+
+    /// Check that the pattern can be filled
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Pattern of the password
+    ///
+    /// # Errors
+    ///
+    /// * The pattern is empty
+    /// * A character type of the placeholders has no candidates
+    pub(crate) fn validate_pattern(&self, pattern: &str) -> Result<(), PasswordError> {
+        let elements = elements(pattern);
+        if elements.is_empty() {
+            return Err(PasswordError::ZeroLength);
+        }
+
+        for element in elements {
+            if let Element::Placeholder(placeholder) = element {
+                let (classifier, name) = self.placeholder_classifier(placeholder).unwrap();
+                if self.is_empty_without_similar(classifier, false) {
+                    return Err(PasswordError::EmptyCandidatesForPattern {
+                        class: self.class_name(classifier, name),
+                        placeholder: placeholder.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Return the distinct candidates of each placeholder of the pattern
+    ///
+    /// The candidates are filtered in the same way as in [`PasswordMaker::fill_pattern`],
+    /// and each candidate has the total weight of its occurrences.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Pattern of the password
+    ///
+    /// # Returns
+    ///
+    /// * Candidates and their weights of each placeholder, in the order of the placeholders
+    pub(crate) fn placeholder_pools(&self, pattern: &str) -> Vec<IndexMap<&str, u32>> {
+        elements(pattern)
+            .into_iter()
+            .filter_map(|element| match element {
+                Element::Placeholder(placeholder) => self.placeholder_classifier(placeholder),
+                Element::Literal(_) => None,
+            })
+            .map(|(classifier, _)| self.placeholder_pool(classifier))
+            .collect()
+    }
+
+    /// Return the distinct candidates of a placeholder and their total weights
+    ///
+    /// # Arguments
+    ///
+    /// * `classifier` - Settings of the character type of the placeholder
+    ///
+    /// # Returns
+    ///
+    /// * Candidates and their weights
+    fn placeholder_pool<'a>(&self, classifier: &'a Classifier) -> IndexMap<&'a str, u32> {
+        let mut pool = IndexMap::new();
+        for (c, weight) in classifier.weighted_candidates() {
+            if 0 < weight && !self.is_excluded(c, false) {
+                *pool.entry(c.as_str()).or_insert(0) += weight;
+            }
+        }
+        pool
+    }
+
+    /// Return the distinct characters that can replace the placeholders of the pattern
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Pattern of the password
+    ///
+    /// # Returns
+    ///
+    /// * List of distinct characters in the order of the placeholders
+    pub(crate) fn pattern_pool(&self, pattern: &str) -> Vec<String> {
+        let mut pool = IndexMap::new();
+        for candidate in self
+            .placeholder_pools(pattern)
+            .iter()
+            .flat_map(|p| p.keys())
+        {
+            pool.insert(candidate.to_string(), ());
+        }
+        pool.into_keys().collect()
+    }
+
+    /// Return the strength of the password that follows the pattern in bits
+    ///
+    /// Only the placeholders are random, so the literals add nothing,
+    /// and each placeholder adds the min-entropy of its candidates, which is `log2(number of its candidates)` without weights.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Pattern of the password
+    ///
+    /// # Returns
+    ///
+    /// * Entropy in bits
+    pub(crate) fn pattern_entropy(&self, pattern: &str) -> f64 {
+        self.placeholder_pools(pattern)
+            .into_iter()
+            .map(|pool| crate::min_entropy(pool.into_values().map(f64::from)))
+            .sum()
+    }
+
+    /// Return the strength of the password that follows the pattern split by character type
+    ///
+    /// Each placeholder adds its min-entropy to the character type it is replaced with.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Pattern of the password
+    ///
+    /// # Returns
+    ///
+    /// * Bits from each character type
+    pub(crate) fn pattern_entropy_breakdown(&self, pattern: &str) -> EntropyBreakdown {
+        let mut breakdown = EntropyBreakdown {
+            others: vec![0.0; self.others.len()],
+            ..EntropyBreakdown::default()
+        };
+        for element in elements(pattern) {
+            let (classifier, part) = match element {
+                Element::Placeholder("A") => (&self.uppercase, &mut breakdown.uppercase),
+                Element::Placeholder("a") => (&self.lowercase, &mut breakdown.lowercase),
+                Element::Placeholder("0") => (&self.number, &mut breakdown.number),
+                Element::Placeholder("!") => (&self.symbol, &mut breakdown.symbol),
+                _ => continue,
+            };
+            *part += crate::min_entropy(
+                self.placeholder_pool(classifier)
+                    .into_values()
+                    .map(f64::from),
+            );
+        }
+
+        breakdown
+    }
+
+    /// Return the number of distinct passwords that follow the pattern
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Pattern of the password
+    ///
+    /// # Returns
+    ///
+    /// * Some: Number of passwords
+    /// * None: The number does not fit in `u128`
+    pub(crate) fn pattern_keyspace(&self, pattern: &str) -> Option<u128> {
+        self.placeholder_pools(pattern)
+            .iter()
+            .try_fold(1u128, |keyspace, pool| {
+                keyspace.checked_mul(pool.len() as u128)
+            })
+    }
+
+    /// Return the settings and the name of the character type of a placeholder
+    ///
+    /// # Arguments
+    ///
+    /// * `placeholder` - Character of the pattern
+    ///
+    /// # Returns
+    ///
+    /// * Some: Settings and name of the character type
+    /// * None: The character is not a placeholder
+    fn placeholder_classifier(&self, placeholder: &str) -> Option<(&Classifier, &'static str)> {
+        match placeholder {
+            "A" => Some((&self.uppercase, "Uppercases")),
+            "a" => Some((&self.lowercase, "Lowercases")),
+            "0" => Some((&self.number, "Numbers")),
+            "!" => Some((&self.symbol, "Symbols")),
+            _ => None,
+        }
+    }
+}
+
+/// Return the length of the passwords that follow a pattern
+///
+/// # Arguments
+///
+/// * `pattern` - Pattern of the password
+///
+/// # Returns
+///
+/// * Number of characters
+pub(crate) fn pattern_length(pattern: &str) -> usize {
+    elements(pattern).len()
+}
+
+/// Split a pattern into its elements
+///
+/// A trailing escape character without a following character is a literal.
+///
+/// # Arguments
+///
+/// * `pattern` - Pattern of the password
+///
+/// # Returns
+///
+/// * Elements of the pattern
+fn elements(pattern: &str) -> Vec<Element<'_>> {
+    let mut elements = vec![];
+    let mut graphemes = pattern.graphemes(true);
+    while let Some(grapheme) = graphemes.next() {
+        elements.push(match grapheme {
+            ESCAPE => Element::Literal(graphemes.next().unwrap_or(ESCAPE)),
+            "A" | "a" | "0" | "!" => Element::Placeholder(grapheme),
+            _ => Element::Literal(grapheme),
+        });
+    }
+    elements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elements() {
+        assert_eq!(
+            super::elements("Aa-0!😺"),
+            vec![
+                Element::Placeholder("A"),
+                Element::Placeholder("a"),
+                Element::Literal("-"),
+                Element::Placeholder("0"),
+                Element::Placeholder("!"),
+                Element::Literal("😺"),
+            ]
+        );
+        assert_eq!(
+            super::elements("\\A\\\\a\\"),
+            vec![
+                Element::Literal("A"),
+                Element::Literal("\\"),
+                Element::Placeholder("a"),
+                Element::Literal("\\"),
+            ]
+        );
+        assert!(super::elements("").is_empty());
+    }
+
+    #[test]
+    fn from_pattern() {
+        let mut password_maker = PasswordMaker::from_pattern("Aa0!-aaaa@😺\\0");
+        assert_eq!(password_maker.length, 12);
+        let mut rng = password_maker.create_rng();
+        for _ in 0..100 {
+            let password = password_maker.generate_with_rng(&mut rng).unwrap();
+            let characters: Vec<&str> = password.graphemes(true).collect();
+            assert_eq!(characters.len(), 12, "{}", password);
+            assert!(password_maker.uppercase.contains(characters[0]));
+            assert!(password_maker.lowercase.contains(characters[1]));
+            assert!(password_maker.number.contains(characters[2]));
+            assert!(password_maker.symbol.contains(characters[3]));
+            assert_eq!(characters[4], "-");
+            for character in &characters[5..9] {
+                assert!(password_maker.lowercase.contains(character));
+            }
+            assert_eq!(&characters[9..], ["@", "😺", "0"]);
+        }
+
+        // The length and the minimum counts are ignored
+        password_maker.length = 1;
+        password_maker.symbol.minimum_count = 5;
+        assert_eq!(
+            password_maker.generate().unwrap().graphemes(true).count(),
+            12
+        );
+
+        // The candidates are used without the similar characters
+        let mut password_maker = PasswordMaker {
+            exclude_similar: true,
+            number: Classifier {
+                candidates: vec!["0".to_string(), "1".to_string(), "2".to_string()],
+                minimum_count: 0,
+                ..Default::default()
+            },
+            ..PasswordMaker::from_pattern("000")
+        };
+        assert_eq!(password_maker.generate().unwrap(), "222");
+    }
+
+    #[test]
+    fn from_pattern_err() {
+        assert_eq!(
+            PasswordMaker::from_pattern("").generate(),
+            Err(PasswordError::ZeroLength)
+        );
+
+        let mut password_maker = PasswordMaker::from_pattern("aa!");
+        password_maker.symbol.candidates.clear();
+        assert_eq!(
+            password_maker.generate(),
+            Err(PasswordError::EmptyCandidatesForPattern {
+                class: "Symbols".to_string(),
+                placeholder: "!".to_string(),
+            })
+        );
+
+        // A type without placeholders may be empty
+        password_maker.pattern = Some("aa\\!".to_string());
+        assert_eq!(password_maker.generate().unwrap().len(), 3);
+
+        // The checks that do not depend on the length and the types still apply
+        let mut password_maker = PasswordMaker::from_pattern("a!");
+        password_maker.symbol.candidates.push("\u{7}".to_string());
+        assert_eq!(
+            password_maker.generate(),
+            Err(PasswordError::ControlCharacters(vec!['\u{7}']))
+        );
+        let mut password_maker = PasswordMaker {
+            length_limit: 3,
+            ..PasswordMaker::from_pattern("aaaa")
+        };
+        assert_eq!(
+            password_maker.generate(),
+            Err(PasswordError::LengthExceedsLimit {
+                length: 4,
+                limit: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn pattern_strength() {
+        // Only the two placeholders are random
+        let password_maker = PasswordMaker::from_pattern("a-fixed-liter\\al-text-0");
+        assert!((password_maker.entropy() - (26f64.log2() + 10f64.log2())).abs() < 1e-9);
+        assert_eq!(password_maker.keyspace(), Some(26 * 10));
+        assert_eq!(password_maker.pool_size(), 26 + 10);
+
+        let password_maker = PasswordMaker::from_pattern("0000");
+        assert!((password_maker.entropy() - 4.0 * 10f64.log2()).abs() < 1e-9);
+        assert_eq!(password_maker.keyspace(), Some(10_000));
+
+        // The filters of the candidates are applied
+        let password_maker = PasswordMaker {
+            exclude_similar: true,
+            ..PasswordMaker::from_pattern("00")
+        };
+        assert!((password_maker.entropy() - 2.0 * 8f64.log2()).abs() < 1e-9);
+
+        // A pattern without placeholders has no randomness
+        let password_maker = PasswordMaker::from_pattern("\\a-b");
+        assert_eq!(password_maker.entropy(), 0.0);
+        assert_eq!(password_maker.keyspace(), Some(1));
+    }
+}
@@ -0,0 +1,234 @@
+//! Generators of random tokens that do not use the character types
+
+use crate::PasswordMaker;
+use rand::prelude::*;
+
+/// Alphabet of base64url (RFC 4648, section 5)
+const BASE64_URL_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+impl PasswordMaker {
+    /// Generate a random token of base64url characters
+    ///
+    /// `bytes` random bytes are encoded with the URL and filename safe alphabet (`A-Z`, `a-z`, `0-9`, `-`, `_`) without padding,
+    /// so the token can be used in URLs and file names as it is, e.g. for API tokens.
+    /// Only `seed` is used from the settings; the candidates and the minimum counts are not.
+    /// The strength is `8 * bytes` bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Number of random bytes
+    ///
+    /// # Returns
+    ///
+    /// * Token of `ceil(4 * bytes / 3)` characters (empty if `bytes` is 0)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let password_maker = PasswordMaker::default();
+    /// let token = password_maker.token_base64(32);
+    /// assert_eq!(token.len(), 43);
+    /// ```
+    pub fn token_base64(&self, bytes: usize) -> String {
+        let mut rng = self.create_rng();
+        self.token_base64_with_rng(&mut rng, bytes)
+    }
+
+    /// Generate a random token of base64url characters with the given random number generator
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - Random number generator
+    /// * `bytes` - Number of random bytes
+    ///
+    /// # Returns
+    ///
+    /// * Token of `ceil(4 * bytes / 3)` characters
+    pub fn token_base64_with_rng<R: RngCore + ?Sized>(&self, rng: &mut R, bytes: usize) -> String {
+        encode_base64_url(&random_bytes(rng, bytes))
+    }
+
+    /// Generate a random token of lowercase hexadecimal digits
+    ///
+    /// Each of the `bytes` random bytes is encoded as two digits (`0-9`, `a-f`).
+    /// Only `seed` is used from the settings; the candidates and the minimum counts are not.
+    /// The strength is `8 * bytes` bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Number of random bytes
+    ///
+    /// # Returns
+    ///
+    /// * Token of `2 * bytes` characters (empty if `bytes` is 0)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use password_maker::PasswordMaker;
+    ///
+    /// let password_maker = PasswordMaker::default();
+    /// let token = password_maker.token_hex(16);
+    /// assert_eq!(token.len(), 32);
+    /// ```
+    pub fn token_hex(&self, bytes: usize) -> String {
+        let mut rng = self.create_rng();
+        self.token_hex_with_rng(&mut rng, bytes)
+    }
+
+    /// Generate a random token of lowercase hexadecimal digits with the given random number generator
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - Random number generator
+    /// * `bytes` - Number of random bytes
+    ///
+    /// # Returns
+    ///
+    /// * Token of `2 * bytes` characters
+    pub fn token_hex_with_rng<R: RngCore + ?Sized>(&self, rng: &mut R, bytes: usize) -> String {
+        random_bytes(rng, bytes)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+/// Draw random bytes
+///
+/// # Arguments
+///
+/// * `rng` - Random number generator
+/// * `bytes` - Number of random bytes
+///
+/// # Returns
+///
+/// * Random bytes
+fn random_bytes<R: RngCore + ?Sized>(rng: &mut R, bytes: usize) -> Vec<u8> {
+    let mut buffer = vec![0; bytes];
+    rng.fill_bytes(&mut buffer);
+    buffer
+}
+
+/// Encode bytes with base64url without padding
+///
+/// # Arguments
+///
+/// * `bytes` - Bytes to encode
+///
+/// # Returns
+///
+/// * Encoded text
+fn encode_base64_url(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let block = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |block, (index, &byte)| {
+                block | u32::from(byte) << (16 - 8 * index)
+            });
+        // 2, 3, or 4 characters for 1, 2, or 3 bytes
+        for index in 0..=chunk.len() {
+            let sextet = (block >> (18 - 6 * index)) & 0x3F;
+            encoded.push(BASE64_URL_ALPHABET[sextet as usize] as char);
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decode base64url without padding
+    fn decode_base64_url(text: &str) -> Vec<u8> {
+        let sextets: Vec<u32> = text
+            .bytes()
+            .map(|c| BASE64_URL_ALPHABET.iter().position(|&a| a == c).unwrap() as u32)
+            .collect();
+        let mut bytes = vec![];
+        for chunk in sextets.chunks(4) {
+            let block = chunk.iter().enumerate().fold(0, |block, (index, &sextet)| {
+                block | sextet << (18 - 6 * index)
+            });
+            for index in 0..chunk.len() - 1 {
+                bytes.push((block >> (16 - 8 * index)) as u8);
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn encode_base64_url() {
+        // Test vectors of RFC 4648
+        assert_eq!(super::encode_base64_url(b""), "");
+        assert_eq!(super::encode_base64_url(b"f"), "Zg");
+        assert_eq!(super::encode_base64_url(b"fo"), "Zm8");
+        assert_eq!(super::encode_base64_url(b"foo"), "Zm9v");
+        assert_eq!(super::encode_base64_url(b"foob"), "Zm9vYg");
+        assert_eq!(super::encode_base64_url(b"fooba"), "Zm9vYmE");
+        assert_eq!(super::encode_base64_url(b"foobar"), "Zm9vYmFy");
+
+        // The URL-safe characters instead of "+" and "/"
+        assert_eq!(super::encode_base64_url(&[0xFB, 0xFF]), "-_8");
+    }
+
+    #[test]
+    fn token_base64() {
+        let password_maker = PasswordMaker::default();
+        let mut rng = password_maker.create_rng();
+
+        for bytes in [1, 2, 3, 16, 32, 100] {
+            let token = password_maker.token_base64_with_rng(&mut rng, bytes);
+            assert_eq!(token.len(), (4 * bytes).div_ceil(3));
+            assert!(token
+                .bytes()
+                .all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_'));
+            assert_eq!(decode_base64_url(&token).len(), bytes);
+        }
+
+        assert_eq!(password_maker.token_base64(0), "");
+
+        // The candidates and the minimum counts are not used
+        let password_maker = PasswordMaker {
+            length: 0,
+            ..PasswordMaker::default()
+        };
+        assert_eq!(password_maker.token_base64(32).len(), 43);
+
+        // The seed makes the token reproducible
+        let password_maker = PasswordMaker {
+            seed: Some(42),
+            ..PasswordMaker::default()
+        };
+        assert_eq!(
+            password_maker.token_base64(32),
+            password_maker.token_base64(32)
+        );
+    }
+
+    #[test]
+    fn token_hex() {
+        let password_maker = PasswordMaker::default();
+        let mut rng = password_maker.create_rng();
+
+        for bytes in [1, 16, 32] {
+            let token = password_maker.token_hex_with_rng(&mut rng, bytes);
+            assert_eq!(token.len(), 2 * bytes);
+            assert!(token
+                .chars()
+                .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)));
+        }
+
+        // Two tokens differ
+        let first = password_maker.token_hex_with_rng(&mut rng, 16);
+        let second = password_maker.token_hex_with_rng(&mut rng, 16);
+        assert_ne!(first, second);
+
+        assert_eq!(password_maker.token_hex(0), "");
+    }
+}
@@ -0,0 +1,113 @@
+#!/usr/bin/env python3
+"""Regenerate the tables of src/unicode_block.rs from the Unicode Character Database.
+
+Usage:
+
+    python3 scripts/unicode_block.py [VERSION]
+
+Downloads Blocks.txt and extracted/DerivedGeneralCategory.txt of the version
+(14.0.0 by default) from unicode.org and rewrites the tables and the version in
+the module documentation. The functions and the tests of the file are kept.
+
+Set UCD_DIR to a local directory with the same layout to work offline.
+"""
+
+import os
+import re
+import sys
+import urllib.request
+from pathlib import Path
+
+TARGET = Path(__file__).resolve().parent.parent / "src" / "unicode_block.rs"
+
+# General categories of the code points that are not a character on their own
+CATEGORIES = {"Cn", "Mn", "Mc", "Me"}
+
+
+def read_ucd(version, name):
+    """Return the lines of a file of the Unicode Character Database."""
+    ucd_dir = os.environ.get("UCD_DIR")
+    if ucd_dir:
+        return (Path(ucd_dir) / name).read_text(encoding="utf-8").splitlines()
+
+    url = f"https://www.unicode.org/Public/{version}/ucd/{name}"
+    with urllib.request.urlopen(url) as response:
+        return response.read().decode("utf-8").splitlines()
+
+
+def parse_ranges(lines):
+    """Yield (first, last, value) for each `XXXX..YYYY ; value` line."""
+    for line in lines:
+        line = line.split("#", 1)[0].strip()
+        if not line:
+            continue
+        code_points, value = (field.strip() for field in line.split(";", 1))
+        first, _, last = code_points.partition("..")
+        yield int(first, 16), int(last or first, 16), value
+
+
+def merge(ranges):
+    """Merge the adjacent ranges, which must be in ascending order."""
+    merged = []
+    for first, last in ranges:
+        if merged and merged[-1][1] + 1 == first:
+            merged[-1] = (merged[-1][0], last)
+        else:
+            merged.append((first, last))
+    return merged
+
+
+def tuple_text(first, last):
+    return f"(0x{first:04X}, 0x{last:04X}),"
+
+
+def tables(version):
+    blocks = sorted(parse_ranges(read_ucd(version, "Blocks.txt")))
+    unassigned_or_combining = merge(
+        sorted(
+            (first, last)
+            for first, last, category in parse_ranges(
+                read_ucd(version, "extracted/DerivedGeneralCategory.txt")
+            )
+            if category in CATEGORIES
+        )
+    )
+
+    # Align the block names like rustfmt does
+    width = max(len(tuple_text(first, last)) for first, last, _ in blocks)
+
+    lines = [
+        "//! Tables of the Unicode blocks and of the code points that are not a character on their own",
+        "//!",
+        f"//! The tables follow Unicode {version} (Blocks.txt and DerivedGeneralCategory.txt).",
+        "//! Regenerate them with `scripts/unicode_block.py`.",
+        "",
+        "/// Unicode blocks as their first and last code points, in ascending order",
+        "const BLOCKS: &[(u32, u32)] = &[",
+    ]
+    lines += [
+        f"    {tuple_text(first, last):<{width}} // {name}" for first, last, name in blocks
+    ]
+    lines += [
+        "];",
+        "",
+        "/// Unassigned code points (Cn) and combining marks (Mn, Mc, Me) as their first and last code points, in ascending order",
+        "///",
+        "/// The surrogates are left out since they are not characters.",
+        "const UNASSIGNED_OR_COMBINING: &[(u32, u32)] = &[",
+    ]
+    lines += [f"    {tuple_text(first, last)}" for first, last in unassigned_or_combining]
+    lines += ["];", ""]
+    return "\n".join(lines) + "\n"
+
+
+def main():
+    version = sys.argv[1] if len(sys.argv) > 1 else "14.0.0"
+    source = TARGET.read_text(encoding="utf-8")
+    # Everything up to the first function is generated
+    rest = source[re.search(r"^/// Return the range of code points", source, re.M).start() :]
+    TARGET.write_text(tables(version) + rest, encoding="utf-8")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,131 @@
+use password_maker::PasswordMaker;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Return the line of character type markers to print beneath the password
+///
+/// Each character is marked with its type, in the first matching order of the following:
+/// - `l`: Lowercase
+/// - `U`: Uppercase
+/// - `d`: Number
+/// - `s`: Symbol
+/// - `o`: Other character
+/// - `_`: Whitespace
+/// - `?`: Not a candidate
+///
+/// A marker is padded with spaces to the display width of its character,
+/// so the markers stay aligned under wide characters such as CJK characters and emoji.
+///
+/// # Arguments
+///
+/// * `password` - Password
+/// * `maker` - Password generator that generated the password
+///
+/// # Returns
+///
+/// Line of markers, as wide as the password
+pub fn markers(password: &str, maker: &PasswordMaker) -> String {
+    let mut line = String::new();
+
+    for grapheme in password.graphemes(true) {
+        let width = grapheme.width();
+        if width == 0 {
+            continue;
+        }
+
+        // The same order as PasswordMaker::composition
+        let marker = if maker.lowercase.contains(grapheme) {
+            'l'
+        } else if maker.uppercase.contains(grapheme) {
+            'U'
+        } else if maker.number.contains(grapheme) {
+            'd'
+        } else if maker.symbol.contains(grapheme) {
+            's'
+        } else if maker.others.iter().any(|c| c.contains(grapheme)) {
+            'o'
+        } else if grapheme == " " {
+            '_'
+        } else {
+            '?'
+        };
+
+        line.push(marker);
+        line.push_str(&" ".repeat(width - 1));
+    }
+
+    line
+}
+
+/// Return the password with all but the last characters hidden
+///
+/// Each hidden grapheme is replaced with "*" as wide as its display width,
+/// so the masked password is as wide as the password and a multi-codepoint grapheme is hidden as a whole.
+///
+/// # Arguments
+///
+/// * `password` - Password
+/// * `reveal` - Number of graphemes left visible at the end
+///
+/// # Returns
+///
+/// Masked password
+pub fn mask(password: &str, reveal: usize) -> String {
+    let graphemes: Vec<&str> = password.graphemes(true).collect();
+    let hidden = graphemes.len().saturating_sub(reveal);
+
+    let mut masked: String = graphemes[..hidden]
+        .iter()
+        .map(|grapheme| "*".repeat(grapheme.width()))
+        .collect();
+    masked.push_str(&graphemes[hidden..].concat());
+    masked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use password_maker::Classifier;
+
+    #[test]
+    fn markers_ascii() {
+        let maker = PasswordMaker::default();
+        assert_eq!(markers("aB3!", &maker), "lUds");
+    }
+
+    #[test]
+    fn markers_wide() {
+        let maker = PasswordMaker {
+            others: vec![Classifier {
+                candidates: vec!["あ".to_string(), "🍣".to_string(), "👨‍👩‍👦".to_string()],
+                minimum_count: 0,
+                ..Default::default()
+            }],
+            ..PasswordMaker::default()
+        };
+
+        let password = "aあ🍣B👨‍👩‍👦 μ";
+        let line = markers(password, &maker);
+        assert_eq!(line, "lo o Uo _?");
+
+        // The markers are as wide as the password
+        assert_eq!(line.width(), password.width());
+    }
+
+    #[test]
+    fn mask_password() {
+        assert_eq!(mask("aB3!xyz", 3), "****xyz");
+        assert_eq!(mask("aB3!", 0), "****");
+
+        // Nothing is hidden if the password is not longer than the visible characters
+        assert_eq!(mask("aB3!", 4), "aB3!");
+        assert_eq!(mask("aB3!", 10), "aB3!");
+
+        // Wide characters are hidden with as many "*" as their width, and graphemes are not split
+        let password = "あ👨‍👩‍👦bcé";
+        let masked = mask(password, 2);
+        assert_eq!(masked, "*****cé");
+        assert_eq!(masked.width(), password.width());
+        assert_eq!(mask("ab👨‍👩‍👦", 1), "**👨‍👩‍👦");
+    }
+}
@@ -0,0 +1,121 @@
+use serde::Deserialize;
+use std::path::Path;
+
+/// Generation settings read from a config file
+///
+/// The keys are the names of the command line options without the leading "--".
+/// Omitted keys keep the values of the command line options.
+///
+/// # Examples
+///
+/// ```toml
+/// length = 20
+/// count = 3
+/// symbol-candidates = "-_"
+/// symbol-minimum-count = 2
+///
+/// [[others]]
+/// candidates = "あいう"
+/// minimum-count = 1
+/// ```
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub length: Option<u32>,
+    pub count: Option<u32>,
+    pub uppercase_candidates: Option<String>,
+    pub uppercase_minimum_count: Option<u32>,
+    pub lowercase_candidates: Option<String>,
+    pub lowercase_minimum_count: Option<u32>,
+    pub number_candidates: Option<String>,
+    pub number_minimum_count: Option<u32>,
+    pub symbol_candidates: Option<String>,
+    pub symbol_minimum_count: Option<u32>,
+    pub others: Option<Vec<Other>>,
+}
+
+/// Other characters read from a config file
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Other {
+    pub candidates: String,
+    #[serde(default)]
+    pub minimum_count: u32,
+}
+
+/// Read the generation settings from a TOML file
+///
+/// # Arguments
+///
+/// * `path` - Path of the config file
+///
+/// # Returns
+///
+/// Generation settings
+///
+/// # Errors
+///
+/// If the file cannot be read, or is not a valid config file
+pub fn load(path: &Path) -> Result<Config, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read the config file {}: {}", path.display(), e))?;
+
+    toml::from_str(&text)
+        .map_err(|e| format!("Invalid config file {}: {}", path.display(), e.message()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn load_config() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            file,
+            "length = 20\nnumber-candidates = \"01\"\n\n[[others]]\ncandidates = \"あい\"\nminimum-count = 2\n\n[[others]]\ncandidates = \"😀\"\n"
+        )
+        .unwrap();
+
+        assert_eq!(
+            load(file.path()),
+            Ok(Config {
+                length: Some(20),
+                number_candidates: Some("01".to_string()),
+                others: Some(vec![
+                    Other {
+                        candidates: "あい".to_string(),
+                        minimum_count: 2,
+                    },
+                    Other {
+                        candidates: "😀".to_string(),
+                        minimum_count: 0,
+                    },
+                ]),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn load_config_err() {
+        // Missing file
+        let dir = tempfile::tempdir().unwrap();
+        let error = load(&dir.path().join("missing.toml")).unwrap_err();
+        assert!(error.starts_with("Failed to read the config file"));
+
+        // Unknown key
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "lenght = 20").unwrap();
+        let error = load(file.path()).unwrap_err();
+        assert!(error.starts_with("Invalid config file"));
+        assert!(error.contains("lenght"));
+
+        // Wrong type
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "length = \"20\"").unwrap();
+        let error = load(file.path()).unwrap_err();
+        assert!(error.starts_with("Invalid config file"));
+    }
+}
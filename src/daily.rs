@@ -0,0 +1,274 @@
+//! Seed of the password of the day, derived from a master secret and a date
+//!
+//! The seed is the first 8 bytes of HMAC-SHA256 (RFC 2104, FIPS 180-4) of the date keyed with the secret.
+
+/// Round constants of SHA-256
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Initial hash value of SHA-256
+const INITIAL_HASH: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Size of the blocks of SHA-256 in bytes
+const BLOCK_SIZE: usize = 64;
+
+/// Return the SHA-256 digest of the data
+///
+/// # Arguments
+///
+/// * `data` - Data to hash
+///
+/// # Returns
+///
+/// Digest
+fn sha256(data: &[u8]) -> [u8; 32] {
+    // Pad the data with a 1 bit, 0 bits, and the length in bits to a multiple of the block size
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % BLOCK_SIZE != BLOCK_SIZE - 8 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    let mut hash = INITIAL_HASH;
+    for block in message.chunks_exact(BLOCK_SIZE) {
+        let mut schedule = [0u32; 64];
+        for (word, bytes) in schedule.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes(bytes.try_into().unwrap());
+        }
+        for index in 16..64 {
+            let before15 = schedule[index - 15];
+            let before2 = schedule[index - 2];
+            let s0 = before15.rotate_right(7) ^ before15.rotate_right(18) ^ (before15 >> 3);
+            let s1 = before2.rotate_right(17) ^ before2.rotate_right(19) ^ (before2 >> 10);
+            schedule[index] = schedule[index - 16]
+                .wrapping_add(s0)
+                .wrapping_add(schedule[index - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = hash;
+        for (constant, word) in K.iter().zip(schedule) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let choice = (e & f) ^ (!e & g);
+            let temp1 = h
+                .wrapping_add(s1)
+                .wrapping_add(choice)
+                .wrapping_add(*constant)
+                .wrapping_add(word);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let majority = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(majority);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+        for (value, add) in hash.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *value = value.wrapping_add(add);
+        }
+    }
+
+    let mut digest = [0u8; 32];
+    for (bytes, value) in digest.chunks_exact_mut(4).zip(hash) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+/// Return the HMAC-SHA256 of the message
+///
+/// # Arguments
+///
+/// * `key` - Key
+/// * `message` - Message to authenticate
+///
+/// # Returns
+///
+/// Message authentication code
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    // A key longer than the block size is hashed, and a shorter one is padded with 0
+    let mut block = [0u8; BLOCK_SIZE];
+    if BLOCK_SIZE < key.len() {
+        block[..32].copy_from_slice(&sha256(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let inner: Vec<u8> = block
+        .iter()
+        .map(|byte| byte ^ 0x36)
+        .chain(message.iter().copied())
+        .collect();
+    let outer: Vec<u8> = block
+        .iter()
+        .map(|byte| byte ^ 0x5c)
+        .chain(sha256(&inner))
+        .collect();
+    sha256(&outer)
+}
+
+/// Derive the seed of the password of the day
+///
+/// # Arguments
+///
+/// * `secret` - Master secret
+/// * `date` - Date in the format YYYY-MM-DD
+///
+/// # Returns
+///
+/// Seed of the random number generator
+pub fn seed(secret: &str, date: &str) -> u64 {
+    // The prefix keeps the seed apart from other uses of the same secret
+    let mac = hmac_sha256(secret.as_bytes(), format!("mkpw daily {}", date).as_bytes());
+    u64::from_be_bytes(mac[..8].try_into().unwrap())
+}
+
+/// Parse a date in the format YYYY-MM-DD
+///
+/// # Arguments
+///
+/// * `text` - Date
+///
+/// # Returns
+///
+/// The date as it is
+///
+/// # Errors
+///
+/// If the text is not a valid date in the format
+pub fn parse_date(text: &str) -> Result<String, String> {
+    let error = || format!("Invalid date: {}. Please specify it as YYYY-MM-DD.", text);
+
+    let parts: Vec<&str> = text.split('-').collect();
+    let [year, month, day] = parts[..] else {
+        return Err(error());
+    };
+    if [(year, 4), (month, 2), (day, 2)]
+        .iter()
+        .any(|(part, len)| part.len() != *len || !part.bytes().all(|b| b.is_ascii_digit()))
+    {
+        return Err(error());
+    }
+
+    let (year, month, day): (u32, u32, u32) = (
+        year.parse().unwrap(),
+        month.parse().unwrap(),
+        day.parse().unwrap(),
+    );
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return Err(error()),
+    };
+    if !(1..=days).contains(&day) {
+        return Err(error());
+    }
+
+    Ok(text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn sha256() {
+        // FIPS 180-4 examples
+        assert_eq!(
+            hex(&super::sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(&super::sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // Two blocks
+        assert_eq!(
+            hex(&super::sha256(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn hmac_sha256() {
+        // RFC 4231 test cases 1, 2, and 6 (a key longer than the block size)
+        assert_eq!(
+            hex(&super::hmac_sha256(&[0x0b; 20], b"Hi There")),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+        );
+        assert_eq!(
+            hex(&super::hmac_sha256(
+                b"Jefe",
+                b"what do ya want for nothing?"
+            )),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hex(&super::hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn seed() {
+        assert_eq!(
+            super::seed("master", "2024-01-01"),
+            super::seed("master", "2024-01-01")
+        );
+        assert_ne!(
+            super::seed("master", "2024-01-01"),
+            super::seed("master", "2024-01-02")
+        );
+        assert_ne!(
+            super::seed("master", "2024-01-01"),
+            super::seed("other", "2024-01-01")
+        );
+    }
+
+    #[test]
+    fn parse_date() {
+        assert_eq!(
+            super::parse_date("2024-02-29"),
+            Ok("2024-02-29".to_string())
+        );
+        assert!(super::parse_date("2000-02-29").is_ok());
+        assert!(super::parse_date("1900-02-29").is_err());
+        assert!(super::parse_date("2023-02-29").is_err());
+        assert!(super::parse_date("2024-04-31").is_err());
+        assert!(super::parse_date("2024-13-01").is_err());
+        assert!(super::parse_date("2024-00-01").is_err());
+        assert!(super::parse_date("2024-1-01").is_err());
+        assert!(super::parse_date("2024/01/01").is_err());
+        assert!(super::parse_date("+024-01-01").is_err());
+        assert_eq!(
+            super::parse_date("tomorrow").unwrap_err(),
+            "Invalid date: tomorrow. Please specify it as YYYY-MM-DD."
+        );
+    }
+}
@@ -1,5 +1,50 @@
 use encoding_rs::Encoding;
 
+/// Encodings that can be specified, in alphabetical order of their labels
+///
+/// The replacement encoding is not included because it cannot encode or decode text.
+const ENCODINGS: [&Encoding; 39] = [
+    encoding_rs::BIG5,
+    encoding_rs::EUC_JP,
+    encoding_rs::EUC_KR,
+    encoding_rs::GB18030,
+    encoding_rs::GBK,
+    encoding_rs::IBM866,
+    encoding_rs::ISO_2022_JP,
+    encoding_rs::ISO_8859_10,
+    encoding_rs::ISO_8859_13,
+    encoding_rs::ISO_8859_14,
+    encoding_rs::ISO_8859_15,
+    encoding_rs::ISO_8859_16,
+    encoding_rs::ISO_8859_2,
+    encoding_rs::ISO_8859_3,
+    encoding_rs::ISO_8859_4,
+    encoding_rs::ISO_8859_5,
+    encoding_rs::ISO_8859_6,
+    encoding_rs::ISO_8859_7,
+    encoding_rs::ISO_8859_8,
+    encoding_rs::ISO_8859_8_I,
+    encoding_rs::KOI8_R,
+    encoding_rs::KOI8_U,
+    encoding_rs::MACINTOSH,
+    encoding_rs::SHIFT_JIS,
+    encoding_rs::UTF_16BE,
+    encoding_rs::UTF_16LE,
+    encoding_rs::UTF_8,
+    encoding_rs::WINDOWS_1250,
+    encoding_rs::WINDOWS_1251,
+    encoding_rs::WINDOWS_1252,
+    encoding_rs::WINDOWS_1253,
+    encoding_rs::WINDOWS_1254,
+    encoding_rs::WINDOWS_1255,
+    encoding_rs::WINDOWS_1256,
+    encoding_rs::WINDOWS_1257,
+    encoding_rs::WINDOWS_1258,
+    encoding_rs::WINDOWS_874,
+    encoding_rs::X_MAC_CYRILLIC,
+    encoding_rs::X_USER_DEFINED,
+];
+
 /// Converts a string with the specified encoding to a String type (UTF-8)
 ///
 /// # Arguments
@@ -30,6 +75,86 @@ pub fn decode(text: &[u8], encoding: &String) -> Result<String, String> {
     Ok(encoding.decode(text).0.into_owned())
 }
 
+/// Converts a string with the first encoding that decodes it without errors to a String type (UTF-8)
+///
+/// Useful when the encoding of the string is uncertain.
+/// Each encoding is tried in order, and an encoding that would need replacement characters is skipped.
+///
+/// # Arguments
+///
+/// * `text` - The string to be converted
+/// * `encodings` - The encodings to try in order
+///
+/// # Returns
+///
+/// The converted string and the name of the encoding used
+///
+/// # Errors
+///
+/// If an encoding is not supported, or no encoding decodes the string without errors
+///
+/// # Examples
+///
+/// ```
+/// let candidates = Vec::<u8>::from(vec![0x82, 0xA0, 0x82, 0xA2, 0x82, 0xA4, 0x82, 0xA6, 0x82, 0xA8]);
+/// let encodings = vec!["utf-8".to_string(), "shift_jis".to_string()];
+/// let result = password_maker::encoding::decode_with_fallback(&candidates, &encodings);
+/// assert_eq!(result, Ok(("あいうえお".to_string(), "Shift_JIS")));
+/// ```
+pub fn decode_with_fallback(
+    text: &[u8],
+    encodings: &[String],
+) -> Result<(String, &'static str), String> {
+    for encoding in encodings {
+        let encoding = Encoding::for_label_no_replacement(encoding.as_bytes())
+            .ok_or(format!("Unsupported encoding: {}", encoding))?;
+
+        let (decoded, had_errors) = encoding.decode_without_bom_handling(text);
+        if !had_errors {
+            return Ok((decoded.into_owned(), encoding.name()));
+        }
+    }
+
+    Err(format!(
+        "The candidates could not be decoded with any of the encodings: {}",
+        encodings.join(", ")
+    ))
+}
+
+/// Returns the canonical name of the encoding
+///
+/// # Arguments
+///
+/// * `encoding` - The encoding label (e.g. "sjis")
+///
+/// # Returns
+///
+/// The canonical name of the encoding (e.g. "Shift_JIS")
+///
+/// # Errors
+///
+/// If the encoding is not supported
+pub fn name(encoding: &str) -> Result<&'static str, String> {
+    Encoding::for_label_no_replacement(encoding.as_bytes())
+        .map(|encoding| encoding.name())
+        .ok_or(format!("Unsupported encoding: {}", encoding))
+}
+
+/// Returns the labels of the supported encodings
+///
+/// Each label is the canonical name of an encoding in lowercase.
+/// Other labels of the same encodings (e.g. "sjis" for Shift_JIS) are accepted as well.
+///
+/// # Returns
+///
+/// The labels in alphabetical order
+pub fn labels() -> Vec<String> {
+    ENCODINGS
+        .iter()
+        .map(|encoding| encoding.name().to_ascii_lowercase())
+        .collect()
+}
+
 /// Converts a UTF-8 string to a string with the specified encoding
 ///
 /// # Arguments
@@ -60,6 +185,80 @@ pub fn encode(text: &str, encoding: &str) -> Result<Vec<u8>, String> {
     Ok(encoding.encode(text).0.into_owned())
 }
 
+/// Converts a UTF-8 string to a string with the specified encoding, failing if any character cannot be converted
+///
+/// Unlike [`encode`], the characters that do not exist in the encoding are not replaced,
+/// so the converted string always matches the original one.
+///
+/// # Arguments
+///
+/// * `text` - The string to be converted
+/// * `encoding` - The encoding
+///
+/// # Returns
+///
+/// The converted string
+///
+/// # Errors
+///
+/// If the encoding is not supported, or the string has characters that cannot be converted
+///
+/// # Examples
+///
+/// ```
+/// assert!(password_maker::encoding::encode_strict("あ", "shift_jis").is_ok());
+/// assert!(password_maker::encoding::encode_strict("😀", "shift_jis").is_err());
+/// ```
+pub fn encode_strict(text: &str, encoding: &str) -> Result<Vec<u8>, String> {
+    if is_encodable(text, encoding)? {
+        return encode(text, encoding);
+    }
+
+    let mut unmappable: Vec<char> = vec![];
+    for c in text.chars() {
+        if !unmappable.contains(&c) && !is_encodable(&c.to_string(), encoding)? {
+            unmappable.push(c);
+        }
+    }
+    Err(format!(
+        "The output contains characters that cannot be encoded in {}: {}. Please remove them from the candidates, or use another encoding.",
+        name(encoding)?,
+        unmappable
+            .iter()
+            .map(|c| format!("\"{}\" (U+{:04X})", c, *c as u32))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
+
+/// Returns whether a UTF-8 string can be converted to the specified encoding without loss
+///
+/// # Arguments
+///
+/// * `text` - The string to be converted
+/// * `encoding` - The encoding
+///
+/// # Returns
+///
+/// Whether the string can be converted without replacing any character
+///
+/// # Errors
+///
+/// If the encoding is not supported
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(password_maker::encoding::is_encodable("あ", "shift_jis"), Ok(true));
+/// assert_eq!(password_maker::encoding::is_encodable("😀", "shift_jis"), Ok(false));
+/// ```
+pub fn is_encodable(text: &str, encoding: &str) -> Result<bool, String> {
+    let encoding = Encoding::for_label_no_replacement(encoding.as_bytes())
+        .ok_or(format!("Unsupported encoding: {}", encoding))?;
+
+    Ok(!encoding.encode(text).2)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,6 +303,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decode_with_fallback_to_shift_jis() {
+        // Shift_JIS encoding of "あいうえお" is invalid as UTF-8, so Shift_JIS is used
+        let candidates = vec![0x82, 0xA0, 0x82, 0xA2, 0x82, 0xA4, 0x82, 0xA6, 0x82, 0xA8];
+        let encodings = vec!["utf-8".to_string(), "shift_jis".to_string()];
+        let result = decode_with_fallback(&candidates, &encodings);
+        assert_eq!(result, Ok(("あいうえお".to_string(), "Shift_JIS")));
+
+        // Valid UTF-8 is decoded with the first encoding
+        let candidates = Vec::<u8>::from("あいうえお");
+        let result = decode_with_fallback(&candidates, &encodings);
+        assert_eq!(result, Ok(("あいうえお".to_string(), "UTF-8")));
+    }
+
+    #[test]
+    fn decode_with_fallback_err() {
+        // No encoding decodes the string without errors
+        let candidates = vec![0x82, 0xA0];
+        let encodings = vec!["utf-8".to_string()];
+        let result = decode_with_fallback(&candidates, &encodings);
+        assert_eq!(
+            result,
+            Err("The candidates could not be decoded with any of the encodings: utf-8".to_string())
+        );
+
+        // Unsupported encoding
+        let encodings = vec!["invalid".to_string(), "utf-8".to_string()];
+        let result = decode_with_fallback(&candidates, &encodings);
+        assert_eq!(result, Err("Unsupported encoding: invalid".to_string()));
+    }
+
+    #[test]
+    fn encoding_name() {
+        assert_eq!(name("sjis"), Ok("Shift_JIS"));
+        assert_eq!(name("utf8"), Ok("UTF-8"));
+        assert_eq!(
+            name("invalid"),
+            Err("Unsupported encoding: invalid".to_string())
+        );
+    }
+
+    #[test]
+    fn encoding_labels() {
+        let labels = labels();
+        assert!(labels.contains(&"utf-8".to_string()));
+        assert!(labels.contains(&"shift_jis".to_string()));
+        assert!(!labels.contains(&"replacement".to_string()));
+
+        // Every label is accepted
+        for label in &labels {
+            assert_eq!(name(label).unwrap().to_ascii_lowercase(), *label);
+        }
+    }
+
     #[test]
     fn encode_to_utf8() {
         let text = "あいうえお";
@@ -133,4 +386,32 @@ mod tests {
         let result = encode(text, encoding);
         assert_eq!(result, Err("Unsupported encoding: invalid".to_string()));
     }
+
+    #[test]
+    fn encode_strict_to_shift_jis() {
+        assert_eq!(
+            encode_strict("aあ", "shift_jis"),
+            Ok(vec![0x61, 0x82, 0xA0])
+        );
+        assert_eq!(
+            encode_strict("a😀あ😀©", "sjis"),
+            Err("The output contains characters that cannot be encoded in Shift_JIS: \"😀\" (U+1F600), \"©\" (U+00A9). Please remove them from the candidates, or use another encoding.".to_string())
+        );
+        assert_eq!(
+            encode_strict("abc", "invalid"),
+            Err("Unsupported encoding: invalid".to_string())
+        );
+    }
+
+    #[test]
+    fn is_encodable_shift_jis() {
+        assert_eq!(is_encodable("aあ!", "shift_jis"), Ok(true));
+        assert_eq!(is_encodable("😀", "shift_jis"), Ok(false));
+        assert_eq!(is_encodable("a😀", "shift_jis"), Ok(false));
+        assert_eq!(is_encodable("😀", "utf-8"), Ok(true));
+        assert_eq!(
+            is_encodable("a", "unknown"),
+            Err("Unsupported encoding: unknown".to_string())
+        );
+    }
 }
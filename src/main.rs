@@ -1,29 +1,165 @@
+mod annotate;
+mod config;
+mod daily;
 mod encoding;
+mod pattern;
+mod phonetic;
+mod unicode_block;
+#[cfg(feature = "clipboard")]
 use arboard::Clipboard;
-use clap::{CommandFactory, Parser};
+#[cfg(all(feature = "clipboard", target_os = "macos"))]
+use arboard::SetExtApple;
+#[cfg(all(feature = "clipboard", windows))]
+use arboard::SetExtWindows;
+#[cfg(all(
+    feature = "clipboard",
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+))]
+use arboard::{LinuxClipboardKind, SetExtLinux};
+use clap::parser::ValueSource;
+use clap::{ArgGroup, ArgMatches, CommandFactory, FromArgMatches, Parser, ValueEnum};
 use clap_complete::aot::{generate, Generator, Shell};
-use encoding::encode;
-use password_maker::PasswordMaker;
+use encoding::encode_strict;
+use password_maker::{Classifier, Passphrase, PasswordError, PasswordMaker, SimilarScope};
+use rand::RngCore;
 use std::ffi::OsString;
 use std::io::Write;
+use std::path::PathBuf;
 use std::{io, process::ExitCode};
 use unicode_segmentation::UnicodeSegmentation;
 
+/// Output destination of error messages
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum ErrorOutput {
+    /// Standard output
+    Stdout,
+    /// Standard error
+    Stderr,
+}
+
+/// Selection of the clipboard to copy the passwords to
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum ClipboardSelection {
+    /// Clipboard for explicit copy and paste
+    Clipboard,
+    /// Primary selection of X11 and Wayland, pasted with the middle mouse button
+    Primary,
+}
+
+/// Output format of the passwords
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum OutputFormat {
+    /// Passwords separated by newline or null characters
+    Text,
+    /// JSON object of the schema version and the array of the passwords
+    Json,
+}
+
+/// Character types from which "--exclude-similar" removes the similar characters
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum ExcludeSimilarScope {
+    /// All the character types, including "--other-candidates"
+    AllClasses,
+    /// Only the uppercases, lowercases, numbers, and symbols
+    StandardClassesOnly,
+}
+
+/// Format of the random tokens
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum TokenFormat {
+    /// URL-safe base64 without padding
+    Base64,
+    /// Lowercase hexadecimal digits
+    Hex,
+}
+
+/// Preset of the settings for a use case
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum Preset {
+    /// 24 characters of all the character types without similar characters
+    Strong,
+    /// 6 digits
+    Pin,
+    /// 20 characters without similar characters, quotes, and backslash
+    Wifi,
+    /// Passphrase of 6 words from "--wordlist"
+    Memorable,
+}
+
+/// Character type whose candidates are read from standard input
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum CandidateClass {
+    /// Uppercases (replaces "--uppercase-candidates")
+    Uppercase,
+    /// Lowercases (replaces "--lowercase-candidates")
+    Lowercase,
+    /// Numbers (replaces "--number-candidates")
+    Number,
+    /// Symbols (replaces "--symbol-candidates")
+    Symbol,
+    /// Other characters (added after "--other-candidates")
+    Other,
+}
+
 /// Command line arguments
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
+#[command(group = ArgGroup::new("passphrase_mode").args(["passphrase", "preset"]).multiple(true))]
 struct Cli {
     /// Specify the length (number of characters) of the password
     #[arg(long, default_value_t = 16)]
     length: u32,
 
+    /// Specify the upper limit of the password length
+    ///
+    /// Protects against allocating a huge amount of memory by a mistyped "--length".
+    /// Raise this value to generate longer passwords.
+    #[arg(long, default_value_t = 1_000_000)]
+    max_length_limit: u32,
+
+    /// Set the length to the shortest one whose entropy is at least this many bits
+    ///
+    /// The length is computed from the number of distinct candidates as "ceil(BITS / log2(number of candidates))",
+    /// and is not shorter than the total of the "--*-minimum-count" options.
+    #[arg(
+        long,
+        value_name = "BITS",
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with_all = ["length", "total_chars", "pattern", "apple_rules"]
+    )]
+    min_entropy: Option<u32>,
+
     /// Specify the number of passwords to output
     #[arg(long, default_value_t = 1)]
     count: u32,
 
+    /// Read the number of passwords to output from standard input
+    ///
+    /// Lets a pipeline decide the number of passwords, e.g. "echo 3 | mkpw --count-stdin".
+    /// The input must be a single integer from 1 to 1000000, surrounded by optional whitespace.
+    #[arg(long, conflicts_with_all = ["count", "candidates_stdin"])]
+    count_stdin: bool,
+
+    /// Specify the total number of characters of all passwords
+    ///
+    /// The characters are distributed evenly over the passwords specified with "--count",
+    /// so the lengths of the passwords add up to this number. Cannot be used with "--length".
+    #[arg(long, value_name = "N", conflicts_with = "length")]
+    total_chars: Option<u32>,
+
+    /// Read the generation settings from a TOML file
+    ///
+    /// The file can set "length", "count", the "*-candidates" and "*-minimum-count" of each character type,
+    /// and groups of other characters as "[[others]]" tables with "candidates" and "minimum-count".
+    /// Options given on the command line take precedence over the file.
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
     /// Candidates for uppercases to include in the password
     ///
     /// If an empty string is specified, no uppercases will be included in the password.
+    /// Ranges such as "A-Z" are expanded.
     #[arg(long, default_value = "ABCDEFGHIJKLMNOPQRSTUVWXYZ")]
     uppercase_candidates: OsString,
 
@@ -33,9 +169,16 @@ struct Cli {
     #[arg(long, default_value_t = 1)]
     uppercase_minimum_count: u32,
 
+    /// The maximum number of uppercases in the password
+    ///
+    /// Excess uppercases are replaced with characters of the other types. If omitted, there is no maximum.
+    #[arg(long, value_name = "N", conflicts_with_all = ["apple_rules", "pin", "pattern"])]
+    uppercase_maximum_count: Option<u32>,
+
     /// Candidates for lowercases to include in the password
     ///
     /// If an empty string is specified, no lowercases will be included in the password.
+    /// Ranges such as "a-z" are expanded.
     #[arg(long, default_value = "abcdefghijklmnopqrstuvwxyz")]
     lowercase_candidates: OsString,
 
@@ -45,9 +188,32 @@ struct Cli {
     #[arg(long, default_value_t = 1)]
     lowercase_minimum_count: u32,
 
+    /// The maximum number of lowercases in the password
+    ///
+    /// Excess lowercases are replaced with characters of the other types. If omitted, there is no maximum.
+    #[arg(long, value_name = "N", conflicts_with_all = ["apple_rules", "pin", "pattern"])]
+    lowercase_maximum_count: Option<u32>,
+
+    /// Use the lowercases converted to uppercase as the candidates for uppercases
+    ///
+    /// Keeps the uppercases in line with customized "--lowercase-candidates", e.g. "abc" makes the uppercases "ABC".
+    /// Lowercases without a single uppercase form are skipped.
+    /// If "--uppercase-candidates" is also specified, the explicit candidates are used.
+    #[arg(long)]
+    uppercase_from_lowercase: bool,
+
+    /// Use the uppercases converted to lowercase as the candidates for lowercases
+    ///
+    /// Keeps the lowercases in line with customized "--uppercase-candidates", e.g. "ABC" makes the lowercases "abc".
+    /// Uppercases without a single lowercase form are skipped.
+    /// If "--lowercase-candidates" is also specified, the explicit candidates are used.
+    #[arg(long, conflicts_with = "uppercase_from_lowercase")]
+    lowercase_from_uppercase: bool,
+
     /// Candidates for numbers to include in the password
     ///
     /// If an empty string is specified, no numbers will be included in the password.
+    /// Ranges such as "0-9" are expanded.
     #[arg(long, default_value = "0123456789")]
     number_candidates: OsString,
 
@@ -57,6 +223,12 @@ struct Cli {
     #[arg(long, default_value_t = 1)]
     number_minimum_count: u32,
 
+    /// The maximum number of numbers in the password
+    ///
+    /// Excess numbers are replaced with characters of the other types. If omitted, there is no maximum.
+    #[arg(long, value_name = "N", conflicts_with_all = ["apple_rules", "pin", "pattern"])]
+    number_maximum_count: Option<u32>,
+
     /// Candidates for symbols to include in the password
     ///
     /// If an empty string is specified, no symbols will be included in the password.
@@ -69,6 +241,113 @@ struct Cli {
     #[arg(long, default_value_t = 1)]
     symbol_minimum_count: u32,
 
+    /// The maximum number of symbols in the password
+    ///
+    /// Excess symbols are replaced with characters of the other types. If omitted, there is no maximum.
+    #[arg(long, value_name = "N", conflicts_with_all = ["apple_rules", "pin", "pattern"])]
+    symbol_maximum_count: Option<u32>,
+
+    /// Generate passwords that follow Apple Password Rules
+    ///
+    /// Many sites publish their password requirements in this syntax,
+    /// e.g. "required: upper; required: lower; allowed: digit, [-_]; minlength: 8; maxlength: 20;".
+    /// The candidates, minimum counts, and length are taken from the rules,
+    /// so this option cannot be used with "--length" and the "--*-candidates" / "--*-minimum-count" options.
+    #[arg(
+        long,
+        value_name = "RULES",
+        conflicts_with_all = [
+            "length",
+            "uppercase_candidates",
+            "uppercase_minimum_count",
+            "lowercase_candidates",
+            "lowercase_minimum_count",
+            "number_candidates",
+            "number_minimum_count",
+            "symbol_candidates",
+            "symbol_minimum_count",
+            "other_candidates",
+            "other_minimum_count",
+        ]
+    )]
+    apple_rules: Option<String>,
+
+    /// Generate numeric PINs of this length
+    ///
+    /// Only the numbers 0 to 9 are used, so this option cannot be used with "--apple-rules", "--length",
+    /// and the "--*-candidates" / "--*-minimum-count" options.
+    #[arg(
+        long,
+        value_name = "LENGTH",
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with_all = [
+            "apple_rules",
+            "length",
+            "uppercase_candidates",
+            "uppercase_minimum_count",
+            "lowercase_candidates",
+            "lowercase_minimum_count",
+            "number_candidates",
+            "number_minimum_count",
+            "symbol_candidates",
+            "symbol_minimum_count",
+            "other_candidates",
+            "other_minimum_count",
+        ]
+    )]
+    pin: Option<u32>,
+
+    /// Generate passwords that follow a pattern
+    ///
+    /// "A", "a", "0", and "!" are replaced with a random uppercase, lowercase, number, and symbol,
+    /// and the other characters are used as they are. Put "\" before a placeholder to use it as it is.
+    /// For example, "Aa0!-aaaa" generates e.g. "Kd7%-qzpe".
+    /// The candidates are taken from the "--*-candidates" options, and the length is the length of the pattern.
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        conflicts_with_all = [
+            "apple_rules",
+            "pin",
+            "length",
+            "total_chars",
+            "uppercase_minimum_count",
+            "lowercase_minimum_count",
+            "number_minimum_count",
+            "symbol_minimum_count",
+            "other_minimum_count",
+        ]
+    )]
+    pattern: Option<String>,
+
+    /// Start from the settings of a preset for a use case
+    ///
+    /// The options given explicitly take precedence over the preset, e.g. "--preset strong --length 32".
+    /// "memorable" generates passphrases, so it needs "--wordlist".
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with_all = ["apple_rules", "pin", "pattern", "passphrase", "token"]
+    )]
+    preset: Option<Preset>,
+
+    /// Do not start the password with "0"
+    ///
+    /// Useful with "--pin" for systems that drop a leading zero.
+    #[arg(long)]
+    no_leading_zero: bool,
+
+    /// Read the candidates of a character type from standard input
+    ///
+    /// The input is decoded with "--encoding" and split into graphemes, and a trailing newline is ignored.
+    /// Only one character type can be read from standard input per run.
+    #[arg(long, value_name = "CLASS", conflicts_with_all = ["apple_rules", "pin"])]
+    candidates_stdin: Option<CandidateClass>,
+
+    /// Candidates read from standard input for "--candidates-stdin"
+    #[arg(skip)]
+    stdin_candidates: Option<Vec<u8>>,
+
     /// Candidates for other characters to include in the password
     ///
     /// By specifying this option multiple times, you can specify multiple other characters.
@@ -86,51 +365,626 @@ struct Cli {
     #[arg(long)]
     other_minimum_count: Option<Vec<u32>>,
 
+    /// The maximum occurrence count of other characters in the password
+    ///
+    /// Can be specified multiple times and corresponds to the order specified with "--other-candidates",
+    /// like "--other-minimum-count". The other characters without a maximum have no maximum.
+    #[arg(long, value_name = "N", conflicts_with_all = ["apple_rules", "pin", "pattern"])]
+    other_maximum_count: Option<Vec<u32>>,
+
+    /// Generate passphrases of words from "--wordlist" instead of passwords
+    ///
+    /// Generates e.g. "correct-horse-battery-staple", which is easier to memorize than a password of the same strength.
+    /// The options of the characters such as "--length" and "--*-candidates" are ignored.
+    #[arg(
+        long,
+        requires = "wordlist",
+        conflicts_with_all = [
+            "apple_rules",
+            "pin",
+            "pattern",
+            "total_chars",
+            "vanity_prefix",
+            "disjoint_from",
+            "reject_dates",
+            "batch_cover_classes",
+            "unique",
+            "summary",
+            "annotate",
+            "verbose",
+        ]
+    )]
+    passphrase: bool,
+
+    /// Specify the number of words of the passphrase
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 5,
+        requires = "passphrase_mode"
+    )]
+    words: u32,
+
+    /// Specify the separator between the words of the passphrase
+    #[arg(
+        long,
+        value_name = "SEPARATOR",
+        default_value = "-",
+        requires = "passphrase_mode"
+    )]
+    separator: String,
+
+    /// Load the words of the passphrase from a file
+    ///
+    /// The file has one word per line, and empty lines are ignored.
+    /// Lines of diceware lists such as "11111 abacus" are read as the word after the dice numbers.
+    /// Use a list of many distinct words, such as the EFF large wordlist, since the strength depends on its size.
+    #[arg(long, value_name = "FILE", requires = "passphrase_mode")]
+    wordlist: Option<PathBuf>,
+
+    /// Generate random tokens of "--bytes" random bytes instead of passwords
+    ///
+    /// Useful for API tokens and other secrets that are not typed by humans.
+    /// The options of the characters such as "--length" and "--*-candidates" are ignored.
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        conflicts_with_all = [
+            "passphrase",
+            "apple_rules",
+            "pin",
+            "pattern",
+            "total_chars",
+            "vanity_prefix",
+            "disjoint_from",
+            "deny_wordlist",
+            "reject_dates",
+            "batch_cover_classes",
+            "unique",
+            "summary",
+            "annotate",
+            "verbose",
+        ]
+    )]
+    token: Option<TokenFormat>,
+
+    /// Specify the number of random bytes of "--token"
+    ///
+    /// The strength of the token is 8 bits per byte.
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 32,
+        value_parser = clap::value_parser!(u32).range(1..),
+        requires = "token"
+    )]
+    bytes: u32,
+
+    /// Exclude characters that look alike (i, l, 1, o, 0, O) from the candidates
+    ///
+    /// Makes the password easier to read and type by hand.
+    /// The excluded characters are not used to meet the "--*-minimum-count" settings either.
+    #[arg(long)]
+    exclude_similar: bool,
+
+    /// Specify the character types from which "--exclude-similar" removes the similar characters
+    ///
+    /// "standard-classes-only" keeps the similar characters in "--other-candidates",
+    /// which are usually chosen on purpose.
+    #[arg(long, value_name = "SCOPE", default_value = "all-classes")]
+    exclude_similar_scope: ExcludeSimilarScope,
+
+    /// Exclude these characters from the candidates of all character types
+    ///
+    /// For example, "--exclude '"\'" keeps " and \ out of the password without rewriting "--symbol-candidates".
+    /// The minimum count of a character type is set to 0 if all of its candidates are excluded.
+    #[arg(long, value_name = "CHARACTERS")]
+    exclude: Option<OsString>,
+
+    /// Include the space character in the candidates
+    ///
+    /// The password may start or end with a space. The output keeps such spaces,
+    /// but they are easy to lose when the password is copied or read with tools that trim whitespace.
+    #[arg(long)]
+    include_whitespace: bool,
+
+    /// Skip ensuring the minimum number of characters for each type
+    ///
+    /// The password is a pure uniform sample from all candidates, so it may not meet the "--*-minimum-count" settings.
+    /// Intended for analyzing the distribution of the candidates. Do not use it for real passwords.
+    #[arg(long)]
+    raw_sample: bool,
+
+    /// Seed the random number generator to make the output reproducible
+    ///
+    /// WARNING: Never use seeded passwords as real credentials. Anyone who knows or guesses the seed can regenerate them.
+    /// Intended for scripted tests and demos. The passwords generated with "--count" differ from each other,
+    /// and the same seed and options generate the same passwords on every run.
+    #[arg(long, value_name = "SEED")]
+    seed: Option<u64>,
+
+    /// Generate the password of the day from the master secret of "--secret"
+    ///
+    /// The seed is derived from the secret and the date with HMAC-SHA256, so everyone who has the secret
+    /// gets the same passwords for the same date and options without coordination, and new passwords the next day.
+    /// Anyone who learns the secret can generate the passwords of every day, past and future,
+    /// so keep it as secret as the passwords and replace it when someone who knows it should lose access.
+    /// The seed has 64 bits, so the passwords are no stronger than 64 bits whatever their length.
+    #[arg(long, requires = "secret", conflicts_with = "seed")]
+    daily: bool,
+
+    /// Master secret of "--daily"
+    ///
+    /// The secret is visible to other users of the system in the process list, and it is stored in the shell history.
+    #[arg(long, value_name = "SECRET", requires = "daily")]
+    secret: Option<String>,
+
+    /// Date of the password of "--daily" as YYYY-MM-DD (today in UTC by default)
+    ///
+    /// Useful to check the password of another day.
+    #[arg(long, value_name = "YYYY-MM-DD", requires = "daily", value_parser = daily::parse_date)]
+    date: Option<String>,
+
+    /// Allow control characters (e.g. tab, newline, NUL, DEL) in the candidates
+    ///
+    /// By default, candidates containing control characters are rejected, since they make passwords hard to type
+    /// and can break newline or null separated output. They sneak in easily when candidates are pasted from files.
+    #[arg(long)]
+    allow_control_chars: bool,
+
+    /// Ensure that every character type appears somewhere in the generated passwords
+    ///
+    /// Each type with candidates appears in at least one of the passwords generated with "--count",
+    /// even if its minimum count is 0. Individual passwords may still lack some types.
+    /// The types that have not appeared yet get a minimum count of 1 in the later passwords,
+    /// and an error names them if those minimum counts cannot be met.
+    #[arg(long)]
+    batch_cover_classes: bool,
+
+    /// Regenerate passwords containing digits that look like a date
+    ///
+    /// Rejects passwords with a year (1900-2099), MMDD, or DDMM in a run of consecutive digits, since dates are easy to guess.
+    /// If no acceptable password is found after many attempts (e.g. when most candidates are digits), an error is returned.
+    #[arg(long)]
+    reject_dates: bool,
+
+    /// Regenerate passwords until they start with the given prefix
+    ///
+    /// Unlike putting the prefix in front of a password, the prefix emerges naturally from the random generation,
+    /// which is useful for demos and analysis. Every character of the prefix must be a candidate.
+    /// Each additional character multiplies the expected number of attempts by about the number of candidates,
+    /// so long prefixes are exponentially expensive and may exceed "--max-attempts".
+    #[arg(long, value_name = "PREFIX")]
+    vanity_prefix: Option<String>,
+
+    /// Regenerate passwords until they share no characters with the old password
+    ///
+    /// Forces a visibly different password when rotating credentials.
+    /// Passwords with any character of the old password are rejected and count toward "--max-attempts",
+    /// and the number of attempts is shown with "--show-stats".
+    /// Fails if the characters of the old password leave no candidates to meet the settings.
+    #[arg(long, value_name = "OLD")]
+    disjoint_from: Option<String>,
+
+    /// Regenerate passwords that contain any word of the file
+    ///
+    /// The file has one word per line, and the words are matched as substrings without regard to case.
+    /// Useful to avoid offensive or common words such as "password" appearing by chance.
+    /// Also applies to "--passphrase". Rejected passwords count toward "--max-attempts".
+    #[arg(long, value_name = "FILE")]
+    deny_wordlist: Option<PathBuf>,
+
+    /// Maximum number of attempts to generate each password that is not rejected
+    ///
+    /// Applies to "--reject-dates", "--vanity-prefix", "--deny-wordlist", and "--unique".
+    /// If no acceptable password is found within the attempts, an error is returned.
+    #[arg(long, default_value_t = 1000)]
+    max_attempts: u32,
+
+    /// Always make "--max-attempts" attempts for each password, and use the first one that is not rejected
+    ///
+    /// Every rejection check runs on every attempt, even after a check fails or a password is accepted.
+    /// Makes the time of the generation independent of how many passwords were rejected,
+    /// which reduces what timing side channels reveal about the passwords.
+    /// This is a best-effort mitigation, not a guarantee of constant time.
+    #[arg(long)]
+    constant_time_ish: bool,
+
+    /// Ensure that all passwords generated with "--count" are different
+    ///
+    /// Passwords that collide with earlier ones are regenerated.
+    /// If the candidates and length allow fewer passwords than "--count", an error is returned.
+    #[arg(long)]
+    unique: bool,
+
+    /// Generate the passwords on this many threads
+    ///
+    /// The passwords are output in the same order as without this option,
+    /// and with "--seed" they are the same byte for byte, since each password has its own stream of the seed.
+    /// Cannot be combined with the options that depend on the passwords generated before.
+    #[arg(
+        long,
+        value_name = "THREADS",
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with_all = ["unique", "batch_cover_classes", "passphrase", "token"]
+    )]
+    parallel: Option<u32>,
+
+    /// Warn about candidates that look alike, such as the Latin "a" and the Cyrillic "а"
+    ///
+    /// Look-alike characters from different scripts make passwords hard to read and type correctly.
+    /// The warnings are printed to standard error, and the passwords are generated as usual.
+    #[arg(long)]
+    check_homoglyphs: bool,
+
+    /// Print a summary of the generation to standard error
+    ///
+    /// The summary includes the number of passwords, their length, the number of candidate characters,
+    /// the entropy per password, and warnings about the settings.
+    #[arg(long)]
+    summary: bool,
+
+    /// Print the total number of attempts to generate the passwords to standard error
+    ///
+    /// Counts the rejected passwords of "--reject-dates" and "--vanity-prefix" as well as the accepted ones.
+    #[arg(long)]
+    show_stats: bool,
+
+    /// Print the effective settings to standard error before generating the passwords
+    ///
+    /// Shows the length, the enabled flags, and the number of candidates and the minimum count of each character type
+    /// after the config file, the presets, and the filters are applied. Useful to find out why passwords look the way they do.
+    #[arg(long, conflicts_with_all = ["passphrase", "token"])]
+    show_config: bool,
+
     /// Separate with null characters
     ///
     /// If this option is not specified, passwords are separated by newline characters.
     #[arg(long)]
     null: bool,
 
+    /// Do not output the separator after the last password
+    ///
+    /// Useful for command substitution and tools that take the output as it is.
+    /// The passwords are still separated from each other with "--count". Ignored with "--format json".
+    #[arg(long)]
+    no_trailing_separator: bool,
+
+    /// Specify the output format of the passwords
+    ///
+    /// "json" outputs a JSON object of the version of its structure and the array of the passwords,
+    /// e.g. {"schema_version":1,"passwords":["password1","password2"]}, for tools that consume the output.
+    /// The version is bumped when the structure changes.
+    /// Cannot be used with "--null" and "--env-var".
+    #[arg(long, value_name = "FORMAT", default_value = "text", conflicts_with_all = ["null", "env_var"])]
+    format: OutputFormat,
+
+    /// Output details of each password
+    ///
+    /// With "--format json", each password is output as an object with its length (number of characters)
+    /// and entropy (bits), e.g. {"schema_version":1,"passwords":[{"password":"...","length":16,"entropy":104.87}]}.
+    /// The size of the candidate pool, the entropy, and a strength rating (Weak, Fair, Strong, or Excellent)
+    /// are also printed once to standard error, so the output of the passwords is not changed.
+    /// The entropy is followed by the bits from each character type.
+    #[arg(long)]
+    verbose: bool,
+
+    /// Prefix each password with its number, starting from 1
+    ///
+    /// Helps to review a batch generated with "--count" by eye, e.g. "1: password".
+    /// Ignored with "--format json" and "--clipboard", so that only the passwords are copied to the clipboard.
+    #[arg(long, conflicts_with = "env_var")]
+    numbered: bool,
+
+    /// Specify the prefix of "--numbered"
+    ///
+    /// "{n}" is replaced with the number of the password.
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        default_value = "{n}: ",
+        requires = "numbered"
+    )]
+    number_format: String,
+
+    /// Insert a separator every N characters of the password
+    ///
+    /// Formats the password like a license key, e.g. "ABCD-EFGH-IJKL" with "--group-size 4".
+    /// The separators do not count toward the length, and wide or combined characters such as emoji are not split.
+    /// Ignored with "--format json", so that tools get the passwords as they are.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    group_size: Option<u32>,
+
+    /// Specify the separator of "--group-size"
+    ///
+    /// The separator must be representable in "--encoding".
+    #[arg(long, value_name = "STR", default_value = "-", requires = "group_size")]
+    group_separator: String,
+
+    /// Print the password spelled out with the NATO phonetic alphabet to standard error
+    ///
+    /// Helps to read the password aloud, e.g. over the phone.
+    /// Uppercase letters are capitalized words ("A" -> "Alpha"), lowercase letters are lowercase words ("b" -> "bravo"),
+    /// and digits and symbols are capitalized names ("7" -> "Seven", "!" -> "Exclamation").
+    /// The password itself is output as usual.
+    #[arg(long)]
+    phonetic: bool,
+
+    /// Print the password expanded into one word per character to standard error
+    ///
+    /// The words are those of "--phonetic", with digits and symbols prefixed by the character ("A" -> "Alpha", "7" -> "7:Seven"),
+    /// so the password can be read back from the first character of each word.
+    /// Characters without a name are kept as they are.
+    /// The password itself is output as usual.
+    #[arg(long)]
+    mnemonic: bool,
+
+    /// Print the password with a line of character type markers beneath it to standard error
+    ///
+    /// Each character is marked with its type: "U" (uppercase), "l" (lowercase), "d" (number), "s" (symbol),
+    /// "o" (other character), or "_" (whitespace). Useful for explaining the structure of a password in documents.
+    /// The password itself is output as usual.
+    #[arg(long)]
+    annotate: bool,
+
+    /// Print the password masked except the last characters to standard error
+    ///
+    /// Useful to confirm the password copied with "--clipboard" or written with "--output" without revealing it.
+    /// Each hidden character is replaced with "*" as wide as the character.
+    /// The password itself is output as usual.
+    #[arg(long)]
+    mask: bool,
+
+    /// Specify the number of characters left visible by "--mask"
+    #[arg(long, value_name = "N", default_value_t = 4, requires = "mask")]
+    mask_reveal: usize,
+
+    /// Output the passwords as shell environment variable assignments
+    ///
+    /// Outputs "export NAME='password'" instead of the bare password, so that the output can be evaluated by a shell,
+    /// e.g. eval "$(mkpw --env-var DB_PASS)".
+    /// If multiple passwords are generated with "--count", the variables are numbered from 1 (NAME_1, NAME_2, ...).
+    #[arg(long, value_name = "NAME")]
+    env_var: Option<String>,
+
+    /// Write the passwords to a file instead of standard output
+    ///
+    /// The file is written with the same separator and "--encoding" as standard output, and overwritten if it exists.
+    /// On Unix, the file is readable and writable only by the owner (0600), since it contains secrets.
+    /// Together with "--clipboard", the passwords are written to the file and also copied to the clipboard.
+    #[arg(long, value_name = "PATH")]
+    output: Option<PathBuf>,
+
     /// Copy the password to the clipboard
     ///
     /// If not specified, the password is output to standard output.
     /// If multiple passwords are generated with "--count", all passwords are copied to the clipboard at once.
+    /// Not available when mkpw is built without the "clipboard" feature.
     #[arg(long)]
+    #[cfg_attr(not(feature = "clipboard"), arg(hide = true))]
     clipboard: bool,
 
+    /// Ask clipboard managers not to keep the copied password in their history
+    ///
+    /// Only effective together with "--clipboard".
+    /// Supported on Windows and macOS. On Linux, the password is marked with the "x-kde-passwordManagerHint"
+    /// that KDE Klipper and other clipboard managers honor.
+    #[arg(long, requires = "clipboard")]
+    #[cfg_attr(not(feature = "clipboard"), arg(hide = true))]
+    clipboard_sensitive: bool,
+
+    /// Specify the selection of the clipboard to copy the passwords to
+    ///
+    /// "primary" is the selection pasted with the middle mouse button,
+    /// which is available only on Linux and the other systems with X11 or Wayland.
+    #[arg(
+        long,
+        value_name = "SELECTION",
+        default_value = "clipboard",
+        requires = "clipboard"
+    )]
+    #[cfg_attr(not(feature = "clipboard"), arg(hide = true))]
+    clipboard_selection: ClipboardSelection,
+
+    /// Keep copying new passwords to the clipboard every interval until interrupted
+    ///
+    /// Useful to rotate demo credentials. Each cycle generates new passwords, copies them to the clipboard,
+    /// and prints the time to standard error. Press Ctrl-C to stop, which clears the clipboard.
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        requires = "clipboard",
+        conflicts_with_all = ["seed", "daily"]
+    )]
+    #[cfg_attr(not(feature = "clipboard"), arg(hide = true))]
+    watch: Option<u64>,
+
+    /// Also output the passwords to standard output with "--clipboard" or "--output"
+    ///
+    /// The passwords are output to all the selected destinations in one run.
+    /// If a destination fails, the others are still tried, and the failed ones are reported.
+    #[arg(long)]
+    tee: bool,
+
+    /// Output the password as a QR code for the terminal instead of the text
+    ///
+    /// Useful to move the password to a phone by scanning it.
+    /// The QR code holds the password in UTF-8 regardless of "--encoding", and only one password can be output.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "clipboard",
+            "output",
+            "env_var",
+            "numbered",
+            "total_chars",
+            "format",
+        ]
+    )]
+    qr: bool,
+
     /// Specify the encoding
     ///
     /// Specify the encoding for each candidate string (--*-candidates).
+    /// The output is written in this encoding, and fails if a password has characters that the encoding cannot represent.
     #[arg(long, default_value = "utf-8")]
     encoding: String,
 
+    /// Try the encodings in order to decode each candidate string (--*-candidates)
+    ///
+    /// The first encoding that decodes the candidates without replacement characters is used,
+    /// e.g. "--encoding-fallback utf-8,shift_jis" when the encoding of the input is uncertain.
+    /// If no encoding decodes them, an error is returned. "--encoding" is still used for the output.
+    #[arg(long, value_name = "LIST", value_delimiter = ',')]
+    encoding_fallback: Option<Vec<String>>,
+
+    /// Remove the variation selectors (U+FE0E, U+FE0F) from the candidates
+    ///
+    /// An emoji with a variation selector (e.g. "❤️") renders differently from its base form ("❤"),
+    /// which makes the password hard to type. With this option the base form is used as the candidate instead.
+    #[arg(long)]
+    strip_variation_selectors: bool,
+
+    /// Use only the candidates that can be represented in the encoding
+    ///
+    /// Candidates that cannot be converted to the encoding without loss (e.g. emoji for "shift_jis") are removed,
+    /// so the password survives systems that store it in that encoding.
+    /// Unlike "--encoding", which converts the candidates and the output, this narrows down the candidates.
+    #[arg(long, value_name = "NAME")]
+    pool_encoding: Option<String>,
+
+    /// Print the encoding used to decode each candidate string (--*-candidates) to standard error
+    #[arg(long)]
+    show_encoding: bool,
+
+    /// Specify where to output error messages
+    ///
+    /// Useful when the consumer of the output reads only standard output.
+    #[arg(long, value_name = "STREAM", default_value = "stderr")]
+    errors_to: ErrorOutput,
+
+    /// Print the equivalent command line to standard error
+    ///
+    /// The command line reconstructs the current settings, including those read from "--config",
+    /// so that the settings can be saved and reproduced later.
+    /// Only the options that differ from the defaults are printed.
+    #[arg(long)]
+    print_command: bool,
+
+    /// Include the options left at their defaults in "--print-command"
+    ///
+    /// Useful to keep the settings reproducible even if the defaults change in a future version.
+    #[arg(long, requires = "print_command")]
+    print_command_verbose: bool,
+
     /// Print the completion script
     ///
     /// If this option is specified, the password is not output. Also, even if '--clipboard' is specified, the completion script is output to standard output.
     #[arg(long, value_name = "SHELL")]
     completion: Option<Shell>,
+
+    /// Print the labels of the encodings that "--encoding" and the other encoding options accept
+    ///
+    /// If this option is specified, the password is not output.
+    #[arg(long)]
+    list_encodings: bool,
 }
 
 impl Default for Cli {
     fn default() -> Self {
         Cli {
             length: 16,
+            max_length_limit: 1_000_000,
             count: 1,
+            total_chars: None,
+            config: None,
             uppercase_candidates: OsString::from("ABCDEFGHIJKLMNOPQRSTUVWXYZ"),
             uppercase_minimum_count: 1,
+            uppercase_maximum_count: None,
             lowercase_candidates: OsString::from("abcdefghijklmnopqrstuvwxyz"),
             lowercase_minimum_count: 1,
+            lowercase_maximum_count: None,
+            uppercase_from_lowercase: false,
+            lowercase_from_uppercase: false,
             number_candidates: OsString::from("0123456789"),
             number_minimum_count: 1,
+            number_maximum_count: None,
             symbol_candidates: OsString::from("!\"#$%&\'()*+,-./:;<=>?@[\\]^_`{|}~"),
             symbol_minimum_count: 1,
+            symbol_maximum_count: None,
+            apple_rules: None,
+            pin: None,
+            pattern: None,
+            preset: None,
+            min_entropy: None,
+            no_leading_zero: false,
+            count_stdin: false,
+            candidates_stdin: None,
+            stdin_candidates: None,
             other_candidates: None,
             other_minimum_count: None,
+            other_maximum_count: None,
+            passphrase: false,
+            words: 5,
+            separator: String::from("-"),
+            wordlist: None,
+            token: None,
+            bytes: 32,
+            exclude_similar: false,
+            exclude_similar_scope: ExcludeSimilarScope::AllClasses,
+            exclude: None,
+            include_whitespace: false,
+            raw_sample: false,
+            seed: None,
+            daily: false,
+            secret: None,
+            date: None,
+            allow_control_chars: false,
+            batch_cover_classes: false,
+            reject_dates: false,
+            vanity_prefix: None,
+            disjoint_from: None,
+            deny_wordlist: None,
+            max_attempts: 1000,
+            constant_time_ish: false,
+            unique: false,
+            parallel: None,
+            check_homoglyphs: false,
+            summary: false,
+            show_stats: false,
+            show_config: false,
+            phonetic: false,
+            mnemonic: false,
+            annotate: false,
+            mask: false,
+            mask_reveal: 4,
+            env_var: None,
             null: false,
+            no_trailing_separator: false,
+            format: OutputFormat::Text,
+            verbose: false,
+            numbered: false,
+            number_format: String::from("{n}: "),
+            group_size: None,
+            group_separator: String::from("-"),
+            output: None,
             clipboard: false,
+            clipboard_sensitive: false,
+            clipboard_selection: ClipboardSelection::Clipboard,
+            watch: None,
+            tee: false,
+            qr: false,
             encoding: String::from("utf-8"),
+            encoding_fallback: None,
+            pool_encoding: None,
+            show_encoding: false,
+            strip_variation_selectors: false,
+            errors_to: ErrorOutput::Stderr,
+            print_command: false,
+            print_command_verbose: false,
             completion: None,
+            list_encodings: false,
         }
     }
 }
@@ -150,613 +1004,5467 @@ fn print_completions<G: Generator>(gen: G) {
 /// # Arguments
 ///
 /// * `text` - Text to write to the clipboard
+/// * `sensitive` - Whether to ask clipboard managers not to keep the text in their history
+/// * `selection` - Selection of the clipboard to write to
 ///
 /// # Returns
 ///
 /// Returns an error message if an error occurs
-fn write_to_clipboard(text: &str) -> Result<(), String> {
+#[cfg(feature = "clipboard")]
+fn write_to_clipboard(
+    text: &str,
+    sensitive: bool,
+    selection: ClipboardSelection,
+) -> Result<(), String> {
+    // Only Linux and the other systems with X11 or Wayland have the primary selection
+    #[cfg(not(all(
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    )))]
+    if selection == ClipboardSelection::Primary {
+        return Err("The primary selection is not available on this platform. Please use \"--clipboard-selection clipboard\".".to_string());
+    }
+
     let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
-    clipboard.set_text(text).map_err(|e| e.to_string())?;
+    let set = clipboard.set();
+
+    // On Linux and the other systems with X11 or Wayland, the text is marked with the "x-kde-passwordManagerHint"
+    // that KDE Klipper and other clipboard managers honor
+    #[cfg(any(
+        windows,
+        all(unix, not(any(target_os = "android", target_os = "emscripten")))
+    ))]
+    let set = if sensitive {
+        set.exclude_from_history()
+    } else {
+        set
+    };
+    #[cfg(not(any(
+        windows,
+        all(unix, not(any(target_os = "android", target_os = "emscripten")))
+    )))]
+    let _ = sensitive;
+
+    #[cfg(all(
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))]
+    let set = set.clipboard(match selection {
+        ClipboardSelection::Clipboard => LinuxClipboardKind::Clipboard,
+        ClipboardSelection::Primary => LinuxClipboardKind::Primary,
+    });
+
+    set.text(text).map_err(|e| e.to_string())?;
     Ok(())
 }
 
-/// Set character types for the password generator
+/// Write text to the clipboard, which is not available without the "clipboard" feature
+///
+/// # Returns
+///
+/// Always returns an error message
+#[cfg(not(feature = "clipboard"))]
+fn write_to_clipboard(
+    _text: &str,
+    _sensitive: bool,
+    _selection: ClipboardSelection,
+) -> Result<(), String> {
+    Err(CLIPBOARD_DISABLED.to_string())
+}
+
+/// Error message for "--clipboard" when mkpw is built without the "clipboard" feature
+#[cfg(not(feature = "clipboard"))]
+const CLIPBOARD_DISABLED: &str = "\"--clipboard\" is not available because mkpw was built without the \"clipboard\" feature. Please rebuild mkpw with the \"clipboard\" feature, or use \"--output\" instead.";
+
+/// Copy new passwords to the clipboard every interval of "--watch" until stopped
+///
+/// Each cycle generates the passwords in the same way as a single run, so the random number generator is drawn again
+/// and the passwords differ in every cycle.
+/// When stopped, or when a cycle fails, the clipboard is cleared so that the last password does not stay in it.
 ///
 /// # Arguments
 ///
-/// * `maker` - Password generator
 /// * `args` - Command line arguments
+/// * `interval` - Interval between the cycles
+/// * `attempts` - Counter of the attempts
+/// * `maker` - Password generator describing the passwords, required for "--annotate", "--summary", and "--verbose"
+/// * `stop` - Flag set to stop, e.g. by Ctrl-C
 ///
 /// # Returns
 ///
 /// Returns an error message if an error occurs
-fn set_classifiers(maker: &mut PasswordMaker, args: &Cli) -> Result<(), String> {
-    fn set_candidates_and_minimum_count(
-        candidates: &[u8],
-        encoding: &String,
-        minimum_count: u32,
-    ) -> Result<(Vec<String>, u32), String> {
-        let decoded = encoding::decode(candidates, encoding)?
-            .graphemes(true)
-            .map(|s| s.to_string())
-            .collect::<Vec<_>>();
-        let min_count = if decoded.is_empty() { 0 } else { minimum_count };
-        Ok((decoded, min_count))
-    }
-
-    (maker.uppercase.candidates, maker.uppercase.minimum_count) = set_candidates_and_minimum_count(
-        args.uppercase_candidates.as_encoded_bytes(),
-        &args.encoding,
-        args.uppercase_minimum_count,
-    )?;
-
-    (maker.lowercase.candidates, maker.lowercase.minimum_count) = set_candidates_and_minimum_count(
-        args.lowercase_candidates.as_encoded_bytes(),
-        &args.encoding,
-        args.lowercase_minimum_count,
-    )?;
+#[cfg(feature = "clipboard")]
+fn watch_clipboard(
+    args: &Cli,
+    interval: std::time::Duration,
+    attempts: &mut u64,
+    maker: Option<&PasswordMaker>,
+    stop: &std::sync::atomic::AtomicBool,
+) -> Result<(), String> {
+    use std::sync::atomic::Ordering;
+    use std::time::{Instant, SystemTime};
 
-    (maker.number.candidates, maker.number.minimum_count) = set_candidates_and_minimum_count(
-        args.number_candidates.as_encoded_bytes(),
-        &args.encoding,
-        args.number_minimum_count,
-    )?;
-
-    (maker.symbol.candidates, maker.symbol.minimum_count) = set_candidates_and_minimum_count(
-        args.symbol_candidates.as_encoded_bytes(),
-        &args.encoding,
-        args.symbol_minimum_count,
-    )?;
+    // Check the flag often so that Ctrl-C stops soon even with a long interval
+    const POLL: std::time::Duration = std::time::Duration::from_millis(100);
 
-    let mut other_candidates = args
-        .other_candidates
-        .clone()
-        .unwrap_or_default()
-        .iter()
-        .map(|s| encoding::decode(s.as_encoded_bytes(), &args.encoding))
-        .collect::<Result<Vec<String>, String>>()?;
-    let mut other_minimum_count = args.other_minimum_count.clone().unwrap_or_default();
+    let mut result = Ok(());
+    while !stop.load(Ordering::SeqCst) {
+        if let Err(e) = output_all_passwords(args, attempts, maker) {
+            result = Err(e);
+            break;
+        }
+        eprintln!(
+            "Copied new passwords at {}",
+            utc_timestamp(SystemTime::now())
+        );
 
-    // Adjust the number of candidates and minimum counts
-    while other_candidates.len() < other_minimum_count.len() {
-        other_candidates.push(String::new());
-    }
-    while other_minimum_count.len() < other_candidates.len() {
-        other_minimum_count.push(0);
+        let cycle = Instant::now();
+        while !stop.load(Ordering::SeqCst) && cycle.elapsed() < interval {
+            std::thread::sleep(POLL.min(interval.saturating_sub(cycle.elapsed())));
+        }
     }
 
-    maker.others = other_candidates
-        .into_iter()
-        .zip(other_minimum_count)
-        .map(|(candidates, minimum_count)| {
-            let candidates = candidates.graphemes(true).map(|s| s.to_string()).collect();
-            password_maker::Classifier {
-                candidates,
-                minimum_count,
-            }
-        })
-        .collect();
+    // The error of the cycle is reported rather than the one of clearing
+    let cleared = write_to_clipboard("", args.clipboard_sensitive, args.clipboard_selection)
+        .map_err(|e| format!("Failed to clear the clipboard: {}", e));
+    result.and(cleared)
+}
 
-    Ok(())
+/// Format a time as an ISO 8601 timestamp in UTC, e.g. "2024-12-31T23:59:59Z"
+///
+/// # Arguments
+///
+/// * `time` - Time to format
+///
+/// # Returns
+///
+/// Timestamp, or the Unix epoch for a time before it
+#[cfg(feature = "clipboard")]
+fn utc_timestamp(time: std::time::SystemTime) -> String {
+    let seconds = seconds_since_epoch(time);
+    let (year, month, day) = civil_from_days(seconds / 86_400);
+    let time_of_day = seconds % 86_400;
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time_of_day / 3_600,
+        time_of_day % 3_600 / 60,
+        time_of_day % 60
+    )
 }
 
-/// Generate passwords
+/// Format the date of a time in UTC, e.g. "2024-12-31"
 ///
 /// # Arguments
 ///
-/// * `args` - Command line arguments
+/// * `time` - Time to format
 ///
 /// # Returns
 ///
-/// List of passwords
+/// Date, or the Unix epoch for a time before it
+fn utc_date(time: std::time::SystemTime) -> String {
+    let (year, month, day) = civil_from_days(seconds_since_epoch(time) / 86_400);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Return the seconds since the Unix epoch, or 0 for a time before it
 ///
-/// # Errors
+/// # Arguments
 ///
-/// Returns an error if password generation fails
-fn generate_passwords(args: &Cli) -> Result<Vec<String>, String> {
-    let mut passwords: Vec<String> = Vec::new();
-    let mut maker = PasswordMaker {
-        length: args.length,
-        ..PasswordMaker::default()
+/// * `time` - Time
+fn seconds_since_epoch(time: std::time::SystemTime) -> u64 {
+    time.duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Convert the days since the Unix epoch to a date of the proleptic Gregorian calendar
+///
+/// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+///
+/// # Arguments
+///
+/// * `days` - Days since the Unix epoch
+///
+/// # Returns
+///
+/// Year, month, and day
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
     };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
 
-    set_classifiers(&mut maker, args)?;
+    (year, month, day)
+}
 
-    for _ in 0..args.count {
-        let password = maker.generate()?;
-        passwords.push(password);
-    }
+/// Disable the case conversions of the candidates given explicitly on the command line
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments
+/// * `matches` - Parsed command line arguments, to tell which options are given on the command line
+fn drop_overridden_conversions(args: &mut Cli, matches: &ArgMatches) {
+    let explicit = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
 
-    Ok(passwords)
+    if explicit("uppercase_candidates") {
+        args.uppercase_from_lowercase = false;
+    }
+    if explicit("lowercase_candidates") {
+        args.lowercase_from_uppercase = false;
+    }
 }
 
-/// Format passwords
-///
-/// If null_separator is true, separate with null characters; otherwise, separate with newline characters (\n)
+/// Maximum number of passwords read by "--count-stdin"
+const MAX_STDIN_COUNT: u32 = 1_000_000;
+
+/// Read the number of passwords of "--count-stdin" from standard input
 ///
 /// # Arguments
 ///
-/// * `passwords` - List of passwords
-/// * `null_separator` - Whether to separate with null characters
+/// * `args` - Command line arguments
+/// * `reader` - Standard input
 ///
 /// # Returns
 ///
-/// Formatted passwords
-fn format_passwords(passwords: Vec<String>, null_separator: bool) -> String {
-    let separater = match null_separator {
-        true => "\0",
-        false => "\n",
-    };
+/// Returns an error message if the input cannot be read or is not a number from 1 to [`MAX_STDIN_COUNT`]
+fn read_stdin_count(args: &mut Cli, reader: &mut impl io::Read) -> Result<(), String> {
+    if !args.count_stdin {
+        return Ok(());
+    }
+
+    let mut input = String::new();
+    reader
+        .read_to_string(&mut input)
+        .map_err(|e| format!("Failed to read the count from standard input: {}", e))?;
 
-    passwords.join(separater) + separater
+    args.count = input
+        .trim()
+        .parse()
+        .ok()
+        .filter(|count| (1..=MAX_STDIN_COUNT).contains(count))
+        .ok_or_else(|| {
+            format!(
+                "The count \"{}\" read from standard input is invalid. Please pass a number from 1 to {}.",
+                input.trim(),
+                MAX_STDIN_COUNT
+            )
+        })?;
+    Ok(())
 }
 
-/// Output passwords
+/// Read the candidates of "--candidates-stdin" from standard input
 ///
-/// Copy to clipboard if specified, otherwise output to standard output
+/// Like an explicit "--uppercase-candidates" or "--lowercase-candidates", the candidates read for them disable the case conversion to them.
 ///
 /// # Arguments
 ///
-/// * `text` - Text to output
 /// * `args` - Command line arguments
+/// * `reader` - Standard input
 ///
 /// # Returns
 ///
-/// Returns an error message if an error occurs
-fn output_passwords(text: &str, args: &Cli) -> Result<(), String> {
-    if args.clipboard {
-        write_to_clipboard(text)?;
-    } else {
-        let encoded_string = encode(text, &args.encoding)?;
+/// Returns an error message if the input cannot be read
+fn read_stdin_candidates(args: &mut Cli, reader: &mut impl io::Read) -> Result<(), String> {
+    let Some(class) = args.candidates_stdin else {
+        return Ok(());
+    };
 
-        let stdout = io::stdout();
-        let mut handle = stdout.lock();
-        handle
-            .write_all(&encoded_string)
-            .map_err(|e| e.to_string())?;
-    }
+    let mut candidates = vec![];
+    reader
+        .read_to_end(&mut candidates)
+        .map_err(|e| format!("Failed to read the candidates from standard input: {}", e))?;
+    args.stdin_candidates = Some(candidates);
 
+    match class {
+        CandidateClass::Uppercase => args.uppercase_from_lowercase = false,
+        CandidateClass::Lowercase => args.lowercase_from_uppercase = false,
+        _ => {}
+    }
     Ok(())
 }
 
-/// Generate passwords
+/// Apply the settings of the preset to the command line arguments
+///
+/// Only the settings that are not given on the command line are taken from the preset.
 ///
 /// # Arguments
 ///
 /// * `args` - Command line arguments
+/// * `matches` - Parsed command line arguments, to tell which options are given on the command line
 ///
 /// # Returns
 ///
 /// Returns an error message if an error occurs
-fn password(args: Cli) -> Result<(), String> {
-    let passwords = generate_passwords(&args)?;
-    let output_string = format_passwords(passwords, args.null);
-    output_passwords(&output_string, &args)
-}
+fn apply_preset(args: &mut Cli, matches: &ArgMatches) -> Result<(), String> {
+    let from_preset = |id: &str| matches.value_source(id) != Some(ValueSource::CommandLine);
 
-fn main() -> ExitCode {
-    let args = Cli::parse();
+    let password_maker = match args.preset {
+        None => return Ok(()),
+        Some(Preset::Memorable) => {
+            if args.wordlist.is_none() {
+                return Err("The preset \"memorable\" generates passphrases, but no wordlist is given. Please specify the wordlist with \"--wordlist\".".to_string());
+            }
+            let passphrase = Passphrase::memorable(vec![]);
+            args.passphrase = true;
+            if from_preset("words") {
+                args.words = passphrase.word_count;
+            }
+            if from_preset("separator") {
+                args.separator = passphrase.separator;
+            }
+            return Ok(());
+        }
+        Some(Preset::Strong) => PasswordMaker::strong(),
+        Some(Preset::Pin) => PasswordMaker::pin(6),
+        Some(Preset::Wifi) => PasswordMaker::wifi(),
+    };
 
-    if let Some(shell) = args.completion {
-        print_completions(shell);
-        return ExitCode::SUCCESS;
+    // The parser accepts the options of passphrases with any preset
+    if ["words", "separator", "wordlist"]
+        .iter()
+        .any(|id| !from_preset(id))
+    {
+        return Err("\"--words\", \"--separator\", and \"--wordlist\" are options of passphrases. Please remove them, or use \"--preset memorable\".".to_string());
     }
 
-    match password(args) {
-        Ok(_) => ExitCode::SUCCESS,
-        Err(e) => {
-            eprintln!("{}", e);
-            ExitCode::FAILURE
+    macro_rules! apply {
+        ($field:ident, $value:expr) => {
+            if from_preset(stringify!($field)) {
+                args.$field = $value;
+            }
+        };
+    }
+
+    let candidates = |classifier: &Classifier| OsString::from(classifier.candidates.concat());
+
+    apply!(length, password_maker.length);
+    apply!(uppercase_candidates, candidates(&password_maker.uppercase));
+    apply!(
+        uppercase_minimum_count,
+        password_maker.uppercase.minimum_count
+    );
+    apply!(lowercase_candidates, candidates(&password_maker.lowercase));
+    apply!(
+        lowercase_minimum_count,
+        password_maker.lowercase.minimum_count
+    );
+    apply!(number_candidates, candidates(&password_maker.number));
+    apply!(number_minimum_count, password_maker.number.minimum_count);
+    apply!(symbol_candidates, candidates(&password_maker.symbol));
+    apply!(symbol_minimum_count, password_maker.symbol.minimum_count);
+    apply!(exclude_similar, password_maker.exclude_similar);
+    apply!(
+        exclude_similar_scope,
+        match password_maker.exclude_similar_scope {
+            SimilarScope::AllClasses => ExcludeSimilarScope::AllClasses,
+            SimilarScope::StandardClassesOnly => ExcludeSimilarScope::StandardClassesOnly,
         }
+    );
+    if !password_maker.exclude_characters.is_empty() {
+        apply!(
+            exclude,
+            Some(OsString::from(password_maker.exclude_characters.concat()))
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use assert_cmd::Command;
-    #[cfg(unix)]
-    use std::os::unix::ffi::OsStringExt;
-    use std::{ffi::OsString, vec};
+    Ok(())
+}
 
-    use super::*;
+/// Merge the settings of the config file into the command line arguments
+///
+/// Only the settings that are not given on the command line are taken from the file.
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments
+/// * `matches` - Parsed command line arguments, to tell which options are given on the command line
+///
+/// # Returns
+///
+/// Returns an error message if an error occurs
+fn merge_config(args: &mut Cli, matches: &ArgMatches) -> Result<(), String> {
+    let Some(path) = &args.config else {
+        return Ok(());
+    };
+    let config = config::load(path)?;
 
-    #[test]
-    fn default_password_generation() {
-        let args = Cli::default();
+    let from_file = |id: &str| matches.value_source(id) != Some(ValueSource::CommandLine);
 
-        let passwords = generate_passwords(&args).unwrap();
-        assert_eq!(passwords.len(), 1);
-        // If candidates are added, one character may not be 1 byte, but by default, one character is 1 byte, so check the length with len()
-        assert_eq!(passwords[0].len(), 16);
+    // TOML files are always UTF-8, while the candidates on the command line are decoded with "--encoding"
+    let has_candidates = config.uppercase_candidates.is_some()
+        || config.lowercase_candidates.is_some()
+        || config.number_candidates.is_some()
+        || config.symbol_candidates.is_some()
+        || config.others.is_some();
+    if has_candidates && encoding::name(&args.encoding)? != "UTF-8" {
+        return Err(format!(
+            "The candidates in the config file are UTF-8, but the encoding is {}. Please remove \"--encoding\" or the candidates from the config file.",
+            args.encoding
+        ));
     }
 
-    #[test]
-    fn multiple_password_generation() {
-        let args = Cli {
-            count: 5,
-            ..Default::default()
+    macro_rules! merge {
+        ($field:ident) => {
+            if let Some(value) = config.$field {
+                if from_file(stringify!($field)) {
+                    args.$field = value.into();
+                }
+            }
         };
+    }
 
-        let passwords = generate_passwords(&args).unwrap();
-        assert_eq!(passwords.len(), 5);
+    // Explicit candidates take precedence over the case conversions
+    if config.uppercase_candidates.is_some() && from_file("uppercase_candidates") {
+        args.uppercase_from_lowercase = false;
+    }
+    if config.lowercase_candidates.is_some() && from_file("lowercase_candidates") {
+        args.lowercase_from_uppercase = false;
+    }
 
-        // Check that there are no duplicate passwords when generating multiple passwords
-        let unique_passwords: std::collections::HashSet<_> = passwords.iter().collect();
-        assert_eq!(passwords.len(), unique_passwords.len());
+    merge!(length);
+    merge!(count);
+    merge!(uppercase_candidates);
+    merge!(uppercase_minimum_count);
+    merge!(lowercase_candidates);
+    merge!(lowercase_minimum_count);
+    merge!(number_candidates);
+    merge!(number_minimum_count);
+    merge!(symbol_candidates);
+    merge!(symbol_minimum_count);
+
+    if let Some(others) = config.others {
+        if from_file("other_candidates") && from_file("other_minimum_count") {
+            args.other_candidates = Some(
+                others
+                    .iter()
+                    .map(|other| OsString::from(&other.candidates))
+                    .collect(),
+            );
+            args.other_minimum_count =
+                Some(others.iter().map(|other| other.minimum_count).collect());
+        }
     }
 
-    #[test]
-    fn password_with_other_characters() {
-        // Generate a password that includes special characters such as surrogate pairs
-        // There may be more special characters, but since we are also testing zero-width joiners, this is sufficient.
-        let args = Cli {
-            other_candidates: Some(vec![
-                // Surrogate pair
-                OsString::from("😀🚀🐱"),
-                // Variation Selectors
-                OsString::from("花󠄁龍󠄀舟󠄁👍🏿"),
-                // Combining character
-                OsString::from("áパぎ"),
-                // Zero-width joiner
-                OsString::from("🏳️‍🌈❤️‍🔥👨‍👩‍👦"),
-                // Emoji flag sequence
-                OsString::from("🇯🇵🇺🇸🇲🇦🇨🇦"),
-            ]),
-            other_minimum_count: Some(vec![1, 2, 3, 4, 2]),
-            ..Default::default()
-        };
+    Ok(())
+}
 
-        let passwords = generate_passwords(&args).unwrap();
-        println!("{}", passwords[0]);
+/// Set character types for the password generator
+///
+/// # Arguments
+///
+/// * `maker` - Password generator
+/// * `args` - Command line arguments
+///
+/// # Returns
+///
+/// Returns an error message if an error occurs
+fn set_classifiers(maker: &mut PasswordMaker, args: &Cli) -> Result<(), String> {
+    fn set_candidates_and_minimum_count(
+        candidates: &str,
+        minimum_count: u32,
+    ) -> (Vec<String>, u32) {
+        let decoded: Vec<String> = expand_ranges(candidates)
+            .graphemes(true)
+            .map(|s| s.to_string())
+            .collect();
+        let min_count = if decoded.is_empty() { 0 } else { minimum_count };
+        (decoded, min_count)
+    }
 
-        assert_eq!(passwords.len(), 1);
-        assert_eq!(passwords[0].graphemes(true).count(), 16);
+    // The candidates read from standard input, without the trailing newline
+    let stdin_candidates = match &args.stdin_candidates {
+        Some(candidates) => {
+            let decoded = decode_candidates(candidates, args)?.0;
+            let line = decoded.strip_suffix('\n').unwrap_or(&decoded);
+            Some(line.strip_suffix('\r').unwrap_or(line).to_string())
+        }
+        None => None,
+    };
+    let from_stdin = |class: CandidateClass| {
+        stdin_candidates
+            .as_ref()
+            .filter(|_| args.candidates_stdin == Some(class))
+    };
 
-        // Test if the string contains the characters
-        let count_rocket = passwords[0].matches("🚀").count();
-        let count_cat = passwords[0].matches("🐱").count();
-        let count_smile = passwords[0].matches("😀").count();
-        assert!(1 <= count_rocket + count_cat + count_smile);
+    let decode = |class: CandidateClass, candidates: &OsString| match from_stdin(class) {
+        Some(candidates) => Ok(candidates.clone()),
+        None => decode_candidates(candidates.as_encoded_bytes(), args).map(|(decoded, _)| decoded),
+    };
 
-        let count_hana = passwords[0].matches("花󠄁").count();
-        let count_ryu = passwords[0].matches("龍󠄀").count();
-        let count_fune = passwords[0].matches("舟󠄁").count();
-        let count_ok = passwords[0].matches("👍🏿").count();
-        assert!(2 <= count_hana + count_ryu + count_fune + count_ok);
+    (maker.uppercase.candidates, maker.uppercase.minimum_count) = set_candidates_and_minimum_count(
+        &decode(CandidateClass::Uppercase, &args.uppercase_candidates)?,
+        args.uppercase_minimum_count,
+    );
+    maker.uppercase.maximum_count = args.uppercase_maximum_count;
 
-        let count_a = passwords[0].matches("á").count();
-        let count_pa = passwords[0].matches("パ").count();
-        let count_ki = passwords[0].matches("ぎ").count();
-        assert!(3 <= count_a + count_pa + count_ki);
+    (maker.lowercase.candidates, maker.lowercase.minimum_count) = set_candidates_and_minimum_count(
+        &decode(CandidateClass::Lowercase, &args.lowercase_candidates)?,
+        args.lowercase_minimum_count,
+    );
+    maker.lowercase.maximum_count = args.lowercase_maximum_count;
 
-        let count_rainbow = passwords[0].matches("🏳️‍🌈").count();
-        let count_fire = passwords[0].matches("❤️‍🔥").count();
-        let count_family = passwords[0].matches("👨‍👩‍👦").count();
-        assert!(4 <= count_rainbow + count_fire + count_family);
+    (maker.number.candidates, maker.number.minimum_count) = set_candidates_and_minimum_count(
+        &decode(CandidateClass::Number, &args.number_candidates)?,
+        args.number_minimum_count,
+    );
+    maker.number.maximum_count = args.number_maximum_count;
 
-        let count_jp = passwords[0].matches("🇯🇵").count();
-        let count_us = passwords[0].matches("🇺🇸").count();
-        let count_ma = passwords[0].matches("🇲🇦").count();
-        let count_ca = passwords[0].matches("🇨🇦").count();
-        assert!(2 <= count_jp + count_us + count_ma + count_ca);
-    }
+    (maker.symbol.candidates, maker.symbol.minimum_count) = set_candidates_and_minimum_count(
+        &decode(CandidateClass::Symbol, &args.symbol_candidates)?,
+        args.symbol_minimum_count,
+    );
+    maker.symbol.maximum_count = args.symbol_maximum_count;
 
-    #[test]
-    fn generate_passwords_err() {
-        let args = Cli {
-            length: 0,
-            ..Default::default()
+    if args.uppercase_from_lowercase {
+        maker.uppercase.candidates = convert_case(&maker.lowercase.candidates, str::to_uppercase);
+        maker.uppercase.minimum_count = if maker.uppercase.candidates.is_empty() {
+            0
+        } else {
+            args.uppercase_minimum_count
         };
-
-        let result = generate_passwords(&args);
-        assert!(result.is_err());
     }
 
-    #[test]
-    fn format_passwords_with_null_separator() {
-        let passwords = vec!["password1".to_string(), "password2".to_string()];
-        let formatted = format_passwords(passwords, true);
-        assert_eq!(formatted, "password1\0password2\0");
+    if args.lowercase_from_uppercase {
+        maker.lowercase.candidates = convert_case(&maker.uppercase.candidates, str::to_lowercase);
+        maker.lowercase.minimum_count = if maker.lowercase.candidates.is_empty() {
+            0
+        } else {
+            args.lowercase_minimum_count
+        };
     }
 
-    #[test]
-    fn format_passwords_with_newline_separator() {
-        let passwords = vec!["password1".to_string(), "password2".to_string()];
-        let formatted = format_passwords(passwords, false);
-        assert_eq!(formatted, "password1\npassword2\n");
+    let mut other_candidates = args
+        .other_candidates
+        .clone()
+        .unwrap_or_default()
+        .iter()
+        .map(|s| decode_candidates(s.as_encoded_bytes(), args).map(|(decoded, _)| decoded))
+        .collect::<Result<Vec<String>, String>>()?;
+    other_candidates.extend(from_stdin(CandidateClass::Other).cloned());
+    let mut other_minimum_count = args.other_minimum_count.clone().unwrap_or_default();
+    let other_maximum_count = args.other_maximum_count.clone().unwrap_or_default();
+
+    // Adjust the number of candidates and minimum counts
+    while other_candidates.len() < other_minimum_count.len().max(other_maximum_count.len()) {
+        other_candidates.push(String::new());
+    }
+    while other_minimum_count.len() < other_candidates.len() {
+        other_minimum_count.push(0);
     }
 
-    #[test]
-    fn set_classifiers_utf8() {
-        let mut maker = PasswordMaker::default();
-        let args = Cli {
-            uppercase_candidates: OsString::from("ABC"),
-            uppercase_minimum_count: 2,
-            lowercase_candidates: OsString::from("abc"),
-            lowercase_minimum_count: 3,
-            number_candidates: OsString::from("123"),
-            number_minimum_count: 4,
-            symbol_candidates: OsString::from("!@#"),
-            symbol_minimum_count: 5,
-            other_candidates: Some(vec![OsString::from("😀👨‍👩‍👦😂"), OsString::from("あいう")]),
-            other_minimum_count: Some(vec![6, 7]),
-            ..Default::default()
-        };
+    maker.others = other_candidates
+        .into_iter()
+        .zip(other_minimum_count)
+        .enumerate()
+        .map(|(index, (candidates, minimum_count))| {
+            let candidates = expand_ranges(&candidates)
+                .graphemes(true)
+                .map(|s| s.to_string())
+                .collect();
+            Classifier {
+                candidates,
+                minimum_count,
+                maximum_count: other_maximum_count.get(index).copied(),
+                ..Default::default()
+            }
+        })
+        .collect();
 
-        set_classifiers(&mut maker, &args).unwrap();
+    Ok(())
+}
 
-        assert_eq!(maker.uppercase.candidates, vec!["A", "B", "C"]);
-        assert_eq!(maker.uppercase.minimum_count, 2);
-        assert_eq!(maker.lowercase.candidates, vec!["a", "b", "c"]);
-        assert_eq!(maker.lowercase.minimum_count, 3);
-        assert_eq!(maker.number.candidates, vec!["1", "2", "3"]);
-        assert_eq!(maker.number.minimum_count, 4);
-        assert_eq!(maker.symbol.candidates, vec!["!", "@", "#"]);
+/// Convert the case of candidates
+///
+/// Candidates that do not change or do not convert to a single grapheme (e.g. "ß" -> "SS") are skipped,
+/// and duplicates are removed.
+///
+/// # Arguments
+///
+/// * `candidates` - Candidates to convert
+/// * `convert` - Case conversion (e.g. `str::to_uppercase`)
+///
+/// # Returns
+///
+/// Converted candidates
+fn convert_case(candidates: &[String], convert: fn(&str) -> String) -> Vec<String> {
+    let mut converted: Vec<String> = vec![];
+    for candidate in candidates {
+        let grapheme = convert(candidate);
+        if grapheme != *candidate
+            && grapheme.graphemes(true).count() == 1
+            && !converted.contains(&grapheme)
+        {
+            converted.push(grapheme);
+        }
+    }
+    converted
+}
+
+/// Expand the ranges of characters such as "a-z" in a candidate string
+///
+/// A range is expanded only if both ends are single scalar values of the same block, and the start is not after the end:
+/// - ASCII: both are digits, lowercases, or uppercases (e.g. "0-9", "a-f", "A-Z")
+/// - Non-ASCII: neither is ASCII nor a control character, and both are in the same Unicode block (e.g. "あ-ん")
+///
+/// The unassigned code points and the combining marks in a non-ASCII range are skipped,
+/// since they are not characters on their own.
+/// Otherwise, the characters are used as they are, so "-" at either end, or between characters that do not form a range,
+/// is a literal hyphen. "\-" and "\\" are a literal hyphen and backslash, and other backslashes are used as they are.
+///
+/// # Arguments
+///
+/// * `candidates` - Candidate string
+///
+/// # Returns
+///
+/// Candidate string with the ranges expanded
+fn expand_ranges(candidates: &str) -> String {
+    fn scalar(grapheme: &str) -> Option<char> {
+        let mut chars = grapheme.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    }
+
+    fn is_range(start: char, end: char) -> bool {
+        let same_ascii_kind = [
+            char::is_ascii_digit,
+            char::is_ascii_lowercase,
+            char::is_ascii_uppercase,
+        ]
+        .iter()
+        .any(|is_kind| is_kind(&start) && is_kind(&end));
+        let same_non_ascii_block = !start.is_ascii()
+            && !end.is_ascii()
+            && !start.is_control()
+            && !end.is_control()
+            && unicode_block::is_same_block(start, end);
+        start <= end && (same_ascii_kind || same_non_ascii_block)
+    }
+
+    let graphemes: Vec<&str> = candidates.graphemes(true).collect();
+    let mut expanded = String::new();
+    let mut index = 0;
+    while index < graphemes.len() {
+        let grapheme = graphemes[index];
+        if grapheme == "\\" && matches!(graphemes.get(index + 1), Some(&"-") | Some(&"\\")) {
+            expanded.push_str(graphemes[index + 1]);
+            index += 2;
+            continue;
+        }
+
+        let range = match (graphemes.get(index + 1), graphemes.get(index + 2)) {
+            (Some(&"-"), Some(end)) => scalar(grapheme).zip(scalar(end)),
+            _ => None,
+        };
+        match range {
+            Some((start, end)) if is_range(start, end) => {
+                expanded.extend(
+                    (start..=end).filter(|&c| !unicode_block::is_unassigned_or_combining(c)),
+                );
+                index += 3;
+            }
+            _ => {
+                expanded.push_str(grapheme);
+                index += 1;
+            }
+        }
+    }
+    expanded
+}
+
+/// Return the seed of the random number generator
+///
+/// With "--daily", the seed is derived from "--secret" and "--date" (today in UTC by default).
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments
+///
+/// # Returns
+///
+/// Seed, or `None` for a random one
+fn seed(args: &Cli) -> Option<u64> {
+    match &args.secret {
+        Some(secret) if args.daily => {
+            let date = match &args.date {
+                Some(date) => date.clone(),
+                None => utc_date(std::time::SystemTime::now()),
+            };
+            Some(daily::seed(secret, &date))
+        }
+        _ => args.seed,
+    }
+}
+
+impl TryFrom<&Cli> for PasswordMaker {
+    type Error = String;
+
+    /// Create a password generator configured with the command line arguments
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - Command line arguments
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the candidates cannot be decoded
+    fn try_from(args: &Cli) -> Result<Self, Self::Error> {
+        let mut maker = match (&args.apple_rules, args.pin) {
+            (Some(rules), _) => {
+                PasswordMaker::from_apple_rules(rules).map_err(|e| e.to_string())?
+            }
+            (None, Some(length)) => PasswordMaker::pin(length),
+            (None, None) => {
+                let mut maker = match &args.pattern {
+                    Some(pattern) => PasswordMaker::from_pattern(pattern),
+                    None => PasswordMaker {
+                        length: args.length,
+                        ..PasswordMaker::default()
+                    },
+                };
+                set_classifiers(&mut maker, args)?;
+                maker
+            }
+        };
+        maker.length_limit = args.max_length_limit;
+        if let Some(pool_encoding) = &args.pool_encoding {
+            // Report an unknown encoding here rather than at the generation
+            encoding::name(pool_encoding)?;
+        }
+        maker.pool_encoding = args.pool_encoding.clone();
+        maker.strip_variation_selectors = args.strip_variation_selectors;
+        if let Some(exclude) = &args.exclude {
+            let excluded: Vec<String> = decode_candidates(exclude.as_encoded_bytes(), args)?
+                .0
+                .graphemes(true)
+                .map(|s| s.to_string())
+                .collect();
+            let classifiers = [
+                &mut maker.uppercase,
+                &mut maker.lowercase,
+                &mut maker.number,
+                &mut maker.symbol,
+            ];
+            for classifier in classifiers.into_iter().chain(maker.others.iter_mut()) {
+                if classifier.candidates.iter().all(|c| excluded.contains(c)) {
+                    classifier.minimum_count = 0;
+                }
+            }
+            maker.exclude_characters = excluded;
+        }
+        maker.exclude_similar = args.exclude_similar;
+        maker.exclude_similar_scope = match args.exclude_similar_scope {
+            ExcludeSimilarScope::AllClasses => SimilarScope::AllClasses,
+            ExcludeSimilarScope::StandardClassesOnly => SimilarScope::StandardClassesOnly,
+        };
+        maker.no_leading_zero = args.no_leading_zero;
+        maker.include_whitespace_in_candidate = args.include_whitespace;
+        maker.raw_sample = args.raw_sample;
+        maker.allow_control_characters = args.allow_control_chars;
+        maker.seed = seed(args);
+        // The pool is final only after all the filters are set
+        if let Some(bits) = args.min_entropy {
+            maker.length = maker
+                .length_for_entropy(bits as f64)
+                .map_err(|e| e.to_string())?;
+        }
+        // Fail before the generation if the maximum counts contradict the minimum counts or the length
+        let classifiers = [
+            &maker.uppercase,
+            &maker.lowercase,
+            &maker.number,
+            &maker.symbol,
+        ];
+        if classifiers
+            .into_iter()
+            .chain(&maker.others)
+            .any(|classifier| classifier.maximum_count.is_some())
+        {
+            maker.validate().map_err(|e| e.to_string())?;
+        }
+
+        Ok(maker)
+    }
+}
+
+/// Generate passwords
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments
+/// * `attempts` - Counter of the attempts, including rejected passwords
+///
+/// # Returns
+///
+/// List of passwords
+///
+/// # Errors
+///
+/// Returns an error if password generation fails
+fn generate_passwords(args: &Cli, attempts: &mut u64) -> Result<Vec<String>, String> {
+    let mut passwords: Vec<String> = Vec::new();
+    generate_passwords_with(args, attempts, &mut |password| {
+        passwords.push(password);
+        Ok(())
+    })?;
+    Ok(passwords)
+}
+
+/// Return the settings and the warnings about them to print before generating the passwords
+///
+/// They are printed once, also when "--watch" generates the passwords in every cycle.
+/// Passphrases and tokens do not use the settings of the characters, so they have none.
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments
+///
+/// # Returns
+///
+/// Lines to print to the standard error
+///
+/// # Errors
+///
+/// Returns an error if the settings are invalid
+fn setting_notices(args: &Cli) -> Result<Vec<String>, String> {
+    if args.passphrase || args.token.is_some() {
+        return Ok(vec![]);
+    }
+
+    let maker = PasswordMaker::try_from(args)?;
+    let mut notices = vec![];
+
+    if args.show_config {
+        notices.push(maker.to_string());
+    }
+
+    if args.check_homoglyphs {
+        notices.extend(homoglyph_warnings(&maker));
+    }
+
+    notices.extend(duplicate_warning(&maker));
+
+    if let Some(prefix) = &args.vanity_prefix {
+        let expected = expected_vanity_attempts(&maker, prefix)?;
+        if f64::from(args.max_attempts) < expected {
+            notices.push(format!(
+                "Warning: a password starts with \"{}\" about once in {:.0} attempts, but at most {} attempts are made. Long vanity prefixes are exponentially expensive.",
+                prefix, expected, args.max_attempts
+            ));
+        }
+    }
+
+    Ok(notices)
+}
+
+/// Generate passwords and pass each of them to a callback as soon as it is generated
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments
+/// * `attempts` - Counter of the attempts, including rejected passwords
+/// * `emit` - Callback that receives the passwords in order
+///
+/// # Returns
+///
+/// Returns an error message if an error occurs
+///
+/// # Errors
+///
+/// Returns an error if password generation or the callback fails
+fn generate_passwords_with(
+    args: &Cli,
+    attempts: &mut u64,
+    emit: &mut dyn FnMut(String) -> Result<(), String>,
+) -> Result<(), String> {
+    if args.passphrase {
+        return generate_passphrases(args, attempts, emit);
+    }
+    if let Some(format) = args.token {
+        return generate_tokens(format, args, attempts, emit);
+    }
+
+    let maker = PasswordMaker::try_from(args)?;
+
+    // Check that the prefix can start a password
+    if let Some(prefix) = &args.vanity_prefix {
+        expected_vanity_attempts(&maker, prefix)?;
+    }
+
+    if let Some(old) = &args.disjoint_from {
+        check_disjoint_feasible(&maker, old)?;
+    }
+
+    let denied = load_deny_wordlist(args)?;
+
+    let lengths = match args.total_chars {
+        Some(total_chars) => distribute_lengths(&maker, total_chars, args.count)?,
+        None => vec![maker.length; args.count as usize],
+    };
+
+    if let Some(threads) = args.parallel {
+        return generate_in_parallel(&maker, args, &denied, &lengths, threads, attempts, emit);
+    }
+
+    // Character types that have not appeared in the passwords yet
+    let mut uncovered: Vec<usize> = if args.batch_cover_classes {
+        (0..4 + maker.others.len())
+            .filter(|&index| !classifier(&maker, index).candidates.is_empty())
+            .collect()
+    } else {
+        vec![]
+    };
+
+    // Fail before generating if there are not enough passwords of each length
+    if args.unique {
+        let mut checked = std::collections::HashSet::new();
+        for &length in lengths.iter().filter(|&&length| checked.insert(length)) {
+            let same_length = lengths.iter().filter(|&&l| l == length).count();
+            let sized = PasswordMaker {
+                length,
+                ..maker.clone()
+            };
+            sized.validate().map_err(|e| e.to_string())?;
+            sized
+                .check_keyspace(same_length)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    let count = lengths.len();
+
+    // Generate the password at an index
+    let next = |uncovered: &[usize], index: usize, attempts: &mut u64, rng: &mut dyn RngCore| {
+        let mut nudged = PasswordMaker {
+            length: lengths[index],
+            ..maker.clone()
+        };
+
+        // Require the uncovered types little by little so that they are spread over the remaining passwords
+        let nudge_count = uncovered.len().div_ceil(count - index);
+        let mut implied = vec![];
+        for &class in uncovered.iter().take(nudge_count) {
+            let classifier = classifier_mut(&mut nudged, class);
+            if classifier.minimum_count == 0 {
+                classifier.minimum_count = 1;
+                implied.push(class_name(class));
+            }
+        }
+
+        generate_accepted(&mut nudged, args, &denied, attempts, rng).map_err(|e| {
+            if implied.is_empty() {
+                e
+            } else {
+                format!(
+                    "{} The minimum counts of {} were set to 1 by \"--batch-cover-classes\" to cover every type across the passwords.",
+                    e,
+                    implied.join(", ")
+                )
+            }
+        })
+    };
+
+    // Remove the types that appear in an accepted password
+    let cover = |uncovered: &mut Vec<usize>, password: &str| {
+        let graphemes: Vec<&str> = password.graphemes(true).collect();
+        uncovered.retain(|&class| {
+            let classifier = classifier(&maker, class);
+            !graphemes.iter().any(|g| classifier.contains(g))
+        });
+    };
+
+    if args.unique {
+        // A collided password is regenerated from the rest of the stream of its index, so that it does not repeat
+        let mut stream: Option<(usize, Box<dyn RngCore>)> = None;
+        let mut last: Option<String> = None;
+        let passwords = maker
+            .clone()
+            .generate_unique_with(count, args.max_attempts as usize, |_, index| {
+                if stream.as_ref().is_none_or(|(current, _)| *current != index) {
+                    // The index moves on only when the last password is accepted
+                    if let Some(password) = last.take() {
+                        cover(&mut uncovered, &password);
+                    }
+                    stream = Some((index, maker.create_rng_at(index as u64)));
+                }
+                let rng = &mut stream.as_mut().unwrap().1;
+                let password = next(&uncovered, index, attempts, rng)?;
+                last = Some(password.clone());
+                Ok::<_, Box<dyn std::error::Error>>(password)
+            })
+            .map_err(|e| e.to_string())?;
+
+        return passwords.into_iter().try_for_each(emit);
+    }
+
+    for index in 0..count {
+        // With "--seed", each password has its own stream so that "--parallel" generates the same passwords
+        let mut rng = maker.create_rng_at(index as u64);
+        let password = next(&uncovered, index, attempts, &mut rng)?;
+        cover(&mut uncovered, &password);
+
+        emit(password)?;
+    }
+
+    Ok(())
+}
+
+/// Generate passwords on several threads and pass each of them to a callback in order
+///
+/// The threads take the indexes in turn, and the password at each index is generated
+/// with the random number generator of its index, as in the serial generation.
+/// Each thread sends its passwords through a bounded channel, and the channels are read in turn,
+/// so each password is passed to the callback as soon as the passwords before it are,
+/// and a thread waits instead of running far ahead of the output.
+///
+/// # Arguments
+///
+/// * `maker` - Password generator
+/// * `args` - Command line arguments
+/// * `denied` - Words that must not appear in the passwords
+/// * `lengths` - Length of each password
+/// * `threads` - Number of threads
+/// * `attempts` - Counter of the attempts, including rejected passwords
+/// * `emit` - Callback that receives the passwords in order
+///
+/// # Returns
+///
+/// Returns an error message if an error occurs
+fn generate_in_parallel(
+    maker: &PasswordMaker,
+    args: &Cli,
+    denied: &[String],
+    lengths: &[u32],
+    threads: u32,
+    attempts: &mut u64,
+    emit: &mut dyn FnMut(String) -> Result<(), String>,
+) -> Result<(), String> {
+    // Number of passwords that each thread may generate ahead of the output
+    const BUFFER: usize = 64;
+
+    let threads = (threads as usize).clamp(1, lengths.len().max(1));
+    std::thread::scope(|scope| {
+        let receivers: Vec<_> = (0..threads)
+            .map(|number| {
+                let (sender, receiver) = std::sync::mpsc::sync_channel(BUFFER);
+                let mut maker = maker.clone();
+                scope.spawn(move || {
+                    for index in (number..lengths.len()).step_by(threads) {
+                        maker.length = lengths[index];
+                        let mut attempts = 0;
+                        let mut rng = maker.create_rng_at(index as u64);
+                        let password =
+                            generate_accepted(&mut maker, args, denied, &mut attempts, &mut rng);
+                        let failed = password.is_err();
+
+                        // Stop when the output has stopped, e.g. on an error
+                        if sender.send((password, attempts)).is_err() || failed {
+                            break;
+                        }
+                    }
+                });
+                receiver
+            })
+            .collect();
+
+        // Read the threads in turn so that the passwords are in index order
+        for index in 0..lengths.len() {
+            let (password, password_attempts) = receivers[index % threads].recv().unwrap();
+            *attempts += password_attempts;
+            emit(password?)?;
+        }
+        Ok(())
+    })
+}
+
+/// Generate passphrases and pass each of them to a callback as soon as it is generated
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments
+/// * `attempts` - Counter of the attempts
+/// * `emit` - Callback that receives the passphrases in order
+///
+/// # Returns
+///
+/// Returns an error message if an error occurs
+fn generate_passphrases(
+    args: &Cli,
+    attempts: &mut u64,
+    emit: &mut dyn FnMut(String) -> Result<(), String>,
+) -> Result<(), String> {
+    let words = match &args.wordlist {
+        Some(path) => load_wordlist(path)?,
+        None => vec![],
+    };
+    let passphrase = Passphrase {
+        words,
+        word_count: args.words,
+        separator: args.separator.clone(),
+        seed: seed(args),
+    };
+
+    let denied = load_deny_wordlist(args)?;
+
+    // Share one random number generator so that the passphrases differ from each other also with "--seed"
+    let mut rng = passphrase.create_rng();
+    for _ in 0..args.count {
+        let mut accepted = None;
+        for _ in 0..args.max_attempts {
+            *attempts += 1;
+            let generated = passphrase
+                .generate_with_rng(&mut rng)
+                .map_err(|e| e.to_string())?;
+            if !passphrase.contains_denied(&generated, &denied) {
+                accepted = Some(generated);
+                break;
+            }
+        }
+
+        emit(accepted.ok_or_else(|| {
+            format!(
+                "Could not generate a passphrase without the denied words in {} attempts. Please remove words from \"--deny-wordlist\", use a larger wordlist, or raise \"--max-attempts\".",
+                args.max_attempts
+            )
+        })?)?;
+    }
+
+    Ok(())
+}
+
+/// Generate random tokens and pass each of them to a callback as soon as it is generated
+///
+/// The tokens do not use the character types, so only the seed ("--seed" or "--daily") is taken from the settings.
+///
+/// # Arguments
+///
+/// * `format` - Format of the tokens
+/// * `args` - Command line arguments
+/// * `attempts` - Counter of the attempts
+/// * `emit` - Callback that receives the tokens in order
+///
+/// # Returns
+///
+/// Returns an error message if an error occurs
+fn generate_tokens(
+    format: TokenFormat,
+    args: &Cli,
+    attempts: &mut u64,
+    emit: &mut dyn FnMut(String) -> Result<(), String>,
+) -> Result<(), String> {
+    let maker = PasswordMaker {
+        seed: seed(args),
+        ..PasswordMaker::default()
+    };
+
+    // Share one random number generator so that the tokens differ from each other also with "--seed"
+    let mut rng = maker.create_rng();
+    for _ in 0..args.count {
+        *attempts += 1;
+        let token = match format {
+            TokenFormat::Base64 => maker.token_base64_with_rng(&mut rng, args.bytes as usize),
+            TokenFormat::Hex => maker.token_hex_with_rng(&mut rng, args.bytes as usize),
+        };
+        emit(token)?;
+    }
+
+    Ok(())
+}
+
+/// Read the words of passphrases from a file
+///
+/// # Arguments
+///
+/// * `path` - Path of the wordlist, with one word per line
+///
+/// # Returns
+///
+/// List of words
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read
+fn load_wordlist(path: &std::path::Path) -> Result<Vec<String>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read the wordlist {}: {}", path.display(), e))?;
+
+    Ok(text
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let first = fields.next()?;
+            // Skip the dice numbers of diceware lists
+            match fields.next() {
+                Some(word) if first.chars().all(|c| c.is_ascii_digit()) => Some(word),
+                _ => Some(line.trim()),
+            }
+        })
+        .map(str::to_string)
+        .collect())
+}
+
+/// Read the denied words of "--deny-wordlist"
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments
+///
+/// # Returns
+///
+/// List of denied words, which is empty without "--deny-wordlist"
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read
+fn load_deny_wordlist(args: &Cli) -> Result<Vec<String>, String> {
+    let Some(path) = &args.deny_wordlist else {
+        return Ok(vec![]);
+    };
+
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read the deny wordlist {}: {}", path.display(), e))?;
+
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Generate a password that is not rejected by the command line options
+///
+/// # Arguments
+///
+/// * `maker` - Password generator
+/// * `args` - Command line arguments
+/// * `denied` - Denied words of "--deny-wordlist"
+/// * `attempts` - Counter of the attempts, including rejected passwords
+/// * `rng` - Random number generator
+///
+/// # Returns
+///
+/// Password
+///
+/// # Errors
+///
+/// Returns an error if password generation fails or all attempts are rejected
+fn generate_accepted(
+    maker: &mut PasswordMaker,
+    args: &Cli,
+    denied: &[String],
+    attempts: &mut u64,
+    rng: &mut dyn RngCore,
+) -> Result<String, String> {
+    let mut accepted = None;
+
+    for _ in 0..args.max_attempts {
+        let password = maker.generate_with_rng(rng).map_err(|e| e.to_string())?;
+        *attempts += 1;
+
+        // Run every check, even after one fails or a password is accepted,
+        // so that each attempt of "--constant-time-ish" takes about the same time
+        let dated = args.reject_dates && pattern::contains_date(&password);
+        let unprefixed = args
+            .vanity_prefix
+            .as_ref()
+            .is_some_and(|prefix| !password.starts_with(prefix.as_str()));
+        let shared = args.disjoint_from.as_ref().is_some_and(|old| {
+            password
+                .graphemes(true)
+                .any(|g| old.graphemes(true).any(|o| o == g))
+        });
+        let forbidden = maker.contains_denied(&password, denied);
+
+        if dated | unprefixed | shared | forbidden || accepted.is_some() {
+            continue;
+        }
+
+        if !args.constant_time_ish {
+            return Ok(password);
+        }
+        accepted = Some(password);
+    }
+
+    accepted.ok_or_else(|| {
+        format!(
+            "Could not generate a password that is not rejected in {} attempts. Please relax the rejection options, add more candidates, or raise \"--max-attempts\".",
+            args.max_attempts
+        )
+    })
+}
+
+/// Check that passwords sharing no characters with the old password can be generated
+///
+/// # Arguments
+///
+/// * `maker` - Password generator
+/// * `old` - Old password
+///
+/// # Returns
+///
+/// Returns an error message if the characters of the old password leave no candidates for the pool
+/// or for a character type with a minimum count
+fn check_disjoint_feasible(maker: &PasswordMaker, old: &str) -> Result<(), String> {
+    let old: std::collections::HashSet<&str> = old.graphemes(true).collect();
+    let remains = |candidates: &[String]| candidates.iter().any(|c| !old.contains(c.as_str()));
+
+    if !remains(&maker.effective_pool()) {
+        return Err("The characters of the old password cover all the candidates, so no password can share no characters with it. Please add more candidates.".to_string());
+    }
+
+    let required = [
+        &maker.uppercase,
+        &maker.lowercase,
+        &maker.number,
+        &maker.symbol,
+    ]
+    .into_iter()
+    .chain(&maker.others)
+    .filter(|classifier| 0 < classifier.minimum_count);
+    for classifier in required {
+        if !remains(&classifier.candidates) {
+            return Err(format!(
+                "The characters of the old password cover all the candidates \"{}\", so their minimum count cannot be met. Please add more candidates or set the minimum count to 0.",
+                classifier.candidates.concat()
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Return warnings about the candidates that look alike
+///
+/// # Arguments
+///
+/// * `maker` - Password generator
+///
+/// # Returns
+///
+/// Warning for each pair of look-alike candidates
+fn homoglyph_warnings(maker: &PasswordMaker) -> Vec<String> {
+    let code_points = |grapheme: &str| {
+        grapheme
+            .chars()
+            .map(|c| format!("U+{:04X}", c as u32))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    maker
+        .homoglyph_conflicts()
+        .into_iter()
+        .map(|(first, second)| {
+            format!(
+                "Warning: \"{}\" ({}) and \"{}\" ({}) look alike",
+                first,
+                code_points(&first),
+                second,
+                code_points(&second)
+            )
+        })
+        .collect()
+}
+
+/// Return a warning about the candidates of more than one character type
+///
+/// # Arguments
+///
+/// * `maker` - Password generator
+///
+/// # Returns
+///
+/// * Some: Warning listing the duplicate candidates
+/// * None: No candidates are duplicated
+fn duplicate_warning(maker: &PasswordMaker) -> Option<String> {
+    let duplicates = maker.duplicate_candidates();
+    if duplicates.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "Warning: {} are candidates of more than one character type. Each of them is used as one candidate.",
+        duplicates
+            .iter()
+            .map(|c| format!("\"{}\"", c))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
+
+/// Return the expected number of attempts until a password starts with the vanity prefix
+///
+/// Each character of the prefix is drawn from the candidates with the probability of its share in them.
+///
+/// # Arguments
+///
+/// * `maker` - Password generator
+/// * `prefix` - Vanity prefix
+///
+/// # Returns
+///
+/// Expected number of attempts
+///
+/// # Errors
+///
+/// Returns an error if the prefix is longer than the password or contains a character that is not a candidate
+fn expected_vanity_attempts(maker: &PasswordMaker, prefix: &str) -> Result<f64, String> {
+    let graphemes: Vec<&str> = prefix.graphemes(true).collect();
+    if maker.length < graphemes.len() as u32 {
+        return Err(format!(
+            "The vanity prefix \"{}\" is longer than the password length {}.",
+            prefix, maker.length
+        ));
+    }
+
+    let candidates = maker.candidates();
+    let mut expected = 1.0;
+    for grapheme in graphemes {
+        let occurrences = candidates.iter().filter(|c| *c == grapheme).count();
+        if occurrences == 0 {
+            return Err(format!(
+                "\"{}\" in the vanity prefix is not a candidate. Please use only candidate characters in the vanity prefix.",
+                grapheme
+            ));
+        }
+        expected *= candidates.len() as f64 / occurrences as f64;
+    }
+
+    Ok(expected)
+}
+
+/// Return the settings of a character type by index
+///
+/// The indexes are 0: uppercases, 1: lowercases, 2: numbers, 3: symbols, and 4 or more: other characters
+///
+/// # Arguments
+///
+/// * `maker` - Password generator
+/// * `index` - Index of the character type
+///
+/// # Returns
+///
+/// Settings of the character type
+fn classifier(maker: &PasswordMaker, index: usize) -> &Classifier {
+    match index {
+        0 => &maker.uppercase,
+        1 => &maker.lowercase,
+        2 => &maker.number,
+        3 => &maker.symbol,
+        _ => &maker.others[index - 4],
+    }
+}
+
+/// Return the settings of a character type by index for changing them
+///
+/// The indexes are the same as [`classifier`].
+///
+/// # Arguments
+///
+/// * `maker` - Password generator
+/// * `index` - Index of the character type
+///
+/// # Returns
+///
+/// Settings of the character type
+fn classifier_mut(maker: &mut PasswordMaker, index: usize) -> &mut Classifier {
+    match index {
+        0 => &mut maker.uppercase,
+        1 => &mut maker.lowercase,
+        2 => &mut maker.number,
+        3 => &mut maker.symbol,
+        _ => &mut maker.others[index - 4],
+    }
+}
+
+/// Return the name of a character type by index for messages
+///
+/// The indexes are the same as [`classifier`].
+///
+/// # Arguments
+///
+/// * `index` - Index of the character type
+///
+/// # Returns
+///
+/// Name of the character type (e.g. "Uppercases", "Other characters at index 0")
+fn class_name(index: usize) -> String {
+    match index {
+        0 => "Uppercases".to_string(),
+        1 => "Lowercases".to_string(),
+        2 => "Numbers".to_string(),
+        3 => "Symbols".to_string(),
+        _ => format!("Other characters at index {}", index - 4),
+    }
+}
+
+/// Distribute the total number of characters evenly over the passwords
+///
+/// If the total number of characters is not divisible by the number of passwords,
+/// the first passwords are one character longer.
+///
+/// # Arguments
+///
+/// * `maker` - Password generator
+/// * `total_chars` - Total number of characters of all passwords
+/// * `count` - Number of passwords
+///
+/// # Returns
+///
+/// Length of each password
+///
+/// # Errors
+///
+/// Returns an error if a password would be shorter than the total minimum number of characters
+fn distribute_lengths(
+    maker: &PasswordMaker,
+    total_chars: u32,
+    count: u32,
+) -> Result<Vec<u32>, String> {
+    if count == 0 {
+        return Ok(vec![]);
+    }
+
+    let base = total_chars / count;
+    let remainder = total_chars % count;
+
+    let minimum = maker
+        .total_minimum_count()
+        .ok_or_else(|| PasswordError::MinimumCountOverflow.to_string())?
+        .max(1);
+    if base < minimum {
+        return Err(format!(
+            "The total number of characters {} is too small to generate {} passwords. Each password needs at least {} characters, so at least {} characters are required.",
+            total_chars,
+            count,
+            minimum,
+            minimum as u64 * count as u64
+        ));
+    }
+
+    Ok((0..count)
+        .map(|index| if index < remainder { base + 1 } else { base })
+        .collect())
+}
+
+/// Format passwords
+///
+/// If null_separator is true, separate with null characters; otherwise, separate with newline characters (\n)
+///
+/// # Arguments
+///
+/// * `passwords` - List of passwords
+/// * `null_separator` - Whether to separate with null characters
+/// * `trailing_separator` - Whether to put the separator after the last password
+///
+/// # Returns
+///
+/// Formatted passwords
+fn format_passwords(
+    passwords: Vec<String>,
+    null_separator: bool,
+    trailing_separator: bool,
+) -> String {
+    let separater = match null_separator {
+        true => "\0",
+        false => "\n",
+    };
+
+    match trailing_separator {
+        true => passwords.join(separater) + separater,
+        false => passwords.join(separater),
+    }
+}
+
+/// Version of the structure of the JSON output
+///
+/// Bumped when the structure changes, so that tools consuming the output can tell which structure they read.
+const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// Return the beginning of the JSON output up to the opening bracket of the passwords
+///
+/// The schema version comes first, so that tools can check it before reading the passwords.
+fn json_output_start() -> String {
+    format!(
+        "{{\"schema_version\":{},\"passwords\":[",
+        JSON_SCHEMA_VERSION
+    )
+}
+
+/// End of the JSON output after the last password
+const JSON_OUTPUT_END: &str = "]}\n";
+
+/// Format passwords as a JSON object of the schema version and the array of the passwords
+///
+/// # Arguments
+///
+/// * `passwords` - List of passwords
+/// * `maker` - Password generator used to generate the passwords, to add the length and entropy of each password
+///
+/// # Returns
+///
+/// JSON object followed by a newline character
+fn format_passwords_json(passwords: &[String], maker: Option<&PasswordMaker>) -> String {
+    let elements: Vec<String> = passwords
+        .iter()
+        .map(|password| format_password_json(password, maker))
+        .collect();
+
+    json_output_start() + &elements.join(",") + JSON_OUTPUT_END
+}
+
+/// Format a password as an element of the JSON array
+///
+/// # Arguments
+///
+/// * `password` - Password
+/// * `maker` - Password generator used to generate the password, to add the length and entropy of the password
+///
+/// # Returns
+///
+/// JSON string of the password, or JSON object of the password, its length, and its entropy if `maker` is given
+fn format_password_json(password: &str, maker: Option<&PasswordMaker>) -> String {
+    let json = match maker {
+        Some(maker) => {
+            let length = password.graphemes(true).count();
+            let sized = PasswordMaker {
+                length: length as u32,
+                ..maker.clone()
+            };
+            serde_json::json!({
+                "password": password,
+                "length": length,
+                "entropy": sized.entropy(),
+            })
+        }
+        None => serde_json::json!(password),
+    };
+
+    json.to_string()
+}
+
+/// Quote a string for POSIX shells
+///
+/// The string is enclosed in single quotes, and single quotes in the string are replaced with '\''
+///
+/// # Arguments
+///
+/// * `text` - String to quote
+///
+/// # Returns
+///
+/// Quoted string
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/// Quote a possibly non-UTF-8 string for POSIX shells
+///
+/// Strings that are not valid UTF-8 are quoted as $'...' with each byte escaped,
+/// which bash and zsh understand.
+///
+/// # Arguments
+///
+/// * `text` - String to quote
+///
+/// # Returns
+///
+/// Quoted string
+fn shell_quote_os(text: &std::ffi::OsStr) -> String {
+    if let Some(text) = text.to_str() {
+        return shell_quote(text);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        let escaped: String = text
+            .as_bytes()
+            .iter()
+            .map(|byte| format!("\\x{:02x}", byte))
+            .collect();
+        format!("$'{}'", escaped)
+    }
+    #[cfg(not(unix))]
+    {
+        shell_quote(&text.to_string_lossy())
+    }
+}
+
+/// Return the command line that reproduces the settings
+///
+/// Options that conflict with the given options are left out even if `verbose` is set,
+/// so that the command line is always accepted.
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments, after merging the config file
+/// * `verbose` - Whether to include the options left at their defaults
+///
+/// # Returns
+///
+/// Command line quoted for POSIX shells
+fn command_line(args: &Cli, verbose: bool) -> String {
+    let default = Cli::default();
+    let show = |changed: bool| verbose || changed;
+    let without_rules = args.apple_rules.is_none() && args.pin.is_none();
+
+    let mut words = vec![env!("CARGO_PKG_NAME").to_string()];
+    // Join the values with "=" so that values starting with "-" are not taken as options
+    let mut push = |name: &str, value: Option<String>| match value {
+        Some(value) => words.push(format!("--{}={}", name, value)),
+        None => words.push(format!("--{}", name)),
+    };
+
+    if without_rules
+        && args.total_chars.is_none()
+        && args.pattern.is_none()
+        && args.min_entropy.is_none()
+        && show(args.length != default.length)
+    {
+        push("length", Some(args.length.to_string()));
+    }
+    if show(args.max_length_limit != default.max_length_limit) {
+        push("max-length-limit", Some(args.max_length_limit.to_string()));
+    }
+    if show(args.count != default.count) {
+        push("count", Some(args.count.to_string()));
+    }
+    if let Some(total_chars) = args.total_chars {
+        push("total-chars", Some(total_chars.to_string()));
+    }
+    if let Some(bits) = args.min_entropy {
+        push("min-entropy", Some(bits.to_string()));
+    }
+
+    if without_rules {
+        // The converted candidates are derived from the others, and giving them would disable the conversion
+        let classes = [
+            (
+                "uppercase",
+                &args.uppercase_candidates,
+                &default.uppercase_candidates,
+                args.uppercase_from_lowercase,
+                args.uppercase_minimum_count,
+                default.uppercase_minimum_count,
+            ),
+            (
+                "lowercase",
+                &args.lowercase_candidates,
+                &default.lowercase_candidates,
+                args.lowercase_from_uppercase,
+                args.lowercase_minimum_count,
+                default.lowercase_minimum_count,
+            ),
+            (
+                "number",
+                &args.number_candidates,
+                &default.number_candidates,
+                false,
+                args.number_minimum_count,
+                default.number_minimum_count,
+            ),
+            (
+                "symbol",
+                &args.symbol_candidates,
+                &default.symbol_candidates,
+                false,
+                args.symbol_minimum_count,
+                default.symbol_minimum_count,
+            ),
+        ];
+        for (name, candidates, default_candidates, converted, minimum, default_minimum) in classes {
+            if !converted && show(candidates != default_candidates) {
+                push(
+                    &format!("{}-candidates", name),
+                    Some(shell_quote_os(candidates)),
+                );
+            }
+            // The pattern ignores the minimum counts and cannot be combined with them
+            if args.pattern.is_none() && show(minimum != default_minimum) {
+                push(
+                    &format!("{}-minimum-count", name),
+                    Some(minimum.to_string()),
+                );
+            }
+        }
+        for candidates in args.other_candidates.iter().flatten() {
+            push("other-candidates", Some(shell_quote_os(candidates)));
+        }
+        for minimum in args.other_minimum_count.iter().flatten() {
+            push("other-minimum-count", Some(minimum.to_string()));
+        }
+        let maximums = [
+            ("uppercase", args.uppercase_maximum_count),
+            ("lowercase", args.lowercase_maximum_count),
+            ("number", args.number_maximum_count),
+            ("symbol", args.symbol_maximum_count),
+        ];
+        for (name, maximum) in maximums {
+            if let Some(maximum) = maximum {
+                push(
+                    &format!("{}-maximum-count", name),
+                    Some(maximum.to_string()),
+                );
+            }
+        }
+        for maximum in args.other_maximum_count.iter().flatten() {
+            push("other-maximum-count", Some(maximum.to_string()));
+        }
+    }
+    if args.passphrase {
+        push("passphrase", None);
+        if show(args.words != default.words) {
+            push("words", Some(args.words.to_string()));
+        }
+        if show(args.separator != default.separator) {
+            push("separator", Some(shell_quote(&args.separator)));
+        }
+    }
+    if let Some(format) = args.token {
+        push(
+            "token",
+            Some(format.to_possible_value().unwrap().get_name().to_string()),
+        );
+        if show(args.bytes != default.bytes) {
+            push("bytes", Some(args.bytes.to_string()));
+        }
+    }
+    if let Some(path) = &args.wordlist {
+        push("wordlist", Some(shell_quote_os(path.as_os_str())));
+    }
+    if args.uppercase_from_lowercase {
+        push("uppercase-from-lowercase", None);
+    }
+    if args.lowercase_from_uppercase {
+        push("lowercase-from-uppercase", None);
+    }
+    if let Some(rules) = &args.apple_rules {
+        push("apple-rules", Some(shell_quote(rules)));
+    }
+    if let Some(length) = args.pin {
+        push("pin", Some(length.to_string()));
+    }
+    if let Some(pattern) = &args.pattern {
+        push("pattern", Some(shell_quote(pattern)));
+    }
+    if let Some(exclude) = &args.exclude {
+        push("exclude", Some(shell_quote_os(exclude)));
+    }
+
+    let flags = [
+        ("exclude-similar", args.exclude_similar),
+        ("no-leading-zero", args.no_leading_zero),
+        ("include-whitespace", args.include_whitespace),
+        ("raw-sample", args.raw_sample),
+        ("allow-control-chars", args.allow_control_chars),
+        ("batch-cover-classes", args.batch_cover_classes),
+        ("reject-dates", args.reject_dates),
+        ("constant-time-ish", args.constant_time_ish),
+        ("unique", args.unique),
+        ("check-homoglyphs", args.check_homoglyphs),
+        ("summary", args.summary),
+        ("show-stats", args.show_stats),
+        ("show-config", args.show_config),
+        ("null", args.null),
+        ("no-trailing-separator", args.no_trailing_separator),
+        ("verbose", args.verbose),
+        ("numbered", args.numbered),
+        ("phonetic", args.phonetic),
+        ("mnemonic", args.mnemonic),
+        ("annotate", args.annotate),
+        ("mask", args.mask),
+        ("clipboard", args.clipboard),
+        ("clipboard-sensitive", args.clipboard_sensitive),
+        ("tee", args.tee),
+        ("qr", args.qr),
+        ("strip-variation-selectors", args.strip_variation_selectors),
+        ("show-encoding", args.show_encoding),
+    ];
+    for (name, _) in flags.iter().filter(|(_, enabled)| *enabled) {
+        push(name, None);
+    }
+
+    if args.clipboard && show(args.clipboard_selection != default.clipboard_selection) {
+        let selection = args.clipboard_selection.to_possible_value().unwrap();
+        push(
+            "clipboard-selection",
+            Some(selection.get_name().to_string()),
+        );
+    }
+    if let Some(seconds) = args.watch {
+        push("watch", Some(seconds.to_string()));
+    }
+    if show(args.exclude_similar_scope != default.exclude_similar_scope) {
+        let scope = args.exclude_similar_scope.to_possible_value().unwrap();
+        push("exclude-similar-scope", Some(scope.get_name().to_string()));
+    }
+    if args.mask && show(args.mask_reveal != default.mask_reveal) {
+        push("mask-reveal", Some(args.mask_reveal.to_string()));
+    }
+    if let Some(seed) = args.seed {
+        push("seed", Some(seed.to_string()));
+    }
+    if args.daily {
+        push("daily", None);
+    }
+    if let Some(secret) = &args.secret {
+        push("secret", Some(shell_quote(secret)));
+    }
+    if let Some(date) = &args.date {
+        push("date", Some(date.clone()));
+    }
+    if let Some(threads) = args.parallel {
+        push("parallel", Some(threads.to_string()));
+    }
+    if let Some(prefix) = &args.vanity_prefix {
+        push("vanity-prefix", Some(shell_quote(prefix)));
+    }
+    if let Some(old) = &args.disjoint_from {
+        push("disjoint-from", Some(shell_quote(old)));
+    }
+    if let Some(path) = &args.deny_wordlist {
+        push("deny-wordlist", Some(shell_quote_os(path.as_os_str())));
+    }
+    if show(args.max_attempts != default.max_attempts) {
+        push("max-attempts", Some(args.max_attempts.to_string()));
+    }
+    if !args.null && args.env_var.is_none() && show(args.format != default.format) {
+        let format = args.format.to_possible_value().unwrap();
+        push("format", Some(format.get_name().to_string()));
+    }
+    if args.numbered && show(args.number_format != default.number_format) {
+        push("number-format", Some(shell_quote(&args.number_format)));
+    }
+    if let Some(size) = args.group_size {
+        push("group-size", Some(size.to_string()));
+        if show(args.group_separator != default.group_separator) {
+            push("group-separator", Some(shell_quote(&args.group_separator)));
+        }
+    }
+    if let Some(name) = &args.env_var {
+        push("env-var", Some(shell_quote(name)));
+    }
+    if let Some(path) = &args.output {
+        push("output", Some(shell_quote_os(path.as_os_str())));
+    }
+    if show(args.encoding != default.encoding) {
+        push("encoding", Some(shell_quote(&args.encoding)));
+    }
+    if let Some(encodings) = &args.encoding_fallback {
+        push("encoding-fallback", Some(shell_quote(&encodings.join(","))));
+    }
+    if let Some(name) = &args.pool_encoding {
+        push("pool-encoding", Some(shell_quote(name)));
+    }
+    if let Some(class) = args.candidates_stdin {
+        let class = class.to_possible_value().unwrap();
+        push("candidates-stdin", Some(class.get_name().to_string()));
+    }
+    if show(args.errors_to != default.errors_to) {
+        let stream = args.errors_to.to_possible_value().unwrap();
+        push("errors-to", Some(stream.get_name().to_string()));
+    }
+
+    words.join(" ")
+}
+
+/// Check that the separator of "--group-size" survives the output encoding
+///
+/// # Arguments
+///
+/// * `separator` - Separator of the groups
+/// * `encoding` - Output encoding
+///
+/// # Returns
+///
+/// Returns an error message if the separator cannot be represented in the encoding
+fn validate_group_separator(separator: &str, encoding: &str) -> Result<(), String> {
+    if !encoding::is_encodable(separator, encoding)? {
+        return Err(format!(
+            "The group separator \"{}\" cannot be represented in {}. Please use another separator.",
+            separator, encoding
+        ));
+    }
+    Ok(())
+}
+
+/// Insert the separator of "--group-size" into a password
+///
+/// # Arguments
+///
+/// * `password` - Password
+/// * `args` - Command line arguments
+///
+/// # Returns
+///
+/// Grouped password, or the password as it is without "--group-size"
+fn group_password(password: String, args: &Cli) -> String {
+    match args.group_size {
+        Some(size) => password_maker::group(&password, size as usize, &args.group_separator),
+        None => password,
+    }
+}
+
+/// Prefix passwords with their numbers
+///
+/// # Arguments
+///
+/// * `passwords` - List of passwords
+/// * `format` - Prefix, in which "{n}" is replaced with the number starting from 1
+///
+/// # Returns
+///
+/// List of numbered passwords
+///
+/// # Errors
+///
+/// Returns an error if the prefix does not contain "{n}"
+fn number_passwords(passwords: Vec<String>, format: &str) -> Result<Vec<String>, String> {
+    validate_number_format(format)?;
+
+    Ok(passwords
+        .into_iter()
+        .enumerate()
+        .map(|(index, password)| number_password(&password, format, index))
+        .collect())
+}
+
+/// Check that the prefix of "--numbered" contains "{n}"
+///
+/// # Arguments
+///
+/// * `format` - Prefix, in which "{n}" is replaced with the number starting from 1
+///
+/// # Returns
+///
+/// Returns an error message if the prefix does not contain "{n}"
+fn validate_number_format(format: &str) -> Result<(), String> {
+    if !format.contains("{n}") {
+        return Err(format!(
+            "Invalid number format: {}. Include \"{{n}}\" where the number goes.",
+            format
+        ));
+    }
+    Ok(())
+}
+
+/// Prefix a password with its number
+///
+/// # Arguments
+///
+/// * `password` - Password
+/// * `format` - Prefix, in which "{n}" is replaced with the number
+/// * `index` - Index of the password, starting from 0
+///
+/// # Returns
+///
+/// Numbered password
+fn number_password(password: &str, format: &str, index: usize) -> String {
+    format.replace("{n}", &(index + 1).to_string()) + password
+}
+
+/// Convert passwords into environment variable assignments
+///
+/// If there is only one password, the variable is named `name`; otherwise, `name_1`, `name_2`, ...
+///
+/// # Arguments
+///
+/// * `passwords` - List of passwords
+/// * `name` - Name of the environment variable
+///
+/// # Returns
+///
+/// List of assignments
+///
+/// # Errors
+///
+/// Returns an error if the name is not a valid environment variable name
+fn env_var_assignments(passwords: Vec<String>, name: &str) -> Result<Vec<String>, String> {
+    validate_env_var_name(name)?;
+
+    let count = passwords.len();
+    Ok(passwords
+        .iter()
+        .enumerate()
+        .map(|(index, password)| env_var_assignment(password, name, index, count))
+        .collect())
+}
+
+/// Check that the name is a valid environment variable name
+///
+/// # Arguments
+///
+/// * `name` - Name of the environment variable
+///
+/// # Returns
+///
+/// Returns an error message if the name is not valid
+fn validate_env_var_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!(
+            "Invalid environment variable name: {}. Use only ASCII letters, digits, and underscores, and do not start with a digit.",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Convert a password into an environment variable assignment
+///
+/// # Arguments
+///
+/// * `password` - Password
+/// * `name` - Name of the environment variable
+/// * `index` - Index of the password, starting from 0
+/// * `count` - Number of passwords, to number the variables only if there are multiple passwords
+///
+/// # Returns
+///
+/// Assignment
+fn env_var_assignment(password: &str, name: &str, index: usize, count: usize) -> String {
+    if count == 1 {
+        format!("export {}={}", name, shell_quote(password))
+    } else {
+        format!("export {}_{}={}", name, index + 1, shell_quote(password))
+    }
+}
+
+/// Write text to a file that only the owner can access
+///
+/// # Arguments
+///
+/// * `text` - Text to write
+/// * `path` - Path of the file
+///
+/// # Returns
+///
+/// Returns an error message if an error occurs
+fn write_to_file(text: &[u8], path: &std::path::Path) -> Result<(), String> {
+    let error = |e: io::Error| format!("Failed to write to {}: {}", path.display(), e);
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+
+        // The mode applies only to new files, so also restrict an existing file before writing the secrets
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+                .map_err(error)?;
+        }
+    }
+
+    let mut file = options.open(path).map_err(error)?;
+    file.write_all(text).map_err(error)?;
+    Ok(())
+}
+
+/// Output passwords
+///
+/// Write to a file and copy to clipboard if specified, otherwise output to standard output.
+/// With "--tee", also output to standard output.
+/// All the destinations are tried even if some of them fail.
+///
+/// # Arguments
+///
+/// * `text` - Text to output
+/// * `args` - Command line arguments
+///
+/// # Returns
+///
+/// Returns an error message for each failed destination if an error occurs
+fn output_passwords(text: &str, args: &Cli) -> Result<(), String> {
+    let mut errors = vec![];
+
+    if let Some(path) = &args.output {
+        if let Err(e) =
+            encode_strict(text, &args.encoding).and_then(|encoded| write_to_file(&encoded, path))
+        {
+            errors.push(e);
+        }
+    }
+
+    if args.clipboard {
+        if let Err(e) = write_to_clipboard(text, args.clipboard_sensitive, args.clipboard_selection)
+        {
+            errors.push(format!("Failed to copy to the clipboard: {}", e));
+        }
+    }
+
+    if args.tee || (!args.clipboard && args.output.is_none()) {
+        let result = encode_strict(text, &args.encoding).and_then(|encoded| {
+            let stdout = io::stdout();
+            let mut handle = stdout.lock();
+            handle
+                .write_all(&encoded)
+                .map_err(|e| format!("Failed to write to standard output: {}", e))
+        });
+        if let Err(e) = result {
+            errors.push(e);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// Summarize the generated passwords
+///
+/// # Arguments
+///
+/// * `passwords` - List of passwords
+/// * `maker` - Password generator used to generate the passwords
+///
+/// # Returns
+///
+/// Summary text
+fn summarize(passwords: &[String], maker: &PasswordMaker) -> String {
+    let lengths: Vec<usize> = passwords
+        .iter()
+        .map(|p| p.graphemes(true).count())
+        .collect();
+    let min_length = lengths.iter().copied().min().unwrap_or(0);
+    let max_length = lengths.iter().copied().max().unwrap_or(0);
+
+    // Duplicate candidates do not add to the strength, and the literals of a pattern have none
+    let pool_size = maker.pool_size();
+    let bits = |length: usize| {
+        PasswordMaker {
+            length: length as u32,
+            ..maker.clone()
+        }
+        .entropy()
+    };
+
+    let range = |min: String, max: String| {
+        if min == max {
+            min
+        } else {
+            format!("{}-{}", min, max)
+        }
+    };
+
+    let mut summary = vec![
+        format!("Passwords: {}", passwords.len()),
+        format!(
+            "Length: {}",
+            range(min_length.to_string(), max_length.to_string())
+        ),
+        format!("Pool size: {}", pool_size),
+        format!(
+            "Entropy: {} bits per password",
+            range(
+                format!("{:.1}", bits(min_length)),
+                format!("{:.1}", bits(max_length))
+            )
+        ),
+    ];
+
+    if maker.raw_sample {
+        summary.push("Warning: the minimum counts are not enforced (--raw-sample)".to_string());
+    }
+
+    summary.join("\n")
+}
+
+/// Decode a candidate string with the encoding given on the command line
+///
+/// # Arguments
+///
+/// * `candidates` - Candidate string
+/// * `args` - Command line arguments
+///
+/// # Returns
+///
+/// The decoded string and the name of the encoding used
+///
+/// # Errors
+///
+/// Returns an error if the encoding is not supported, or no fallback encoding decodes the string
+fn decode_candidates(candidates: &[u8], args: &Cli) -> Result<(String, &'static str), String> {
+    match &args.encoding_fallback {
+        Some(encodings) => encoding::decode_with_fallback(candidates, encodings),
+        None => Ok((
+            encoding::decode(candidates, &args.encoding)?,
+            encoding::name(&args.encoding)?,
+        )),
+    }
+}
+
+/// Return the encoding used to decode each candidate string
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments
+///
+/// # Returns
+///
+/// Option name and encoding name of each non-empty candidate string
+///
+/// # Errors
+///
+/// Returns an error if a candidate string cannot be decoded
+fn candidate_encodings(args: &Cli) -> Result<Vec<(String, &'static str)>, String> {
+    let mut options = vec![
+        (
+            "--uppercase-candidates".to_string(),
+            &args.uppercase_candidates,
+        ),
+        (
+            "--lowercase-candidates".to_string(),
+            &args.lowercase_candidates,
+        ),
+        ("--number-candidates".to_string(), &args.number_candidates),
+        ("--symbol-candidates".to_string(), &args.symbol_candidates),
+    ];
+    for (index, candidates) in args.other_candidates.iter().flatten().enumerate() {
+        options.push((format!("--other-candidates #{}", index + 1), candidates));
+    }
+
+    options
+        .into_iter()
+        .filter(|(_, candidates)| !candidates.is_empty())
+        .map(|(option, candidates)| {
+            decode_candidates(candidates.as_encoded_bytes(), args).map(|(_, name)| (option, name))
+        })
+        .collect()
+}
+
+/// Print the spellings of the password requested by the command line options to standard error
+///
+/// # Arguments
+///
+/// * `password` - Password
+/// * `args` - Command line arguments
+/// * `maker` - Password generator used to generate the password, required for "--annotate"
+fn print_spellings(password: &str, args: &Cli, maker: Option<&PasswordMaker>) {
+    if args.phonetic {
+        eprintln!("{}", phonetic::spell(password));
+    }
+
+    if args.mnemonic {
+        eprintln!("{}", phonetic::mnemonic(password));
+    }
+
+    if let Some(maker) = maker.filter(|_| args.annotate) {
+        eprintln!("{}\n{}", password, annotate::markers(password, maker));
+    }
+
+    if args.mask {
+        eprintln!("{}", annotate::mask(password, args.mask_reveal));
+    }
+}
+
+/// Generate the passwords and write each of them to standard output as soon as it is generated
+///
+/// Only the current password is kept in memory, so any number of passwords can be generated.
+/// If an error occurs midway, the passwords generated before it have already been written.
+///
+/// In JSON, the schema version and the opening bracket are written with the first password,
+/// each following password after a comma, and the closing brackets after the last password,
+/// so the output is the same as formatting all the passwords at once.
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments
+/// * `attempts` - Counter of the attempts, including rejected passwords
+/// * `maker` - Password generator describing the passwords, required for "--annotate", "--summary", and "--verbose"
+///
+/// # Returns
+///
+/// Returns an error message if an error occurs
+fn stream_passwords(
+    args: &Cli,
+    attempts: &mut u64,
+    maker: Option<&PasswordMaker>,
+) -> Result<(), String> {
+    if let Some(name) = &args.env_var {
+        validate_env_var_name(name)?;
+    }
+    if args.numbered {
+        validate_number_format(&args.number_format)?;
+    }
+    if args.group_size.is_some() {
+        validate_group_separator(&args.group_separator, &args.encoding)?;
+    }
+
+    let json = args.format == OutputFormat::Json;
+    let separator = match args.null {
+        true => "\0",
+        false => "\n",
+    };
+    let count = args.count as usize;
+
+    let mut handle = io::BufWriter::new(io::stdout().lock());
+    let mut index = 0;
+    generate_passwords_with(args, attempts, &mut |password| {
+        print_spellings(&password, args, maker);
+
+        if json {
+            let element = format_password_json(&password, maker.filter(|_| args.verbose));
+            let delimiter = if index == 0 {
+                json_output_start()
+            } else {
+                ",".to_string()
+            };
+            index += 1;
+
+            return handle
+                .write_all(&encode_strict(&(delimiter + &element), &args.encoding)?)
+                .map_err(|e| e.to_string());
+        }
+
+        let password = group_password(password, args);
+        let mut line = match &args.env_var {
+            Some(name) => env_var_assignment(&password, name, index, count),
+            None => password,
+        };
+        if args.numbered {
+            line = number_password(&line, &args.number_format, index);
+        }
+        // Without the trailing separator, the separator goes before each password but the first
+        if !args.no_trailing_separator {
+            line.push_str(separator);
+        } else if 0 < index {
+            line.insert_str(0, separator);
+        }
+        index += 1;
+
+        handle
+            .write_all(&encode_strict(&line, &args.encoding)?)
+            .map_err(|e| e.to_string())
+    })?;
+
+    if json {
+        let end = match index {
+            0 => json_output_start() + JSON_OUTPUT_END,
+            _ => JSON_OUTPUT_END.to_string(),
+        };
+        handle
+            .write_all(&encode_strict(&end, &args.encoding)?)
+            .map_err(|e| e.to_string())?;
+    }
+
+    handle.flush().map_err(|e| e.to_string())
+}
+
+/// Generate a password and output it as a QR code to standard output
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments
+/// * `attempts` - Counter of the attempts, including rejected passwords
+/// * `maker` - Password generator describing the passwords, required for "--annotate", "--summary", and "--verbose"
+///
+/// # Returns
+///
+/// Returns an error message if an error occurs
+///
+/// # Errors
+///
+/// Returns an error if more than one password is requested, or the password does not fit in a QR code
+fn output_qr_code(
+    args: &Cli,
+    attempts: &mut u64,
+    maker: Option<&PasswordMaker>,
+) -> Result<(), String> {
+    if args.count != 1 {
+        return Err(format!(
+            "\"--qr\" outputs only one password, but \"--count\" is {}. Please set \"--count\" to 1.",
+            args.count
+        ));
+    }
+
+    let password = generate_passwords(args, attempts)?.remove(0);
+    print_spellings(&password, args, maker);
+
+    let password = group_password(password, args);
+    let code = qr_code(&password)?;
+    writeln!(io::stdout().lock(), "{}", code)
+        .map_err(|e| format!("Failed to write to standard output: {}", e))
+}
+
+/// Render the text as a QR code of block characters
+///
+/// Two rows of modules are drawn per line. The light modules are drawn with blocks,
+/// so that the code can be scanned on terminals with a dark background.
+///
+/// # Arguments
+///
+/// * `text` - Text held by the QR code in UTF-8
+///
+/// # Returns
+///
+/// QR code with the quiet zone around it
+///
+/// # Errors
+///
+/// Returns an error if the text is too long for a QR code
+fn qr_code(text: &str) -> Result<String, String> {
+    let code = qrcode::QrCode::new(text.as_bytes()).map_err(|e| {
+        format!(
+            "Failed to create the QR code: {}. Please shorten the password.",
+            e
+        )
+    })?;
+    Ok(code
+        .render::<qrcode::render::unicode::Dense1x2>()
+        .dark_color(qrcode::render::unicode::Dense1x2::Light)
+        .light_color(qrcode::render::unicode::Dense1x2::Dark)
+        .build())
+}
+
+/// Generate all the passwords first and then output them together
+///
+/// Used when the output needs all the passwords, e.g. the clipboard, files, and "--summary".
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments
+/// * `attempts` - Counter of the attempts, including rejected passwords
+/// * `maker` - Password generator describing the passwords, required for "--annotate", "--summary", and "--verbose"
+///
+/// # Returns
+///
+/// Returns an error message if an error occurs
+fn output_all_passwords(
+    args: &Cli,
+    attempts: &mut u64,
+    maker: Option<&PasswordMaker>,
+) -> Result<(), String> {
+    let passwords = generate_passwords(args, attempts)?;
+
+    for password in &passwords {
+        print_spellings(password, args, maker);
+    }
+
+    let summary = maker
+        .filter(|_| args.summary)
+        .map(|maker| summarize(&passwords, maker));
+
+    let passwords = if args.group_size.is_some() && args.format == OutputFormat::Text {
+        validate_group_separator(&args.group_separator, &args.encoding)?;
+        passwords
+            .into_iter()
+            .map(|password| group_password(password, args))
+            .collect()
+    } else {
+        passwords
+    };
+
+    let passwords = match &args.env_var {
+        Some(name) => env_var_assignments(passwords, name)?,
+        None => passwords,
+    };
+
+    let passwords = if args.numbered && !args.clipboard && args.format == OutputFormat::Text {
+        number_passwords(passwords, &args.number_format)?
+    } else {
+        passwords
+    };
+
+    let output_string = match args.format {
+        OutputFormat::Text => format_passwords(passwords, args.null, !args.no_trailing_separator),
+        OutputFormat::Json => format_passwords_json(&passwords, maker.filter(|_| args.verbose)),
+    };
+    output_passwords(&output_string, args)?;
+
+    if let Some(summary) = summary {
+        eprintln!("{}", summary);
+    }
+
+    Ok(())
+}
+
+/// Generate passwords
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments
+///
+/// # Returns
+///
+/// Returns an error message if an error occurs
+fn password(args: Cli) -> Result<(), String> {
+    if args.print_command {
+        eprintln!("{}", command_line(&args, args.print_command_verbose));
+    }
+
+    if args.show_encoding && args.apple_rules.is_none() && args.pin.is_none() {
+        for (option, name) in candidate_encodings(&args)? {
+            eprintln!("{}: {}", option, name);
+        }
+    }
+
+    for notice in setting_notices(&args)? {
+        eprintln!("{}", notice);
+    }
+
+    // Built once and shared by all the outputs that describe the passwords
+    let maker = match args.annotate || args.summary || args.verbose {
+        true => Some(PasswordMaker::try_from(&args)?),
+        false => None,
+    };
+
+    let mut attempts = 0;
+    #[cfg(feature = "clipboard")]
+    if let Some(seconds) = args.watch {
+        let stop = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let handler = std::sync::Arc::clone(&stop);
+        ctrlc::set_handler(move || handler.store(true, std::sync::atomic::Ordering::SeqCst))
+            .map_err(|e| format!("Failed to handle Ctrl-C: {}", e))?;
+        return watch_clipboard(
+            &args,
+            std::time::Duration::from_secs(seconds),
+            &mut attempts,
+            maker.as_ref(),
+            &stop,
+        );
+    }
+
+    if args.qr {
+        output_qr_code(&args, &mut attempts, maker.as_ref())?;
+    } else if !args.clipboard && args.output.is_none() && !args.summary {
+        // Stream the passwords unless they are needed all at once
+        stream_passwords(&args, &mut attempts, maker.as_ref())?;
+    } else {
+        output_all_passwords(&args, &mut attempts, maker.as_ref())?;
+    }
+
+    if args.show_stats {
+        eprintln!("Attempts: {}", attempts);
+    }
+
+    if let Some(maker) = maker.filter(|_| args.verbose) {
+        eprintln!("{}", strength_report(&args, &maker));
+    }
+
+    Ok(())
+}
+
+/// Rate the strength of a password
+///
+/// # Arguments
+///
+/// * `bits` - Entropy of the password in bits
+///
+/// # Returns
+///
+/// "Weak" (less than 50 bits), "Fair" (less than 80 bits), "Strong" (less than 128 bits), or "Excellent"
+fn strength_rating(bits: f64) -> &'static str {
+    if bits < 50.0 {
+        "Weak"
+    } else if bits < 80.0 {
+        "Fair"
+    } else if bits < 128.0 {
+        "Strong"
+    } else {
+        "Excellent"
+    }
+}
+
+/// Describe the candidate pool and the strength of the passwords
+///
+/// The passwords share the pool, so it is described once for all of them.
+/// With "--total-chars", the lengths of the passwords differ, so the entropy is given per character.
+/// The entropy is followed by the bits from each character type that adds any.
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments
+/// * `maker` - Password generator used to generate the passwords
+///
+/// # Returns
+///
+/// Report text
+fn strength_report(args: &Cli, maker: &PasswordMaker) -> String {
+    let pool_size = maker.pool_size();
+    let (entropy, breakdown, unit) = if args.total_chars.is_some() {
+        let per_character = PasswordMaker {
+            length: 1,
+            ..maker.clone()
+        };
+        (
+            format!(
+                "Entropy: {:.1} bits per character",
+                maker.bits_per_character()
+            ),
+            per_character.entropy_breakdown(),
+            "bits per character",
+        )
+    } else {
+        let bits = maker.entropy();
+        (
+            format!("Entropy: {:.1} bits ({})", bits, strength_rating(bits)),
+            maker.entropy_breakdown(),
+            "bits",
+        )
+    };
+
+    let mut parts = vec![
+        ("Lowercases".to_string(), breakdown.lowercase),
+        ("Uppercases".to_string(), breakdown.uppercase),
+        ("Numbers".to_string(), breakdown.number),
+        ("Symbols".to_string(), breakdown.symbol),
+    ];
+    for (index, bits) in breakdown.others.iter().enumerate() {
+        parts.push((format!("Other characters at index {}", index), *bits));
+    }
+    parts.push(("Whitespaces".to_string(), breakdown.whitespace));
+
+    let mut report = format!("Pool size: {}\n{}", pool_size, entropy);
+    for (name, bits) in parts.into_iter().filter(|(_, bits)| 0.0 < *bits) {
+        report += &format!("\n  {}: {:.1} {}", name, bits, unit);
+    }
+    report
+}
+
+fn main() -> ExitCode {
+    let matches = Cli::command().get_matches();
+    let mut args = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    if let Some(shell) = args.completion {
+        print_completions(shell);
+        return ExitCode::SUCCESS;
+    }
+
+    if args.list_encodings {
+        for label in encoding::labels() {
+            println!("{}", label);
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    if args.clipboard {
+        Cli::command()
+            .error(clap::error::ErrorKind::ArgumentConflict, CLIPBOARD_DISABLED)
+            .exit();
+    }
+
+    drop_overridden_conversions(&mut args, &matches);
+
+    let errors_to = args.errors_to;
+    let result = apply_preset(&mut args, &matches)
+        .and_then(|_| merge_config(&mut args, &matches))
+        .and_then(|_| read_stdin_candidates(&mut args, &mut io::stdin().lock()))
+        .and_then(|_| read_stdin_count(&mut args, &mut io::stdin().lock()))
+        .and_then(|_| password(args));
+    match result {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            match errors_to {
+                ErrorOutput::Stdout => println!("{}", e),
+                ErrorOutput::Stderr => eprintln!("{}", e),
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_cmd::Command;
+    #[cfg(unix)]
+    use std::os::unix::ffi::OsStringExt;
+    use std::{ffi::OsString, vec};
+
+    use super::*;
+
+    #[test]
+    fn default_password_generation() {
+        let args = Cli::default();
+
+        let passwords = generate_passwords(&args, &mut 0).unwrap();
+        assert_eq!(passwords.len(), 1);
+        // If candidates are added, one character may not be 1 byte, but by default, one character is 1 byte, so check the length with len()
+        assert_eq!(passwords[0].len(), 16);
+    }
+
+    #[test]
+    fn multiple_password_generation() {
+        let args = Cli {
+            count: 5,
+            ..Default::default()
+        };
+
+        let passwords = generate_passwords(&args, &mut 0).unwrap();
+        assert_eq!(passwords.len(), 5);
+
+        // Check that there are no duplicate passwords when generating multiple passwords
+        let unique_passwords: std::collections::HashSet<_> = passwords.iter().collect();
+        assert_eq!(passwords.len(), unique_passwords.len());
+    }
+
+    #[test]
+    fn password_with_other_characters() {
+        // Generate a password that includes special characters such as surrogate pairs
+        // There may be more special characters, but since we are also testing zero-width joiners, this is sufficient.
+        let args = Cli {
+            other_candidates: Some(vec![
+                // Surrogate pair
+                OsString::from("😀🚀🐱"),
+                // Variation Selectors
+                OsString::from("花󠄁龍󠄀舟󠄁👍🏿"),
+                // Combining character
+                OsString::from("áパぎ"),
+                // Zero-width joiner
+                OsString::from("🏳️‍🌈❤️‍🔥👨‍👩‍👦"),
+                // Emoji flag sequence
+                OsString::from("🇯🇵🇺🇸🇲🇦🇨🇦"),
+            ]),
+            other_minimum_count: Some(vec![1, 2, 3, 4, 2]),
+            ..Default::default()
+        };
+
+        let passwords = generate_passwords(&args, &mut 0).unwrap();
+        println!("{}", passwords[0]);
+
+        assert_eq!(passwords.len(), 1);
+        assert_eq!(passwords[0].graphemes(true).count(), 16);
+
+        // Test if the string contains the characters
+        let count_rocket = passwords[0].matches("🚀").count();
+        let count_cat = passwords[0].matches("🐱").count();
+        let count_smile = passwords[0].matches("😀").count();
+        assert!(1 <= count_rocket + count_cat + count_smile);
+
+        let count_hana = passwords[0].matches("花󠄁").count();
+        let count_ryu = passwords[0].matches("龍󠄀").count();
+        let count_fune = passwords[0].matches("舟󠄁").count();
+        let count_ok = passwords[0].matches("👍🏿").count();
+        assert!(2 <= count_hana + count_ryu + count_fune + count_ok);
+
+        let count_a = passwords[0].matches("á").count();
+        let count_pa = passwords[0].matches("パ").count();
+        let count_ki = passwords[0].matches("ぎ").count();
+        assert!(3 <= count_a + count_pa + count_ki);
+
+        let count_rainbow = passwords[0].matches("🏳️‍🌈").count();
+        let count_fire = passwords[0].matches("❤️‍🔥").count();
+        let count_family = passwords[0].matches("👨‍👩‍👦").count();
+        assert!(4 <= count_rainbow + count_fire + count_family);
+
+        let count_jp = passwords[0].matches("🇯🇵").count();
+        let count_us = passwords[0].matches("🇺🇸").count();
+        let count_ma = passwords[0].matches("🇲🇦").count();
+        let count_ca = passwords[0].matches("🇨🇦").count();
+        assert!(2 <= count_jp + count_us + count_ma + count_ca);
+    }
+
+    #[test]
+    fn generate_passwords_err() {
+        let args = Cli {
+            length: 0,
+            ..Default::default()
+        };
+
+        let result = generate_passwords(&args, &mut 0);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn generate_passwords_length_limit() {
+        // The password length is equal to the limit
+        let args = Cli {
+            length: 32,
+            max_length_limit: 32,
+            ..Default::default()
+        };
+        assert!(generate_passwords(&args, &mut 0).is_ok());
+
+        // The password length exceeds the limit
+        let args = Cli {
+            length: 33,
+            max_length_limit: 32,
+            ..Default::default()
+        };
+        assert!(generate_passwords(&args, &mut 0).is_err());
+    }
+
+    #[test]
+    fn generate_passwords_total_chars() {
+        let args = Cli {
+            count: 5,
+            total_chars: Some(100),
+            ..Default::default()
+        };
+
+        let passwords = generate_passwords(&args, &mut 0).unwrap();
+        assert_eq!(passwords.len(), 5);
+        assert_eq!(
+            passwords
+                .iter()
+                .map(|p| p.graphemes(true).count())
+                .sum::<usize>(),
+            100
+        );
+        // Each password still meets the minimum counts
+        for password in &passwords {
+            assert!(password.chars().any(|c| c.is_ascii_uppercase()));
+            assert!(password.chars().any(|c| c.is_ascii_lowercase()));
+            assert!(password.chars().any(|c| c.is_ascii_digit()));
+            assert!(password.chars().any(|c| c.is_ascii_punctuation()));
+        }
+
+        // The remainder is distributed to the first passwords
+        let maker = PasswordMaker::default();
+        assert_eq!(distribute_lengths(&maker, 22, 4).unwrap(), vec![6, 6, 5, 5]);
+
+        // Each password needs at least 4 characters by default
+        let args = Cli {
+            count: 5,
+            total_chars: Some(19),
+            ..Default::default()
+        };
+        assert!(generate_passwords(&args, &mut 0).is_err());
+    }
+
+    #[test]
+    fn generate_passwords_batch_cover_classes() {
+        // 5 types must be covered by 2 passwords of 4 characters without any minimum count
+        let args = Cli {
+            length: 4,
+            count: 2,
+            uppercase_minimum_count: 0,
+            lowercase_minimum_count: 0,
+            number_minimum_count: 0,
+            symbol_minimum_count: 0,
+            other_candidates: Some(vec![OsString::from("あ")]),
+            batch_cover_classes: true,
+            ..Default::default()
+        };
+
+        // Repeat because the passwords are random
+        for _ in 0..20 {
+            let passwords = generate_passwords(&args, &mut 0).unwrap();
+            assert_eq!(passwords.len(), 2);
+
+            let batch = passwords.concat();
+            assert!(batch.chars().any(|c| c.is_ascii_uppercase()));
+            assert!(batch.chars().any(|c| c.is_ascii_lowercase()));
+            assert!(batch.chars().any(|c| c.is_ascii_digit()));
+            assert!(batch.chars().any(|c| c.is_ascii_punctuation()));
+            assert!(batch.contains('あ'));
+        }
+
+        // The minimum counts set to cover the types are reported if they cannot be met
+        let args = Cli {
+            length: 1,
+            count: 1,
+            number_candidates: OsString::from(""),
+            symbol_candidates: OsString::from(""),
+            ..args
+        };
+        let error = generate_passwords(&args, &mut 0).unwrap_err();
+        assert!(error.starts_with(
+            "The total minimum number of characters is greater than the password length."
+        ));
+        assert!(error.ends_with("The minimum counts of Uppercases, Lowercases, Other characters at index 0 were set to 1 by \"--batch-cover-classes\" to cover every type across the passwords."));
+    }
+
+    #[test]
+    fn generate_passwords_reject_dates() {
+        // Mostly digits, so dates are likely without the option
+        let args = Cli {
+            length: 12,
+            count: 20,
+            number_minimum_count: 8,
+            reject_dates: true,
+            ..Default::default()
+        };
+
+        let passwords = generate_passwords(&args, &mut 0).unwrap();
+        assert_eq!(passwords.len(), 20);
+        assert!(passwords.iter().all(|p| !pattern::contains_date(p)));
+
+        // Only digits: almost every password contains a date, so it gives up instead of looping forever
+        let args = Cli {
+            length: 500,
+            uppercase_candidates: OsString::from(""),
+            lowercase_candidates: OsString::from(""),
+            symbol_candidates: OsString::from(""),
+            reject_dates: true,
+            ..Default::default()
+        };
+        let result = generate_passwords(&args, &mut 0);
+        assert!(result.unwrap_err().contains("1000 attempts"));
+    }
+
+    #[test]
+    fn generate_passwords_vanity_prefix() {
+        let args = Cli {
+            count: 5,
+            vanity_prefix: Some("a".to_string()),
+            ..Default::default()
+        };
+
+        let mut attempts = 0;
+        let passwords = generate_passwords(&args, &mut attempts).unwrap();
+        assert_eq!(passwords.len(), 5);
+        assert!(passwords.iter().all(|p| p.starts_with('a')));
+        assert!(5 <= attempts);
+
+        // A character that is not a candidate can never appear
+        let args = Cli {
+            vanity_prefix: Some("あ".to_string()),
+            ..Default::default()
+        };
+        assert!(generate_passwords(&args, &mut 0)
+            .unwrap_err()
+            .contains("is not a candidate"));
+
+        // The prefix is longer than the password
+        let args = Cli {
+            length: 2,
+            lowercase_minimum_count: 0,
+            uppercase_minimum_count: 0,
+            symbol_minimum_count: 0,
+            vanity_prefix: Some("abc".to_string()),
+            ..Default::default()
+        };
+        assert!(generate_passwords(&args, &mut 0)
+            .unwrap_err()
+            .contains("longer than the password length"));
+
+        // Gives up after the maximum number of attempts
+        let args = Cli {
+            vanity_prefix: Some("abc".to_string()),
+            max_attempts: 10,
+            ..Default::default()
+        };
+        assert!(generate_passwords(&args, &mut 0)
+            .unwrap_err()
+            .contains("10 attempts"));
+    }
+
+    #[test]
+    fn generate_passwords_disjoint_from() {
+        let small = |old: &str| Cli {
+            length: 4,
+            count: 20,
+            uppercase_candidates: OsString::new(),
+            uppercase_minimum_count: 0,
+            lowercase_candidates: OsString::from("abcdefgh"),
+            number_candidates: OsString::from("0123"),
+            symbol_candidates: OsString::new(),
+            symbol_minimum_count: 0,
+            disjoint_from: Some(old.to_string()),
+            ..Default::default()
+        };
+
+        // The new passwords share no characters with the old one
+        let mut attempts = 0;
+        let passwords = generate_passwords(&small("abcd01"), &mut attempts).unwrap();
+        assert_eq!(passwords.len(), 20);
+        for password in &passwords {
+            assert!(
+                password.chars().all(|c| "efgh23".contains(c)),
+                "{}",
+                password
+            );
+        }
+        assert!(20 <= attempts);
+
+        // The old password covers all the numbers, which are required
+        let error = generate_passwords(&small("0123"), &mut 0).unwrap_err();
+        assert!(error.contains("\"0123\""), "{}", error);
+
+        // The old password covers the whole pool
+        let error = generate_passwords(&small("abcdefgh0123"), &mut 0).unwrap_err();
+        assert!(error.contains("cover all the candidates"), "{}", error);
+    }
+
+    #[test]
+    fn generate_passwords_deny_wordlist() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "aa\n\n  BB  ").unwrap();
+        let small = |path: &std::path::Path| Cli {
+            length: 2,
+            count: 20,
+            uppercase_candidates: OsString::new(),
+            uppercase_minimum_count: 0,
+            lowercase_candidates: OsString::from("ab"),
+            number_candidates: OsString::new(),
+            number_minimum_count: 0,
+            symbol_candidates: OsString::new(),
+            symbol_minimum_count: 0,
+            deny_wordlist: Some(path.to_path_buf()),
+            ..Default::default()
+        };
+
+        // The passwords with the denied words are regenerated
+        let mut attempts = 0;
+        let passwords = generate_passwords(&small(file.path()), &mut attempts).unwrap();
+        assert_eq!(passwords.len(), 20);
+        assert!(passwords.iter().all(|p| p == "ab" || p == "ba"));
+        assert!(20 <= attempts);
+
+        // Every password contains a denied word
+        writeln!(file, "a\nb").unwrap();
+        let error = generate_passwords(&small(file.path()), &mut 0).unwrap_err();
+        assert!(error.contains("Could not generate"), "{}", error);
+
+        // The file does not exist
+        let dir = tempfile::tempdir().unwrap();
+        let error =
+            generate_passwords(&small(&dir.path().join("missing.txt")), &mut 0).unwrap_err();
+        assert!(error.starts_with("Failed to read the deny wordlist"));
+    }
+
+    #[test]
+    fn passphrase_deny_wordlist() {
+        let mut words = tempfile::NamedTempFile::new().unwrap();
+        writeln!(words, "correct\nhorse").unwrap();
+        let mut denied = tempfile::NamedTempFile::new().unwrap();
+        writeln!(denied, "Horse").unwrap();
+
+        let args = Cli {
+            passphrase: true,
+            wordlist: Some(words.path().to_path_buf()),
+            words: 2,
+            count: 5,
+            deny_wordlist: Some(denied.path().to_path_buf()),
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_passwords(&args, &mut 0).unwrap(),
+            vec!["correct-correct"; 5]
+        );
+
+        // Every word is denied
+        writeln!(denied, "correct").unwrap();
+        let error = generate_passwords(&args, &mut 0).unwrap_err();
+        assert!(
+            error.contains("Could not generate a passphrase"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn homoglyph_warnings_cyrillic() {
+        let args = Cli {
+            other_candidates: Some(vec![OsString::from("\u{0430}")]),
+            other_minimum_count: Some(vec![0]),
+            ..Default::default()
+        };
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        assert_eq!(
+            homoglyph_warnings(&maker),
+            vec!["Warning: \"a\" (U+0061) and \"\u{0430}\" (U+0430) look alike"]
+        );
+
+        assert!(homoglyph_warnings(&PasswordMaker::try_from(&Cli::default()).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn duplicate_warning() {
+        let args = Cli {
+            other_candidates: Some(vec![OsString::from("-😺a")]),
+            ..Default::default()
+        };
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        assert_eq!(
+            super::duplicate_warning(&maker).unwrap(),
+            "Warning: \"a\", \"-\" are candidates of more than one character type. Each of them is used as one candidate."
+        );
+        assert!(
+            super::duplicate_warning(&PasswordMaker::try_from(&Cli::default()).unwrap()).is_none()
+        );
+
+        // The warning goes to standard error, and the password is still generated
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--symbol-candidates=-_", "--other-candidates", "_"])
+            .assert()
+            .success();
+        let output = assert.get_output();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim_end().len(), 16);
+        assert!(String::from_utf8_lossy(&output.stderr).starts_with("Warning: \"_\""));
+    }
+
+    #[test]
+    fn setting_notices() {
+        let args = Cli {
+            show_config: true,
+            other_candidates: Some(vec![OsString::from("a")]),
+            ..Default::default()
+        };
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        assert_eq!(
+            super::setting_notices(&args).unwrap(),
+            [maker.to_string(), super::duplicate_warning(&maker).unwrap()]
+        );
+        assert!(super::setting_notices(&Cli::default()).unwrap().is_empty());
+
+        // The notices are printed once for all the passwords
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--other-candidates", "a", "--count", "3"])
+            .assert()
+            .success();
+        let stderr = String::from_utf8_lossy(&assert.get_output().stderr).to_string();
+        assert_eq!(stderr.matches("Warning: \"a\"").count(), 1, "{}", stderr);
+
+        // Passphrases do not use the candidates
+        let args = Cli {
+            passphrase: true,
+            show_config: true,
+            ..Default::default()
+        };
+        assert!(super::setting_notices(&args).unwrap().is_empty());
+    }
+
+    #[test]
+    fn generate_passwords_unique() {
+        // 4 possible passwords
+        let args = Cli {
+            length: 2,
+            count: 4,
+            uppercase_candidates: OsString::from(""),
+            lowercase_candidates: OsString::from("ab"),
+            number_candidates: OsString::from(""),
+            symbol_candidates: OsString::from(""),
+            uppercase_minimum_count: 0,
+            lowercase_minimum_count: 0,
+            number_minimum_count: 0,
+            symbol_minimum_count: 0,
+            unique: true,
+            ..Default::default()
+        };
+        let mut passwords = generate_passwords(&args, &mut 0).unwrap();
+        passwords.sort();
+        assert_eq!(passwords, vec!["aa", "ab", "ba", "bb"]);
+        // More passwords than possible, detected before generating
+        let args = Cli { count: 5, ..args };
+        let mut attempts = 0;
+        let error = generate_passwords(&args, &mut attempts).unwrap_err();
+        assert_eq!(
+            error,
+            "Cannot produce 5 unique passwords; the keyspace is only 4. Please increase the password length or add more candidates."
+        );
+        assert_eq!(attempts, 0);
+
+        // Passwords of different lengths do not collide, so each length is checked separately
+        let args = Cli {
+            count: 5,
+            total_chars: Some(11),
+            ..args
+        };
+        let mut passwords = generate_passwords(&args, &mut 0).unwrap();
+        passwords.sort();
+        assert_eq!(passwords.len(), 5);
+
+        // The search space is exhausted when the rejected passwords narrow it further
+        let args = Cli {
+            count: 4,
+            total_chars: None,
+            vanity_prefix: Some("a".to_string()),
+            ..args
+        };
+        let error = generate_passwords(&args, &mut 0).unwrap_err();
+        assert!(error.contains("Could not generate 4 unique passwords"));
+        assert!(error.contains("search space is exhausted"));
+    }
+
+    #[test]
+    fn generate_passwords_unique_options() {
+        let small = |seed, batch_cover_classes| Cli {
+            length: 2,
+            count: 4,
+            uppercase_candidates: OsString::from(""),
+            lowercase_candidates: OsString::from("ab"),
+            number_candidates: OsString::from("0"),
+            symbol_candidates: OsString::from(""),
+            uppercase_minimum_count: 0,
+            lowercase_minimum_count: 0,
+            number_minimum_count: 0,
+            symbol_minimum_count: 0,
+            unique: true,
+            seed,
+            batch_cover_classes,
+            ..Default::default()
+        };
+
+        // The collided passwords are regenerated reproducibly with "--seed"
+        let passwords = generate_passwords(&small(Some(1), false), &mut 0).unwrap();
+        assert_eq!(
+            generate_passwords(&small(Some(1), false), &mut 0).unwrap(),
+            passwords
+        );
+        let distinct: std::collections::HashSet<_> = passwords.iter().collect();
+        assert_eq!(distinct.len(), 4);
+
+        // The uncovered types are still required, so "a" and "b" alone are never generated
+        for _ in 0..20 {
+            let args = Cli {
+                length: 1,
+                count: 2,
+                ..small(None, true)
+            };
+            let passwords = generate_passwords(&args, &mut 0).unwrap();
+            assert!(passwords.contains(&"0".to_string()), "{:?}", passwords);
+        }
+    }
+
+    #[test]
+    fn generate_passwords_constant_time_ish() {
+        // The number of attempts does not depend on how many passwords are rejected
+        for seed in 0..3 {
+            let args = Cli {
+                count: 2,
+                vanity_prefix: Some("a".to_string()),
+                max_attempts: 2000,
+                constant_time_ish: true,
+                seed: Some(seed),
+                ..Default::default()
+            };
+
+            let mut attempts = 0;
+            let passwords = generate_passwords(&args, &mut attempts).unwrap();
+            assert!(passwords.iter().all(|p| p.starts_with('a')));
+            assert_eq!(attempts, 2 * 2000);
+        }
+
+        // Without the option, generation stops at the first accepted password
+        let args = Cli {
+            count: 3,
+            max_attempts: 2000,
+            seed: Some(0),
+            ..Default::default()
+        };
+        let mut attempts = 0;
+        generate_passwords(&args, &mut attempts).unwrap();
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn vanity_prefix_show_stats() {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--vanity-prefix", "Z", "--show-stats"])
+            .assert()
+            .success();
+        let output = assert.get_output();
+        assert!(String::from_utf8_lossy(&output.stdout).starts_with('Z'));
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let attempts: u64 = stderr
+            .lines()
+            .find_map(|line| line.strip_prefix("Attempts: "))
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(1 <= attempts);
+    }
+
+    #[test]
+    fn strength_report() {
+        assert_eq!(strength_rating(0.0), "Weak");
+        assert_eq!(strength_rating(50.0), "Fair");
+        assert_eq!(strength_rating(104.9), "Strong");
+        assert_eq!(strength_rating(128.0), "Excellent");
+
+        let args = Cli::default();
+        assert_eq!(
+            super::strength_report(&args, &PasswordMaker::try_from(&args).unwrap()),
+            "Pool size: 94\nEntropy: 104.9 bits (Strong)\n  Lowercases: 29.0 bits\n  Uppercases: 29.0 bits\n  Numbers: 11.2 bits\n  Symbols: 35.7 bits"
+        );
+
+        // The report goes to standard error once, and the passwords are output as they are
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd.args(["--verbose", "--count", "3"]).assert().success();
+        let output = assert.get_output();
+        let stdout = String::from_utf8(output.stdout.clone()).unwrap();
+        assert_eq!(stdout.lines().count(), 3);
+        assert!(stdout.lines().all(|password| password.len() == 16));
+        let stderr = String::from_utf8(output.stderr.clone()).unwrap();
+        assert_eq!(stderr.matches("Entropy").count(), 1, "{}", stderr);
+        assert!(stderr.contains("Strong"), "{}", stderr);
+
+        // Only the placeholders of a pattern are random
+        for (pattern, report) in [
+            (
+                "a-fixed-literal-text",
+                "Pool size: 26\nEntropy: 9.4 bits (Weak)\n  Lowercases: 9.4 bits",
+            ),
+            (
+                "0000",
+                "Pool size: 10\nEntropy: 13.3 bits (Weak)\n  Numbers: 13.3 bits",
+            ),
+        ] {
+            let args = Cli {
+                pattern: Some(pattern.to_string()),
+                ..Default::default()
+            };
+            assert_eq!(
+                super::strength_report(&args, &PasswordMaker::try_from(&args).unwrap()),
+                report
+            );
+        }
+
+        // A heavily weighted candidate lowers the strength
+        let args = Cli::default();
+        let mut maker = PasswordMaker::try_from(&args).unwrap();
+        maker.lowercase.weights = vec![1_000_000];
+        maker.lowercase.weights.resize(26, 1);
+        let report = super::strength_report(&args, &maker);
+        assert!(report.contains("(Weak)"), "{}", report);
+
+        // The other characters and the whitespace are reported separately, per character with "--total-chars"
+        let args = Cli {
+            other_candidates: Some(vec!["あ".into()]),
+            include_whitespace: true,
+            total_chars: Some(40),
+            count: 2,
+            ..Default::default()
+        };
+        let report = super::strength_report(&args, &PasswordMaker::try_from(&args).unwrap());
+        assert!(
+            report.starts_with("Pool size: 96\nEntropy: 6.6 bits per character\n"),
+            "{}",
+            report
+        );
+        assert!(
+            report.contains("\n  Other characters at index 0: 0.1 bits per character"),
+            "{}",
+            report
+        );
+        assert!(
+            report.ends_with("\n  Whitespaces: 0.1 bits per character"),
+            "{}",
+            report
+        );
+    }
+
+    #[test]
+    fn summarize_passwords() {
+        // 3 passwords of 8 characters from 16 candidates (4 bits each)
+        let args = Cli {
+            length: 8,
+            count: 3,
+            uppercase_candidates: OsString::from("ABCD"),
+            lowercase_candidates: OsString::from("abcd"),
+            number_candidates: OsString::from("0123"),
+            symbol_candidates: OsString::from("!@#$"),
+            ..Default::default()
+        };
+        let passwords = generate_passwords(&args, &mut 0).unwrap();
+        let maker = PasswordMaker::try_from(&args).unwrap();
+
+        let summary = summarize(&passwords, &maker);
+        assert_eq!(
+            summary,
+            "Passwords: 3\nLength: 8\nPool size: 16\nEntropy: 32.0 bits per password"
+        );
+
+        // Lengths differ with --total-chars, and duplicate candidates are counted once
+        let args = Cli {
+            count: 2,
+            total_chars: Some(17),
+            symbol_candidates: OsString::from("!!"),
+            raw_sample: true,
+            ..Default::default()
+        };
+        let passwords = generate_passwords(&args, &mut 0).unwrap();
+        let maker = PasswordMaker::try_from(&args).unwrap();
+
+        let summary = summarize(&passwords, &maker);
+        assert!(summary.contains("Passwords: 2\n"));
+        assert!(summary.contains("Length: 8-9\n"));
+        assert!(summary.contains("Pool size: 63\n"));
+        assert!(summary.contains("Warning: the minimum counts are not enforced"));
+    }
+
+    #[test]
+    fn format_passwords_with_null_separator() {
+        let passwords = vec!["password1".to_string(), "password2".to_string()];
+        let formatted = format_passwords(passwords, true, true);
+        assert_eq!(formatted, "password1\0password2\0");
+    }
+
+    #[test]
+    fn format_passwords_with_newline_separator() {
+        let passwords = vec!["password1".to_string(), "password2".to_string()];
+        let formatted = format_passwords(passwords, false, true);
+        assert_eq!(formatted, "password1\npassword2\n");
+    }
+
+    #[test]
+    fn format_passwords_without_trailing_separator() {
+        let passwords = vec!["password1".to_string(), "password2".to_string()];
+        assert_eq!(
+            format_passwords(passwords.clone(), false, false),
+            "password1\npassword2"
+        );
+        assert_eq!(
+            format_passwords(passwords, true, false),
+            "password1\0password2"
+        );
+        assert_eq!(
+            format_passwords(vec!["password1".to_string()], false, false),
+            "password1"
+        );
+    }
+
+    #[test]
+    fn no_trailing_separator() {
+        // A single password is output without a trailing byte
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd.arg("--no-trailing-separator").assert().success();
+        let stdout = &assert.get_output().stdout;
+        assert_eq!(stdout.len(), 16);
+
+        // Two passwords are separated but not terminated, also when they are streamed
+        for extra in [&[][..], &["--null"], &["--summary"]] {
+            let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+            let assert = cmd
+                .args(["--no-trailing-separator", "--count", "2"])
+                .args(extra)
+                .assert()
+                .success();
+            let stdout = &assert.get_output().stdout;
+            let separator = if extra == ["--null"] { b'\0' } else { b'\n' };
+            assert_eq!(stdout.len(), 16 * 2 + 1, "{:?}", extra);
+            assert_eq!(stdout[16], separator);
+            assert_ne!(stdout.last(), Some(&separator));
+        }
+    }
+
+    #[test]
+    fn format_passwords_as_json() {
+        let passwords = vec!["pass\"word".to_string(), "パスワード".to_string()];
+        let formatted = format_passwords_json(&passwords, None);
+        assert_eq!(
+            formatted,
+            "{\"schema_version\":1,\"passwords\":[\"pass\\\"word\",\"パスワード\"]}\n"
+        );
+
+        // With the details of each password
+        let maker = PasswordMaker::default();
+        let formatted = format_passwords_json(&passwords, Some(&maker));
+        let parsed: serde_json::Value = serde_json::from_str(&formatted).unwrap();
+        assert_eq!(parsed["schema_version"], JSON_SCHEMA_VERSION);
+        let parsed = &parsed["passwords"];
+        assert_eq!(parsed[1]["password"], "パスワード");
+        assert_eq!(parsed[1]["length"], 5);
+        let entropy = parsed[1]["entropy"].as_f64().unwrap();
+        assert!((entropy - 5.0 * 94f64.log2()).abs() < 1e-9);
+
+        // Only the placeholders of a pattern are random
+        let maker = PasswordMaker::from_pattern("0000");
+        let formatted = format_passwords_json(&["1234".to_string()], Some(&maker));
+        let parsed: serde_json::Value = serde_json::from_str(&formatted).unwrap();
+        let entropy = parsed["passwords"][0]["entropy"].as_f64().unwrap();
+        assert!((entropy - 4.0 * 10f64.log2()).abs() < 1e-9);
+
+        assert_eq!(
+            format_passwords_json(&[], None),
+            "{\"schema_version\":1,\"passwords\":[]}\n"
+        );
+    }
+
+    /// Parse the passwords from the JSON output, checking the schema version
+    ///
+    /// # Arguments
+    ///
+    /// * `stdout` - JSON output
+    ///
+    /// # Returns
+    ///
+    /// List of passwords
+    fn json_passwords(stdout: &str) -> Vec<String> {
+        let parsed: serde_json::Value = serde_json::from_str(stdout).unwrap();
+        assert_eq!(parsed["schema_version"], JSON_SCHEMA_VERSION);
+        serde_json::from_value(parsed["passwords"].clone()).unwrap()
+    }
+
+    #[test]
+    fn output_json() {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--format", "json", "--count", "5"])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        let passwords = json_passwords(&stdout);
+        assert_eq!(passwords.len(), 5);
+        assert!(passwords.iter().all(|password| password.len() == 16));
+
+        // The JSON is output in the specified encoding
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args([
+                "--format",
+                "json",
+                "--encoding",
+                "shift_jis",
+                "--encoding-fallback",
+                "utf-8",
+                "--uppercase-candidates",
+                "",
+                "--lowercase-candidates",
+                "",
+                "--number-candidates",
+                "",
+                "--symbol-candidates",
+                "",
+                "--other-candidates",
+                "あ",
+                "--length",
+                "4",
+            ])
+            .assert()
+            .success();
+        let stdout =
+            encoding::decode(&assert.get_output().stdout, &"shift_jis".to_string()).unwrap();
+        let passwords = json_passwords(&stdout);
+        assert_eq!(passwords, vec!["あ".repeat(4)]);
+
+        // JSON has its own separators
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        cmd.args(["--format", "json", "--null"]).assert().failure();
+    }
+
+    #[test]
+    fn shell_quote_string() {
+        assert_eq!(shell_quote("abc"), "'abc'");
+        assert_eq!(shell_quote("a'b"), "'a'\\''b'");
+        assert_eq!(shell_quote("$HOME `ls` \"\\"), "'$HOME `ls` \"\\'");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn shell_quote_os_string() {
+        assert_eq!(shell_quote_os(&OsString::from("a'b")), "'a'\\''b'");
+        assert_eq!(
+            shell_quote_os(&OsString::from_vec(vec![0x82, 0xa0, b'a'])),
+            "$'\\x82\\xa0\\x61'"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn command_line_reproduces_settings() {
+        let cases: Vec<Vec<&str>> = vec![
+            vec![],
+            vec![
+                "--length",
+                "20",
+                "--symbol-candidates=-_'\"$",
+                "--other-candidates",
+                "あい",
+                "--other-minimum-count",
+                "1",
+                "--exclude-similar",
+                "--seed",
+                "7",
+            ],
+            vec!["--apple-rules", "required: upper; maxlength: 12;"],
+            vec!["--pin", "6", "--no-leading-zero"],
+            vec!["--min-entropy", "80", "--exclude-similar"],
+            vec!["--exclude", "\"\\'"],
+            vec![
+                "--symbol-minimum-count",
+                "0",
+                "--symbol-maximum-count",
+                "0",
+                "--other-candidates",
+                "あい",
+                "--other-maximum-count",
+                "3",
+            ],
+            vec!["--pattern", "Aa0!-\\aaaa", "--symbol-candidates", "#"],
+            vec![
+                "--lowercase-candidates",
+                "abc",
+                "--uppercase-from-lowercase",
+                "--null",
+                "--no-trailing-separator",
+                "--show-config",
+            ],
+            vec![
+                "--passphrase",
+                "--words",
+                "3",
+                "--separator",
+                " ",
+                "--wordlist",
+                "words.txt",
+                "--deny-wordlist",
+                "denied.txt",
+            ],
+            vec![
+                "--total-chars",
+                "40",
+                "--count",
+                "3",
+                "--numbered",
+                "--number-format",
+                "#{n} ",
+            ],
+            vec![
+                "--daily",
+                "--secret",
+                "it's a secret",
+                "--date",
+                "2024-01-01",
+            ],
+        ];
+
+        for case in cases {
+            let args = Cli::try_parse_from(std::iter::once("mkpw").chain(case.clone())).unwrap();
+            for verbose in [false, true] {
+                let line = command_line(&args, verbose);
+
+                // Let the shell split the command line into words
+                let output = std::process::Command::new("sh")
+                    .arg("-c")
+                    .arg(format!("printf '%s\\0' {}", line))
+                    .output()
+                    .unwrap();
+                let stdout = String::from_utf8(output.stdout).unwrap();
+                let words: Vec<&str> = stdout.split_terminator('\0').collect();
+
+                let reproduced = Cli::try_parse_from(words).unwrap();
+                assert_eq!(
+                    PasswordMaker::try_from(&reproduced),
+                    PasswordMaker::try_from(&args),
+                    "{:?} {}",
+                    case,
+                    line
+                );
+                assert_eq!(command_line(&reproduced, verbose), line);
+            }
+        }
+
+        // Only the options that differ from the defaults are printed without verbose
+        let args = Cli::try_parse_from(["mkpw", "--count", "3"]).unwrap();
+        assert_eq!(command_line(&args, false), "mkpw --count=3");
+        assert!(command_line(&args, true)
+            .starts_with("mkpw --length=16 --max-length-limit=1000000 --count=3"));
+    }
+
+    #[test]
+    fn print_command() {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--count", "2", "--exclude-similar", "--print-command"])
+            .assert()
+            .success();
+        let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+        assert_eq!(stderr, "mkpw --count=2 --exclude-similar\n");
+
+        // Verbose needs "--print-command"
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        cmd.arg("--print-command-verbose").assert().failure();
+    }
+
+    #[test]
+    fn load_wordlist_formats() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, "apple\n\n  river \n11111\tabacus\n11112 abdomen\n").unwrap();
+        assert_eq!(
+            load_wordlist(file.path()),
+            Ok(vec![
+                "apple".to_string(),
+                "river".to_string(),
+                "abacus".to_string(),
+                "abdomen".to_string(),
+            ])
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let error = load_wordlist(&dir.path().join("missing.txt")).unwrap_err();
+        assert!(error.starts_with("Failed to read the wordlist"));
+    }
+
+    #[test]
+    fn passphrase() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "correct\nhorse\nbattery\nstaple").unwrap();
+        let wordlist = file.path().to_str().unwrap();
+
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--passphrase", "--words", "6", "--separator", "_"])
+            .args(["--wordlist", wordlist, "--count", "3"])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines.len(), 3);
+        for line in lines {
+            let words: Vec<&str> = line.split('_').collect();
+            assert_eq!(words.len(), 6);
+            assert!(words
+                .iter()
+                .all(|w| ["correct", "horse", "battery", "staple"].contains(w)));
+        }
+
+        // The default is 5 words separated by "-"
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--passphrase", "--wordlist", wordlist])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        assert_eq!(stdout.trim_end().split('-').count(), 5);
+
+        // A wordlist is required
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        cmd.arg("--passphrase").assert().failure();
+
+        // An empty wordlist is an error
+        let empty = tempfile::NamedTempFile::new().unwrap();
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        cmd.args(["--passphrase", "--wordlist", empty.path().to_str().unwrap()])
+            .assert()
+            .failure()
+            .stderr(PasswordError::EmptyWordlist.to_string() + "\n");
+    }
+
+    #[test]
+    fn stream_passwords() {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let streamed = cmd
+            .args(["--seed", "1", "--count", "1000", "--null"])
+            .assert()
+            .success();
+        let streamed = streamed.get_output().stdout.clone();
+        assert_eq!(streamed.iter().filter(|&&b| b == b'\0').count(), 1000);
+
+        // The same passwords as buffering all of them for "--summary"
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let buffered = cmd
+            .args(["--seed", "1", "--count", "1000", "--null", "--summary"])
+            .assert()
+            .success();
+        assert_eq!(buffered.get_output().stdout, streamed);
+
+        // Variables are numbered also while streaming
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--count", "2", "--env-var", "PASS"])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert!(lines[0].starts_with("export PASS_1='"));
+        assert!(lines[1].starts_with("export PASS_2='"));
+    }
+
+    #[test]
+    fn stream_passwords_json() {
+        let run = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+            let assert = cmd.args(args).assert().success();
+            assert.get_output().stdout.clone()
+        };
+
+        // The streamed array is valid JSON with all the passwords
+        let streamed = run(&["--seed", "1", "--count", "5000", "--format", "json"]);
+        let passwords = json_passwords(std::str::from_utf8(&streamed).unwrap());
+        assert_eq!(passwords.len(), 5000);
+
+        // The same passwords as buffering all of them for "--summary"
+        let buffered = run(&[
+            "--seed",
+            "1",
+            "--count",
+            "5000",
+            "--format",
+            "json",
+            "--summary",
+        ]);
+        assert_eq!(buffered, streamed);
+
+        // Also with the length and entropy of each password
+        let args = [
+            "--seed",
+            "1",
+            "--count",
+            "3",
+            "--format",
+            "json",
+            "--verbose",
+        ];
+        assert_eq!(run(&args), run(&[&args[..], &["--summary"]].concat()));
+
+        // No passwords are an empty array
+        let stdout = run(&["--count", "0", "--format", "json"]);
+        assert!(json_passwords(std::str::from_utf8(&stdout).unwrap()).is_empty());
+
+        // The schema version is also output with the details of each password
+        let stdout = run(&["--count", "2", "--format", "json", "--verbose"]);
+        let parsed: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+        assert_eq!(parsed["schema_version"], JSON_SCHEMA_VERSION);
+        assert_eq!(parsed["passwords"][1]["length"], 16);
+    }
+
+    #[test]
+    fn number_passwords_format() {
+        let passwords = vec!["abc".to_string(), "def".to_string()];
+        assert_eq!(
+            number_passwords(passwords.clone(), "{n}: ").unwrap(),
+            vec!["1: abc", "2: def"]
+        );
+        assert_eq!(
+            number_passwords(passwords.clone(), "#{n}\t").unwrap(),
+            vec!["#1\tabc", "#2\tdef"]
+        );
+        assert_eq!(
+            number_passwords(passwords, "n: ").unwrap_err(),
+            "Invalid number format: n: . Include \"{n}\" where the number goes."
+        );
+    }
+
+    #[test]
+    fn numbered() {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd.args(["--count", "3", "--numbered"]).assert().success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines.len(), 3);
+        for (index, line) in lines.iter().enumerate() {
+            let prefix = format!("{}: ", index + 1);
+            assert!(line.starts_with(&prefix));
+            assert_eq!(line.len(), prefix.len() + 16);
+        }
+
+        // Only the passwords are output as JSON
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--count", "3", "--numbered", "--format", "json"])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        let passwords = json_passwords(&stdout);
+        assert!(passwords.iter().all(|password| password.len() == 16));
+    }
+
+    #[test]
+    fn env_var_assignments_format() {
+        // A single password is assigned to the name as it is
+        let assignments = env_var_assignments(vec!["p'w".to_string()], "DB_PASS").unwrap();
+        assert_eq!(assignments, vec!["export DB_PASS='p'\\''w'"]);
+
+        // Multiple passwords are numbered from 1
+        let assignments =
+            env_var_assignments(vec!["a".to_string(), "b".to_string()], "_PASS2").unwrap();
+        assert_eq!(
+            assignments,
+            vec!["export _PASS2_1='a'", "export _PASS2_2='b'"]
+        );
+
+        // Invalid names
+        assert!(env_var_assignments(vec!["a".to_string()], "").is_err());
+        assert!(env_var_assignments(vec!["a".to_string()], "1PASS").is_err());
+        assert!(env_var_assignments(vec!["a".to_string()], "DB-PASS").is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn env_var_eval() {
+        // Use many quotes and shell metacharacters to check that the output survives eval
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args([
+                "--env-var",
+                "MKPW_TEST",
+                "--length",
+                "64",
+                "--symbol-candidates",
+                "'\"$`\\;&|",
+                "--symbol-minimum-count",
+                "32",
+            ])
+            .assert()
+            .success();
+        let statement = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        assert!(statement.starts_with("export MKPW_TEST='"));
+
+        let output = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(format!("{}printf '%s' \"$MKPW_TEST\"", statement))
+            .output()
+            .unwrap();
+        let password = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(password.chars().count(), 64);
+        assert_eq!(
+            statement,
+            format!("export MKPW_TEST={}\n", shell_quote(&password))
+        );
+    }
+
+    #[test]
+    fn merge_config_precedence() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            file,
+            "length = 20\ncount = 3\nnumber-candidates = \"01\"\n\n[[others]]\ncandidates = \"あい\"\nminimum-count = 2\n"
+        )
+        .unwrap();
+        let path = file.path().to_str().unwrap();
+
+        // The file fills the options that are not given on the command line
+        let matches = Cli::command().get_matches_from(["mkpw", "--config", path, "--count", "5"]);
+        let mut args = Cli::from_arg_matches(&matches).unwrap();
+        merge_config(&mut args, &matches).unwrap();
+        assert_eq!(args.length, 20);
+        assert_eq!(args.count, 5);
+        assert_eq!(args.number_candidates, OsString::from("01"));
+        assert_eq!(args.other_candidates, Some(vec![OsString::from("あい")]));
+        assert_eq!(args.other_minimum_count, Some(vec![2]));
+        assert_eq!(args.symbol_candidates, Cli::default().symbol_candidates);
+
+        // Other characters on the command line replace all groups of the file
+        let matches =
+            Cli::command().get_matches_from(["mkpw", "--config", path, "--other-candidates", "😀"]);
+        let mut args = Cli::from_arg_matches(&matches).unwrap();
+        merge_config(&mut args, &matches).unwrap();
+        assert_eq!(args.other_candidates, Some(vec![OsString::from("😀")]));
+        assert_eq!(args.other_minimum_count, None);
+
+        // The candidates in the file cannot be decoded with another encoding
+        let matches =
+            Cli::command().get_matches_from(["mkpw", "--config", path, "--encoding", "shift_jis"]);
+        let mut args = Cli::from_arg_matches(&matches).unwrap();
+        let error = merge_config(&mut args, &matches).unwrap_err();
+        assert!(error.contains("the encoding is shift_jis"));
+    }
+
+    #[test]
+    fn config_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            file,
+            "length = 24\nuppercase-candidates = \"\"\nlowercase-candidates = \"ab\"\nnumber-candidates = \"\"\nsymbol-candidates = \"-\"\n"
+        )
+        .unwrap();
+
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .arg("--config")
+            .arg(file.path())
+            .args(["--count", "3"])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+
+        let passwords: Vec<&str> = stdout.lines().collect();
+        assert_eq!(passwords.len(), 3);
+        for password in passwords {
+            assert_eq!(password.len(), 24);
+            assert!(password.chars().all(|c| "ab-".contains(c)));
+            assert!(password.contains('-'));
+        }
+
+        // A missing config file is reported as an error
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .arg("--config")
+            .arg(file.path().with_extension("missing"))
+            .assert()
+            .failure();
+        let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+        assert!(stderr.starts_with("Failed to read the config file"));
+    }
+
+    #[test]
+    fn maximum_counts() {
+        let args = Cli {
+            length: 30,
+            number_maximum_count: Some(2),
+            other_candidates: Some(vec![OsString::from("😺"), OsString::from("あ")]),
+            other_maximum_count: Some(vec![1]),
+            count: 20,
+            ..Default::default()
+        };
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        assert_eq!(maker.number.maximum_count, Some(2));
+        assert_eq!(maker.uppercase.maximum_count, None);
+        assert_eq!(maker.others[0].maximum_count, Some(1));
+        assert_eq!(maker.others[1].maximum_count, None);
+        for password in generate_passwords(&args, &mut 0).unwrap() {
+            assert!(password.chars().filter(char::is_ascii_digit).count() <= 2);
+            assert!(password.matches('😺').count() <= 1);
+        }
+
+        // A maximum below the minimum fails before the generation
+        let args = Cli {
+            symbol_maximum_count: Some(0),
+            ..Default::default()
+        };
+        let error = PasswordMaker::try_from(&args).unwrap_err();
+        assert!(
+            error.contains("less than the minimum number of characters"),
+            "{}",
+            error
+        );
+
+        // Maximums that add up to less than the length fail as well
+        let args = Cli {
+            length: 8,
+            uppercase_maximum_count: Some(1),
+            lowercase_maximum_count: Some(1),
+            number_maximum_count: Some(1),
+            symbol_maximum_count: Some(1),
+            ..Default::default()
+        };
+        let error = PasswordMaker::try_from(&args).unwrap_err();
+        assert!(
+            error.contains("total maximum number of characters is 4"),
+            "{}",
+            error
+        );
+
+        // The rules and the PIN set the counts themselves
+        assert!(
+            Cli::try_parse_from(["mkpw", "--pin", "4", "--number-maximum-count", "2"]).is_err()
+        );
+    }
+
+    #[test]
+    fn set_classifiers_convert_case() {
+        let args = Cli {
+            lowercase_candidates: OsString::from("abc"),
+            uppercase_from_lowercase: true,
+            ..Default::default()
+        };
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        assert_eq!(maker.uppercase.candidates, vec!["A", "B", "C"]);
+        assert_eq!(maker.uppercase.minimum_count, 1);
+
+        // Candidates without a single grapheme in the other case are skipped
+        let args = Cli {
+            lowercase_candidates: OsString::from("aßa1é"),
+            uppercase_from_lowercase: true,
+            ..Default::default()
+        };
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        assert_eq!(maker.uppercase.candidates, vec!["A", "É"]);
+
+        let args = Cli {
+            uppercase_candidates: OsString::from("XYZ"),
+            lowercase_from_uppercase: true,
+            ..Default::default()
+        };
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        assert_eq!(maker.lowercase.candidates, vec!["x", "y", "z"]);
+
+        // Nothing to convert
+        let args = Cli {
+            lowercase_candidates: OsString::from("123"),
+            uppercase_from_lowercase: true,
+            ..Default::default()
+        };
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        assert!(maker.uppercase.candidates.is_empty());
+        assert_eq!(maker.uppercase.minimum_count, 0);
+
+        // Explicit candidates on the command line take precedence
+        let matches = Cli::command().get_matches_from([
+            "mkpw",
+            "--lowercase-candidates",
+            "abc",
+            "--uppercase-candidates",
+            "XY",
+            "--uppercase-from-lowercase",
+        ]);
+        let mut args = Cli::from_arg_matches(&matches).unwrap();
+        drop_overridden_conversions(&mut args, &matches);
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        assert_eq!(maker.uppercase.candidates, vec!["X", "Y"]);
+    }
+
+    #[test]
+    fn set_classifiers_utf8() {
+        let mut maker = PasswordMaker::default();
+        let args = Cli {
+            uppercase_candidates: OsString::from("ABC"),
+            uppercase_minimum_count: 2,
+            lowercase_candidates: OsString::from("abc"),
+            lowercase_minimum_count: 3,
+            number_candidates: OsString::from("123"),
+            number_minimum_count: 4,
+            symbol_candidates: OsString::from("!@#"),
+            symbol_minimum_count: 5,
+            other_candidates: Some(vec![OsString::from("😀👨‍👩‍👦😂"), OsString::from("あいう")]),
+            other_minimum_count: Some(vec![6, 7]),
+            ..Default::default()
+        };
+
+        set_classifiers(&mut maker, &args).unwrap();
+
+        assert_eq!(maker.uppercase.candidates, vec!["A", "B", "C"]);
+        assert_eq!(maker.uppercase.minimum_count, 2);
+        assert_eq!(maker.lowercase.candidates, vec!["a", "b", "c"]);
+        assert_eq!(maker.lowercase.minimum_count, 3);
+        assert_eq!(maker.number.candidates, vec!["1", "2", "3"]);
+        assert_eq!(maker.number.minimum_count, 4);
+        assert_eq!(maker.symbol.candidates, vec!["!", "@", "#"]);
+        assert_eq!(maker.symbol.minimum_count, 5);
+        assert_eq!(maker.others.len(), 2);
+        assert_eq!(maker.others[0].candidates, vec!["😀", "👨‍👩‍👦", "😂"]);
+        assert_eq!(maker.others[0].minimum_count, 6);
+        assert_eq!(maker.others[1].candidates, vec!["あ", "い", "う"]);
+        assert_eq!(maker.others[1].minimum_count, 7);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn set_classifiers_shift_jis() {
+        let mut maker = PasswordMaker::default();
+        let args = Cli {
+            // Shift_JIS for "あいうえお"
+            uppercase_candidates: OsString::from_vec(vec![
+                0x82, 0xA0, 0x82, 0xA2, 0x82, 0xA4, 0x82, 0xA6, 0x82, 0xA8,
+            ]),
+
+            // Shift_JIS for "アイウエオ"
+            lowercase_candidates: OsString::from_vec(vec![
+                0x83, 0x41, 0x83, 0x43, 0x83, 0x45, 0x83, 0x47, 0x83, 0x49,
+            ]),
+
+            // Shift_JIS for "ｱｲｳｴｵ"
+            number_candidates: OsString::from_vec(vec![0xB1, 0xB2, 0xB3, 0xB4, 0xB5]),
+
+            // Shift_JIS for "ｧｨｩｪｫ"
+            symbol_candidates: OsString::from_vec(vec![0xA7, 0xA8, 0xA9, 0xAA, 0xAB]),
+
+            // Shift_JIS for "ａｉｕｅｏ" and "安以宇衣於"
+            other_candidates: Some(vec![
+                OsString::from_vec(vec![
+                    0x82, 0x81, 0x82, 0x89, 0x82, 0x95, 0x82, 0x85, 0x82, 0x8f,
+                ]),
+                OsString::from_vec(vec![
+                    0x88, 0xC0, 0x88, 0xC8, 0x89, 0x46, 0x88, 0xDF, 0x89, 0x97,
+                ]),
+            ]),
+            encoding: "shift_jis".to_string(),
+            ..Default::default()
+        };
+
+        set_classifiers(&mut maker, &args).unwrap();
+
+        assert_eq!(
+            maker.uppercase.candidates,
+            vec!["あ", "い", "う", "え", "お"]
+        );
+
+        assert_eq!(
+            maker.lowercase.candidates,
+            vec!["ア", "イ", "ウ", "エ", "オ"]
+        );
+
+        assert_eq!(maker.number.candidates, vec!["ｱ", "ｲ", "ｳ", "ｴ", "ｵ"]);
+
+        assert_eq!(maker.symbol.candidates, vec!["ｧ", "ｨ", "ｩ", "ｪ", "ｫ"]);
+
+        assert_eq!(maker.others.len(), 2);
+        assert_eq!(
+            maker.others[0].candidates,
+            vec!["ａ", "ｉ", "ｕ", "ｅ", "ｏ"]
+        );
+        assert_eq!(
+            maker.others[1].candidates,
+            vec!["安", "以", "宇", "衣", "於"]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn set_classifiers_encoding_fallback() {
+        let mut maker = PasswordMaker::default();
+        let args = Cli {
+            // Shift_JIS for "あいうえお"
+            other_candidates: Some(vec![
+                OsString::from_vec(vec![
+                    0x82, 0xA0, 0x82, 0xA2, 0x82, 0xA4, 0x82, 0xA6, 0x82, 0xA8,
+                ]),
+                OsString::from("αβ"),
+            ]),
+            encoding_fallback: Some(vec!["utf-8".to_string(), "shift_jis".to_string()]),
+            ..Default::default()
+        };
+
+        set_classifiers(&mut maker, &args).unwrap();
+        assert_eq!(
+            maker.others[0].candidates,
+            vec!["あ", "い", "う", "え", "お"]
+        );
+        assert_eq!(maker.others[1].candidates, vec!["α", "β"]);
+
+        let encodings = candidate_encodings(&args).unwrap();
+        assert_eq!(
+            encodings,
+            vec![
+                ("--uppercase-candidates".to_string(), "UTF-8"),
+                ("--lowercase-candidates".to_string(), "UTF-8"),
+                ("--number-candidates".to_string(), "UTF-8"),
+                ("--symbol-candidates".to_string(), "UTF-8"),
+                ("--other-candidates #1".to_string(), "Shift_JIS"),
+                ("--other-candidates #2".to_string(), "UTF-8"),
+            ]
+        );
+
+        // Without the fallback, "--encoding" is used
+        let args = Cli {
+            encoding_fallback: None,
+            encoding: "sjis".to_string(),
+            ..args
+        };
+        assert_eq!(candidate_encodings(&args).unwrap()[0].1, "Shift_JIS");
+    }
+
+    #[test]
+    fn strip_variation_selectors() {
+        let args = Cli {
+            other_candidates: Some(vec![OsString::from("❤️☺︎a\u{FE0F}")]),
+            ..Default::default()
+        };
+
+        // Kept as they are by default
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        assert_eq!(
+            maker.effective_pool()[94..],
+            ["❤️", "☺︎", "a\u{FE0F}"].map(String::from)
+        );
+
+        // Replaced with the base form
+        let args = Cli {
+            strip_variation_selectors: true,
+            ..args
+        };
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        assert_eq!(maker.effective_pool()[94..], ["❤", "☺"].map(String::from));
+    }
+
+    #[test]
+    fn set_classifiers_empty() {
+        // When all candidates are empty, and everything else is default
+        {
+            let mut maker = PasswordMaker::default();
+            let args = Cli {
+                uppercase_candidates: OsString::from(""),
+                lowercase_candidates: OsString::from(""),
+                number_candidates: OsString::from(""),
+                symbol_candidates: OsString::from(""),
+                ..Default::default()
+            };
+
+            set_classifiers(&mut maker, &args).unwrap();
+
+            assert!(maker.uppercase.candidates.is_empty());
+            assert_eq!(maker.uppercase.minimum_count, 0);
+            assert!(maker.lowercase.candidates.is_empty());
+            assert_eq!(maker.lowercase.minimum_count, 0);
+            assert!(maker.number.candidates.is_empty());
+            assert_eq!(maker.number.minimum_count, 0);
+            assert!(maker.symbol.candidates.is_empty());
+            assert_eq!(maker.symbol.minimum_count, 0);
+            assert!(maker.others.is_empty());
+        }
+
+        // When all candidates and minimum counts are empty, and everything else is default
+        {
+            let mut maker = PasswordMaker::default();
+
+            let args = Cli {
+                uppercase_candidates: OsString::from(""),
+                uppercase_minimum_count: 1,
+                lowercase_candidates: OsString::from(""),
+                lowercase_minimum_count: 2,
+                number_candidates: OsString::from(""),
+                number_minimum_count: 3,
+                symbol_candidates: OsString::from(""),
+                symbol_minimum_count: 4,
+                ..Default::default()
+            };
+
+            set_classifiers(&mut maker, &args).unwrap();
+
+            assert!(maker.uppercase.candidates.is_empty());
+            assert_eq!(maker.uppercase.minimum_count, 0);
+            assert!(maker.lowercase.candidates.is_empty());
+            assert_eq!(maker.lowercase.minimum_count, 0);
+            assert!(maker.number.candidates.is_empty());
+            assert_eq!(maker.number.minimum_count, 0);
+            assert!(maker.symbol.candidates.is_empty());
+            assert_eq!(maker.symbol.minimum_count, 0);
+            assert!(maker.others.is_empty());
+        }
+    }
+
+    #[test]
+    fn set_classifiers_err() {
+        let mut maker = PasswordMaker::default();
+        let args = Cli {
+            uppercase_candidates: OsString::from("ABC"),
+            encoding: "invalid".to_string(),
+            ..Default::default()
+        };
+
+        let result = set_classifiers(&mut maker, &args);
+        assert_eq!(result, Err("Unsupported encoding: invalid".to_string()));
+    }
+
+    #[test]
+    fn set_classifiers_omit_other_minimum_count() {
+        // When there are no other_candidates
+        {
+            let mut maker = PasswordMaker::default();
+            let args = Cli {
+                other_candidates: None,
+                ..Default::default()
+            };
+
+            set_classifiers(&mut maker, &args).unwrap();
+
+            assert!(maker.others.is_empty());
+        }
+
+        // When there is one other_candidate and no other_minimum_count
+        {
+            let mut maker = PasswordMaker::default();
+            let args = Cli {
+                other_candidates: Some(vec![OsString::from("😀👨‍👩‍👦😂")]),
+                ..Default::default()
+            };
+
+            set_classifiers(&mut maker, &args).unwrap();
+
+            assert_eq!(maker.others.len(), 1);
+            assert_eq!(maker.others[0].candidates, vec!["😀", "👨‍👩‍👦", "😂"]);
+            assert_eq!(maker.others[0].minimum_count, 0);
+        }
+
+        // When there are two other_candidates and no other_minimum_count
+        {
+            let mut maker = PasswordMaker::default();
+            let args = Cli {
+                other_candidates: Some(vec![OsString::from("😀👨‍👩‍👦😂"), OsString::from("あいう")]),
+                ..Default::default()
+            };
+
+            set_classifiers(&mut maker, &args).unwrap();
+
+            assert_eq!(maker.others.len(), 2);
+            assert_eq!(maker.others[0].candidates, vec!["😀", "👨‍👩‍👦", "😂"]);
+            assert_eq!(maker.others[0].minimum_count, 0);
+
+            assert_eq!(maker.others[1].candidates, vec!["あ", "い", "う"]);
+            assert_eq!(maker.others[1].minimum_count, 0);
+        }
+
+        // When there are two other_candidates and one other_minimum_count
+        {
+            let mut maker = PasswordMaker::default();
+            let args = Cli {
+                other_candidates: Some(vec![OsString::from("😀👨‍👩‍👦😂"), OsString::from("あいう")]),
+                other_minimum_count: Some(vec![6]),
+                ..Default::default()
+            };
+
+            set_classifiers(&mut maker, &args).unwrap();
+
+            assert_eq!(maker.others.len(), 2);
+            assert_eq!(maker.others[0].candidates, vec!["😀", "👨‍👩‍👦", "😂"]);
+            assert_eq!(maker.others[0].minimum_count, 6);
+
+            assert_eq!(maker.others[1].candidates, vec!["あ", "い", "う"]);
+            assert_eq!(maker.others[1].minimum_count, 0);
+        }
+
+        // When there are two other_candidates and two other_minimum_counts
+        {
+            let mut maker = PasswordMaker::default();
+            let args = Cli {
+                other_candidates: Some(vec![OsString::from("😀👨‍👩‍👦😂"), OsString::from("あいう")]),
+                other_minimum_count: Some(vec![1, 2]),
+                ..Default::default()
+            };
+
+            set_classifiers(&mut maker, &args).unwrap();
+
+            assert_eq!(maker.others.len(), 2);
+            assert_eq!(maker.others[0].candidates, vec!["😀", "👨‍👩‍👦", "😂"]);
+            assert_eq!(maker.others[0].minimum_count, 1);
+
+            assert_eq!(maker.others[1].candidates, vec!["あ", "い", "う"]);
+            assert_eq!(maker.others[1].minimum_count, 2);
+        }
+
+        // When there are two other_candidates and three other_minimum_counts
+        {
+            let mut maker = PasswordMaker::default();
+            let args = Cli {
+                other_candidates: Some(vec![OsString::from("😀👨‍👩‍👦😂"), OsString::from("あいう")]),
+                other_minimum_count: Some(vec![1, 2, 3]),
+                ..Default::default()
+            };
+
+            set_classifiers(&mut maker, &args).unwrap();
+
+            assert_eq!(maker.others.len(), 3);
+            assert_eq!(maker.others[0].candidates, vec!["😀", "👨‍👩‍👦", "😂"]);
+            assert_eq!(maker.others[0].minimum_count, 1);
+
+            assert_eq!(maker.others[1].candidates, vec!["あ", "い", "う"]);
+            assert_eq!(maker.others[1].minimum_count, 2);
+
+            assert_eq!(maker.others[2].candidates, Vec::<String>::new());
+            assert_eq!(maker.others[2].minimum_count, 3);
+        }
+    }
+
+    #[test]
+    fn password_maker_try_from_cli() {
+        let args = Cli {
+            length: 20,
+            max_length_limit: 100,
+            uppercase_candidates: OsString::from("ABC"),
+            uppercase_minimum_count: 2,
+            lowercase_candidates: OsString::from("abc"),
+            lowercase_minimum_count: 3,
+            number_candidates: OsString::from("123"),
+            number_minimum_count: 4,
+            symbol_candidates: OsString::from("!@#"),
+            symbol_minimum_count: 5,
+            other_candidates: Some(vec![OsString::from("😀👨‍👩‍👦😂")]),
+            other_minimum_count: Some(vec![6]),
+            raw_sample: true,
+            ..Default::default()
+        };
+
+        let maker = PasswordMaker::try_from(&args).unwrap();
+
+        assert_eq!(maker.length, 20);
+        assert_eq!(maker.length_limit, 100);
+        assert!(maker.raw_sample);
+        assert_eq!(maker.uppercase.candidates, vec!["A", "B", "C"]);
+        assert_eq!(maker.uppercase.minimum_count, 2);
+        assert_eq!(maker.lowercase.candidates, vec!["a", "b", "c"]);
+        assert_eq!(maker.lowercase.minimum_count, 3);
+        assert_eq!(maker.number.candidates, vec!["1", "2", "3"]);
+        assert_eq!(maker.number.minimum_count, 4);
+        assert_eq!(maker.symbol.candidates, vec!["!", "@", "#"]);
         assert_eq!(maker.symbol.minimum_count, 5);
-        assert_eq!(maker.others.len(), 2);
+        assert_eq!(maker.others.len(), 1);
         assert_eq!(maker.others[0].candidates, vec!["😀", "👨‍👩‍👦", "😂"]);
         assert_eq!(maker.others[0].minimum_count, 6);
-        assert_eq!(maker.others[1].candidates, vec!["あ", "い", "う"]);
-        assert_eq!(maker.others[1].minimum_count, 7);
+
+        // Decoding errors are returned
+        let args = Cli {
+            encoding: "invalid".to_string(),
+            ..Default::default()
+        };
+        assert!(PasswordMaker::try_from(&args).is_err());
+    }
+
+    #[test]
+    fn generate_passwords_control_chars() {
+        // A tab pasted into the candidates is rejected by default
+        let args = Cli {
+            other_candidates: Some(vec![OsString::from("a\tb")]),
+            other_minimum_count: Some(vec![0]),
+            ..Default::default()
+        };
+        let error = generate_passwords(&args, &mut 0).unwrap_err();
+        assert!(error.contains("control characters: U+0009"));
+
+        // Allowed explicitly
+        let args = Cli {
+            allow_control_chars: true,
+            ..args
+        };
+        assert!(generate_passwords(&args, &mut 0).is_ok());
+    }
+
+    #[test]
+    fn generate_passwords_apple_rules() {
+        let args = Cli {
+            count: 10,
+            apple_rules: Some("required: lower; required: digit; maxlength: 10;".to_string()),
+            ..Default::default()
+        };
+
+        let passwords = generate_passwords(&args, &mut 0).unwrap();
+        assert_eq!(passwords.len(), 10);
+        for password in passwords {
+            assert_eq!(password.len(), 10);
+            assert!(password.chars().any(|c| c.is_ascii_lowercase()));
+            assert!(password.chars().any(|c| c.is_ascii_digit()));
+            assert!(password
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+        }
+
+        // Invalid rules
+        let args = Cli {
+            apple_rules: Some("required: nothing".to_string()),
+            ..Default::default()
+        };
+        assert!(generate_passwords(&args, &mut 0).is_err());
     }
 
     #[test]
-    #[cfg(unix)]
-    fn set_classifiers_shift_jis() {
-        let mut maker = PasswordMaker::default();
+    fn generate_passwords_pin() {
         let args = Cli {
-            // Shift_JIS for "あいうえお"
-            uppercase_candidates: OsString::from_vec(vec![
-                0x82, 0xA0, 0x82, 0xA2, 0x82, 0xA4, 0x82, 0xA6, 0x82, 0xA8,
-            ]),
+            count: 10,
+            pin: Some(6),
+            no_leading_zero: true,
+            ..Default::default()
+        };
 
-            // Shift_JIS for "アイウエオ"
-            lowercase_candidates: OsString::from_vec(vec![
-                0x83, 0x41, 0x83, 0x43, 0x83, 0x45, 0x83, 0x47, 0x83, 0x49,
-            ]),
+        let passwords = generate_passwords(&args, &mut 0).unwrap();
+        assert_eq!(passwords.len(), 10);
+        for password in passwords {
+            assert_eq!(password.len(), 6);
+            assert!(password.chars().all(|c| c.is_ascii_digit()));
+            assert!(!password.starts_with('0'));
+        }
 
-            // Shift_JIS for "ｱｲｳｴｵ"
-            number_candidates: OsString::from_vec(vec![0xB1, 0xB2, 0xB3, 0xB4, 0xB5]),
+        // The PIN cannot be combined with the length
+        assert!(Cli::try_parse_from(["mkpw", "--pin", "4", "--length", "8"]).is_err());
+    }
 
-            // Shift_JIS for "ｧｨｩｪｫ"
-            symbol_candidates: OsString::from_vec(vec![0xA7, 0xA8, 0xA9, 0xAA, 0xAB]),
+    #[test]
+    fn preset() {
+        let preset = |arguments: &[&str]| {
+            let matches = Cli::command().get_matches_from([&["mkpw"], arguments].concat());
+            let mut args = Cli::from_arg_matches(&matches).unwrap();
+            apply_preset(&mut args, &matches).map(|_| args)
+        };
 
-            // Shift_JIS for "ａｉｕｅｏ" and "安以宇衣於"
-            other_candidates: Some(vec![
-                OsString::from_vec(vec![
-                    0x82, 0x81, 0x82, 0x89, 0x82, 0x95, 0x82, 0x85, 0x82, 0x8f,
-                ]),
-                OsString::from_vec(vec![
-                    0x88, 0xC0, 0x88, 0xC8, 0x89, 0x46, 0x88, 0xDF, 0x89, 0x97,
-                ]),
-            ]),
-            encoding: "shift_jis".to_string(),
+        let args = preset(&["--preset", "strong", "--count", "10"]).unwrap();
+        for password in generate_passwords(&args, &mut 0).unwrap() {
+            assert_eq!(password.len(), 24);
+            assert!(
+                !password.contains(['i', 'l', '1', 'o', '0', 'O']),
+                "{}",
+                password
+            );
+        }
+
+        let args = preset(&["--preset", "pin", "--count", "10"]).unwrap();
+        for password in generate_passwords(&args, &mut 0).unwrap() {
+            assert_eq!(password.len(), 6);
+            assert!(password.chars().all(|c| c.is_ascii_digit()), "{}", password);
+        }
+
+        let args = preset(&["--preset", "wifi", "--count", "10"]).unwrap();
+        for password in generate_passwords(&args, &mut 0).unwrap() {
+            assert_eq!(password.len(), 20);
+            assert!(!password.contains(['"', '\'', '`', '\\']), "{}", password);
+        }
+
+        // The options given explicitly take precedence over the preset
+        let args = preset(&[
+            "--preset",
+            "pin",
+            "--length",
+            "4",
+            "--number-candidates",
+            "12",
+        ])
+        .unwrap();
+        let passwords = generate_passwords(&args, &mut 0).unwrap();
+        assert_eq!(passwords[0].len(), 4);
+        assert!(passwords[0].chars().all(|c| "12".contains(c)));
+
+        // The options of passphrases need "--preset memorable"
+        let error = preset(&["--preset", "strong", "--words", "4"]).unwrap_err();
+        assert!(error.contains("are options of passphrases"));
+        assert!(Cli::try_parse_from(["mkpw", "--wordlist", "words.txt"]).is_err());
+        assert!(Cli::try_parse_from(["mkpw", "--preset", "pin", "--pin", "4"]).is_err());
+    }
+
+    #[test]
+    fn preset_memorable() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, "correct\nhorse\nbattery\nstaple\n").unwrap();
+        let wordlist = file.path().to_str().unwrap();
+
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--preset", "memorable", "--wordlist", wordlist])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        let words: Vec<&str> = stdout.trim_end().split('-').collect();
+        assert_eq!(words.len(), 6);
+        assert!(words
+            .iter()
+            .all(|w| ["correct", "horse", "battery", "staple"].contains(w)));
+
+        // A wordlist is required
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd.args(["--preset", "memorable"]).assert().failure();
+        let stderr = String::from_utf8_lossy(&assert.get_output().stderr).to_string();
+        assert!(stderr.contains("no wordlist is given"));
+    }
+
+    #[test]
+    fn generate_passwords_pattern() {
+        let args = Cli {
+            count: 10,
+            pattern: Some("Aa0!-\\a".to_string()),
+            symbol_candidates: OsString::from("#"),
             ..Default::default()
         };
 
-        set_classifiers(&mut maker, &args).unwrap();
+        let passwords = generate_passwords(&args, &mut 0).unwrap();
+        assert_eq!(passwords.len(), 10);
+        for password in passwords {
+            let characters: Vec<char> = password.chars().collect();
+            assert_eq!(characters.len(), 6, "{}", password);
+            assert!(characters[0].is_ascii_uppercase());
+            assert!(characters[1].is_ascii_lowercase());
+            assert!(characters[2].is_ascii_digit());
+            assert_eq!(&characters[3..], ['#', '-', 'a']);
+        }
 
-        assert_eq!(
-            maker.uppercase.candidates,
-            vec!["あ", "い", "う", "え", "お"]
+        // The pattern cannot be combined with the minimum counts
+        assert!(
+            Cli::try_parse_from(["mkpw", "--pattern", "aaa", "--symbol-minimum-count", "1"])
+                .is_err()
         );
+    }
 
-        assert_eq!(
-            maker.lowercase.candidates,
-            vec!["ア", "イ", "ウ", "エ", "オ"]
-        );
+    #[test]
+    fn expand_ranges() {
+        assert_eq!(super::expand_ranges("A-Z"), "ABCDEFGHIJKLMNOPQRSTUVWXYZ");
+        assert_eq!(super::expand_ranges("0-9"), "0123456789");
+        assert_eq!(super::expand_ranges("a-cx-z"), "abcxyz");
+        assert_eq!(super::expand_ranges("あ-お"), "あぃいぅうぇえぉお");
 
-        assert_eq!(maker.number.candidates, vec!["ｱ", "ｲ", "ｳ", "ｴ", "ｵ"]);
+        // Literal hyphens
+        assert_eq!(super::expand_ranges("a-c-"), "abc-");
+        assert_eq!(super::expand_ranges("-a-c"), "-abc");
+        assert_eq!(super::expand_ranges("a\\-c"), "a-c");
+        assert_eq!(super::expand_ranges("\\\\-a"), "\\-a");
 
-        assert_eq!(maker.symbol.candidates, vec!["ｧ", "ｨ", "ｩ", "ｪ", "ｫ"]);
+        // Not ranges
+        assert_eq!(super::expand_ranges("z-a"), "z-a");
+        assert_eq!(super::expand_ranges("a-Z"), "a-Z");
+        assert_eq!(super::expand_ranges("!-~"), "!-~");
+        assert_eq!(super::expand_ranges("a-😀"), "a-😀");
+        assert_eq!(super::expand_ranges("あ-😀"), "あ-😀");
+        assert_eq!(super::expand_ranges("👨‍👩‍👦-😀"), "👨‍👩‍👦-😀");
+        assert_eq!(super::expand_ranges("[\\]"), "[\\]");
+        // Latin-1 Supplement and Latin Extended-A
+        assert_eq!(super::expand_ranges("ÿ-Ā"), "ÿ-Ā");
+        // Hiragana and Katakana
+        assert_eq!(super::expand_ranges("ん-ア"), "ん-ア");
 
-        assert_eq!(maker.others.len(), 2);
-        assert_eq!(
-            maker.others[0].candidates,
-            vec!["ａ", "ｉ", "ｕ", "ｅ", "ｏ"]
-        );
+        // The unassigned code points and the combining marks are skipped
+        // "Ͱ-Ϳ" has the unassigned U+0378 and U+0379
+        let greek: String = ('\u{0370}'..='\u{037F}')
+            .filter(|c| !['\u{0378}', '\u{0379}'].contains(c))
+            .collect();
+        assert_eq!(super::expand_ranges("\u{0370}-\u{037F}"), greek);
         assert_eq!(
-            maker.others[1].candidates,
-            vec!["安", "以", "宇", "衣", "於"]
+            super::expand_ranges("\u{05BE}-\u{05C3}"),
+            "\u{05BE}\u{05C0}\u{05C3}"
         );
+
+        // The default candidates have no ranges
+        let default = Cli::default();
+        for candidates in [
+            &default.uppercase_candidates,
+            &default.lowercase_candidates,
+            &default.number_candidates,
+            &default.symbol_candidates,
+        ] {
+            let candidates = candidates.to_str().unwrap();
+            assert_eq!(super::expand_ranges(candidates), candidates);
+        }
+
+        // The candidate options are expanded
+        let args = Cli {
+            uppercase_candidates: OsString::from("A-C"),
+            number_candidates: OsString::from("0-2"),
+            ..Default::default()
+        };
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        assert_eq!(maker.uppercase.candidates, ["A", "B", "C"]);
+        assert_eq!(maker.number.candidates, ["0", "1", "2"]);
     }
 
     #[test]
-    fn set_classifiers_empty() {
-        // When all candidates are empty, and everything else is default
-        {
-            let mut maker = PasswordMaker::default();
-            let args = Cli {
-                uppercase_candidates: OsString::from(""),
-                lowercase_candidates: OsString::from(""),
-                number_candidates: OsString::from(""),
-                symbol_candidates: OsString::from(""),
-                ..Default::default()
-            };
+    fn min_entropy() {
+        // 128 / log2(94) = 19.5...
+        let args = Cli {
+            min_entropy: Some(128),
+            ..Default::default()
+        };
+        assert_eq!(PasswordMaker::try_from(&args).unwrap().length, 20);
+        let passwords = generate_passwords(&args, &mut 0).unwrap();
+        assert_eq!(passwords[0].len(), 20);
 
-            set_classifiers(&mut maker, &args).unwrap();
+        // The pool after the filters is used: 128 / log2(10 - 2) = 42.6...
+        let args = Cli {
+            min_entropy: Some(128),
+            uppercase_candidates: OsString::from(""),
+            lowercase_candidates: OsString::from(""),
+            symbol_candidates: OsString::from(""),
+            exclude_similar: true,
+            ..Default::default()
+        };
+        assert_eq!(PasswordMaker::try_from(&args).unwrap().length, 43);
 
-            assert!(maker.uppercase.candidates.is_empty());
-            assert_eq!(maker.uppercase.minimum_count, 0);
-            assert!(maker.lowercase.candidates.is_empty());
-            assert_eq!(maker.lowercase.minimum_count, 0);
-            assert!(maker.number.candidates.is_empty());
-            assert_eq!(maker.number.minimum_count, 0);
-            assert!(maker.symbol.candidates.is_empty());
-            assert_eq!(maker.symbol.minimum_count, 0);
-            assert!(maker.others.is_empty());
+        // The length is not shorter than the minimum counts
+        let args = Cli {
+            min_entropy: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(PasswordMaker::try_from(&args).unwrap().length, 4);
+
+        // No candidates
+        let args = Cli {
+            min_entropy: Some(80),
+            uppercase_candidates: OsString::from(""),
+            lowercase_candidates: OsString::from(""),
+            number_candidates: OsString::from(""),
+            symbol_candidates: OsString::from(""),
+            ..Default::default()
+        };
+        assert!(PasswordMaker::try_from(&args).is_err());
+
+        assert!(Cli::try_parse_from(["mkpw", "--min-entropy", "80", "--length", "8"]).is_err());
+    }
+
+    #[test]
+    fn show_config() {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--show-config", "--length", "20", "--exclude-similar"])
+            .assert()
+            .success();
+        let output = assert.get_output();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim_end().len(), 20);
+        let stderr = String::from_utf8(output.stderr.clone()).unwrap();
+        assert!(stderr.contains("length: 20"), "{}", stderr);
+        assert!(stderr.contains("exclude_similar"), "{}", stderr);
+        for name in ["lowercase", "uppercase", "number", "symbol", "others"] {
+            assert!(stderr.contains(name), "{}", stderr);
         }
+    }
 
-        // When all candidates and minimum counts are empty, and everything else is default
-        {
-            let mut maker = PasswordMaker::default();
+    #[test]
+    fn count_stdin() {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .arg("--count-stdin")
+            .write_stdin("3\n")
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        assert_eq!(stdout.lines().count(), 3);
 
-            let args = Cli {
-                uppercase_candidates: OsString::from(""),
-                uppercase_minimum_count: 1,
-                lowercase_candidates: OsString::from(""),
-                lowercase_minimum_count: 2,
-                number_candidates: OsString::from(""),
-                number_minimum_count: 3,
-                symbol_candidates: OsString::from(""),
-                symbol_minimum_count: 4,
+        // The input is not a number in the range
+        for input in ["", "three", "0", "-1", "1000001", "1 2"] {
+            let mut args = Cli {
+                count_stdin: true,
                 ..Default::default()
             };
+            let error = read_stdin_count(&mut args, &mut input.as_bytes()).unwrap_err();
+            assert!(error.contains("from 1 to 1000000"), "{}", error);
+        }
 
-            set_classifiers(&mut maker, &args).unwrap();
+        // Standard input cannot be read for both the count and the candidates
+        assert!(
+            Cli::try_parse_from(["mkpw", "--count-stdin", "--candidates-stdin", "lowercase"])
+                .is_err()
+        );
+        assert!(Cli::try_parse_from(["mkpw", "--count-stdin", "--count", "2"]).is_err());
+    }
 
-            assert!(maker.uppercase.candidates.is_empty());
-            assert_eq!(maker.uppercase.minimum_count, 0);
-            assert!(maker.lowercase.candidates.is_empty());
-            assert_eq!(maker.lowercase.minimum_count, 0);
-            assert!(maker.number.candidates.is_empty());
-            assert_eq!(maker.number.minimum_count, 0);
-            assert!(maker.symbol.candidates.is_empty());
-            assert_eq!(maker.symbol.minimum_count, 0);
-            assert!(maker.others.is_empty());
+    #[test]
+    fn candidates_stdin() {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args([
+                "--candidates-stdin",
+                "lowercase",
+                "--uppercase-candidates",
+                "",
+                "--number-candidates",
+                "",
+                "--symbol-candidates",
+                "",
+                "--length",
+                "100",
+                "--count",
+                "3",
+            ])
+            .write_stdin("xyz\n")
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        assert_eq!(stdout.lines().count(), 3);
+        for password in stdout.lines() {
+            assert_eq!(password.len(), 100);
+            assert!(password.chars().all(|c| "xyz".contains(c)), "{}", password);
         }
+
+        // The input is decoded with the encoding, and other characters are added as a new group
+        let mut args = Cli {
+            candidates_stdin: Some(CandidateClass::Other),
+            encoding: "shift_jis".to_string(),
+            other_candidates: Some(vec![OsString::from("ab")]),
+            other_minimum_count: Some(vec![0, 1]),
+            ..Default::default()
+        };
+        read_stdin_candidates(&mut args, &mut &[0x82, 0xA0, 0x82, 0xA2, b'\r', b'\n'][..]).unwrap();
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        assert_eq!(maker.others[1].candidates, ["あ", "い"]);
+        assert_eq!(maker.others[1].minimum_count, 1);
+
+        // The candidates read for a type disable the conversion to it
+        let mut args = Cli {
+            candidates_stdin: Some(CandidateClass::Uppercase),
+            uppercase_from_lowercase: true,
+            ..Default::default()
+        };
+        read_stdin_candidates(&mut args, &mut "XY".as_bytes()).unwrap();
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        assert_eq!(maker.uppercase.candidates, ["X", "Y"]);
     }
 
     #[test]
-    fn set_classifiers_err() {
-        let mut maker = PasswordMaker::default();
+    fn exclude() {
         let args = Cli {
-            uppercase_candidates: OsString::from("ABC"),
-            encoding: "invalid".to_string(),
+            length: 500,
+            exclude: Some(OsString::from("\"\\")),
             ..Default::default()
         };
+        let passwords = generate_passwords(&args, &mut 0).unwrap();
+        assert_eq!(passwords[0].len(), 500);
+        assert!(!passwords[0].contains(['"', '\\']), "{}", passwords[0]);
 
-        let result = set_classifiers(&mut maker, &args);
-        assert_eq!(result, Err("Unsupported encoding: invalid".to_string()));
+        // The minimum count of a type whose candidates are all excluded is ignored
+        let args = Cli {
+            number_candidates: OsString::from("01"),
+            number_minimum_count: 3,
+            exclude: Some(OsString::from("10")),
+            ..Default::default()
+        };
+        let passwords = generate_passwords(&args, &mut 0).unwrap();
+        assert!(!passwords[0].contains(['0', '1']), "{}", passwords[0]);
     }
 
     #[test]
-    fn set_classifiers_omit_other_minimum_count() {
-        // When there are no other_candidates
-        {
-            let mut maker = PasswordMaker::default();
-            let args = Cli {
-                other_candidates: None,
-                ..Default::default()
-            };
+    #[cfg(feature = "clipboard")]
+    fn output_passwords_to_clipboard() {
+        // When testing in an environment where DISPLAY is not set,
+        // arboard::Clipboard::new() will fail, so test in an environment where DISPLAY is set.
+        // Also, since it copies to the clipboard, running this will change the clipboard contents.
+        // This is a major feature, so do not exclude it with ignore.
 
-            set_classifiers(&mut maker, &args).unwrap();
+        let args = Cli {
+            clipboard: true,
+            ..Default::default()
+        };
 
-            assert!(maker.others.is_empty());
-        }
+        let text = "password1\npassword2\0password3";
+        output_passwords(text, &args).unwrap();
 
-        // When there is one other_candidate and no other_minimum_count
-        {
-            let mut maker = PasswordMaker::default();
-            let args = Cli {
-                other_candidates: Some(vec![OsString::from("😀👨‍👩‍👦😂")]),
-                ..Default::default()
-            };
+        let mut clipboard = Clipboard::new().unwrap();
+        let clipboard_text = clipboard.get_text().unwrap();
+        assert_eq!(clipboard_text, text);
+    }
+
+    #[test]
+    #[cfg(feature = "clipboard")]
+    fn output_passwords_to_clipboard_watch() {
+        // Requires a clipboard like output_passwords_to_clipboard.
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let args = Cli {
+            clipboard: true,
+            watch: Some(60),
+            ..Default::default()
+        };
+        let sentinel = "not a password";
+        Clipboard::new().unwrap().set_text(sentinel).unwrap();
+
+        let stop = AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            let watcher = scope.spawn(|| {
+                watch_clipboard(
+                    &args,
+                    std::time::Duration::from_secs(60),
+                    &mut 0,
+                    None,
+                    &stop,
+                )
+            });
+
+            // The first cycle copies a password without waiting for the interval
+            let mut copied = sentinel.to_string();
+            for _ in 0..50 {
+                copied = Clipboard::new().unwrap().get_text().unwrap_or_default();
+                if copied != sentinel {
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+            assert_eq!(copied.chars().count(), 16, "{}", copied);
+
+            // Stopping clears the clipboard
+            stop.store(true, Ordering::SeqCst);
+            watcher.join().unwrap().unwrap();
+        });
+        assert_eq!(Clipboard::new().unwrap().get_text().unwrap_or_default(), "");
+    }
+
+    #[test]
+    #[cfg(feature = "clipboard")]
+    fn output_passwords_to_clipboard_watch_error() {
+        // Requires a clipboard like output_passwords_to_clipboard.
+        use std::sync::atomic::AtomicBool;
+
+        let directory = tempfile::tempdir().unwrap();
+        let args = Cli {
+            clipboard: true,
+            watch: Some(60),
+            deny_wordlist: Some(directory.path().join("missing.txt")),
+            ..Default::default()
+        };
+        Clipboard::new()
+            .unwrap()
+            .set_text("not a password")
+            .unwrap();
+
+        // A failed cycle clears the clipboard and returns the error
+        let error = watch_clipboard(
+            &args,
+            std::time::Duration::from_secs(60),
+            &mut 0,
+            None,
+            &AtomicBool::new(false),
+        )
+        .unwrap_err();
+        assert!(
+            error.starts_with("Failed to read the deny wordlist"),
+            "{}",
+            error
+        );
+        assert_eq!(Clipboard::new().unwrap().get_text().unwrap_or_default(), "");
+    }
+
+    #[test]
+    fn watch_args() {
+        assert!(Cli::try_parse_from(["mkpw", "--clipboard", "--watch", "5"]).is_ok());
+        // "--watch" only updates the clipboard
+        assert!(Cli::try_parse_from(["mkpw", "--watch", "5"]).is_err());
+        assert!(Cli::try_parse_from(["mkpw", "--clipboard", "--watch", "0"]).is_err());
+        // A seed would copy the same passwords in every cycle
+        assert!(
+            Cli::try_parse_from(["mkpw", "--clipboard", "--watch", "5", "--seed", "1"]).is_err()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "clipboard")]
+    fn utc_timestamp() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let at = |seconds| super::utc_timestamp(UNIX_EPOCH + Duration::from_secs(seconds));
+        assert_eq!(at(0), "1970-01-01T00:00:00Z");
+        assert_eq!(at(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(at(1_735_689_599), "2024-12-31T23:59:59Z");
+        assert_eq!(at(4_107_542_400), "2100-03-01T00:00:00Z");
+        assert_eq!(
+            super::utc_timestamp(UNIX_EPOCH - Duration::from_secs(1)),
+            "1970-01-01T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn utc_date() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let at = |seconds| super::utc_date(UNIX_EPOCH + Duration::from_secs(seconds));
+        assert_eq!(at(0), "1970-01-01");
+        assert_eq!(at(951_868_799), "2000-02-29");
+        assert_eq!(at(1_735_689_600), "2025-01-01");
+    }
+
+    #[test]
+    #[cfg(feature = "clipboard")]
+    fn output_passwords_to_clipboard_sensitive() {
+        // Requires a clipboard like output_passwords_to_clipboard.
+        // The history hint cannot be read back as text, so check that the text is still copied with it.
+        let args = Cli {
+            clipboard: true,
+            clipboard_sensitive: true,
+            ..Default::default()
+        };
+
+        let text = "password1\npassword2\0password3";
+        output_passwords(text, &args).unwrap();
+
+        let mut clipboard = Clipboard::new().unwrap();
+        let clipboard_text = clipboard.get_text().unwrap();
+        assert_eq!(clipboard_text, text);
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "clipboard",
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))]
+    fn output_passwords_to_clipboard_primary() {
+        // Requires a clipboard like output_passwords_to_clipboard.
+        use arboard::GetExtLinux;
+
+        let args = Cli {
+            clipboard: true,
+            clipboard_selection: ClipboardSelection::Primary,
+            ..Default::default()
+        };
+
+        let text = "password1\npassword2";
+        output_passwords(text, &args).unwrap();
+
+        let mut clipboard = Clipboard::new().unwrap();
+        let primary_text = clipboard
+            .get()
+            .clipboard(LinuxClipboardKind::Primary)
+            .text()
+            .unwrap();
+        assert_eq!(primary_text, text);
+    }
+
+    #[test]
+    fn clipboard_selection() {
+        let args = Cli::try_parse_from(["mkpw", "--clipboard", "--clipboard-selection", "primary"])
+            .unwrap();
+        assert_eq!(args.clipboard_selection, ClipboardSelection::Primary);
+        assert_eq!(
+            Cli::try_parse_from(["mkpw", "--clipboard"])
+                .unwrap()
+                .clipboard_selection,
+            ClipboardSelection::Clipboard
+        );
 
-            set_classifiers(&mut maker, &args).unwrap();
+        // The selection needs "--clipboard"
+        assert!(Cli::try_parse_from(["mkpw", "--clipboard-selection", "primary"]).is_err());
+        assert!(
+            Cli::try_parse_from(["mkpw", "--clipboard", "--clipboard-selection", "secondary"])
+                .is_err()
+        );
+    }
 
-            assert_eq!(maker.others.len(), 1);
-            assert_eq!(maker.others[0].candidates, vec!["😀", "👨‍👩‍👦", "😂"]);
-            assert_eq!(maker.others[0].minimum_count, 0);
-        }
+    #[test]
+    #[cfg(feature = "clipboard")]
+    fn output_passwords_to_clipboard_tee() {
+        // Requires a clipboard like output_passwords_to_clipboard.
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--tee", "--clipboard", "--count", "2"])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        assert_eq!(stdout.lines().count(), 2);
 
-        // When there are two other_candidates and no other_minimum_count
-        {
-            let mut maker = PasswordMaker::default();
-            let args = Cli {
-                other_candidates: Some(vec![OsString::from("😀👨‍👩‍👦😂"), OsString::from("あいう")]),
-                ..Default::default()
-            };
+        let mut clipboard = Clipboard::new().unwrap();
+        let clipboard_text = clipboard.get_text().unwrap();
+        assert_eq!(clipboard_text, stdout);
+    }
 
-            set_classifiers(&mut maker, &args).unwrap();
+    #[test]
+    #[cfg(not(feature = "clipboard"))]
+    fn clipboard_disabled() {
+        // Built with "--no-default-features", "--clipboard" is rejected before generating the passwords
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd.arg("--clipboard").assert().failure();
+        let output = assert.get_output();
+        assert!(output.stdout.is_empty());
+        assert!(String::from_utf8_lossy(&output.stderr).contains(CLIPBOARD_DISABLED));
 
-            assert_eq!(maker.others.len(), 2);
-            assert_eq!(maker.others[0].candidates, vec!["😀", "👨‍👩‍👦", "😂"]);
-            assert_eq!(maker.others[0].minimum_count, 0);
+        let args = Cli {
+            clipboard: true,
+            ..Default::default()
+        };
+        assert!(output_passwords("password", &args)
+            .unwrap_err()
+            .contains(CLIPBOARD_DISABLED));
+    }
 
-            assert_eq!(maker.others[1].candidates, vec!["あ", "い", "う"]);
-            assert_eq!(maker.others[1].minimum_count, 0);
-        }
+    #[test]
+    fn output_passwords_tee_errors() {
+        let dir = tempfile::tempdir().unwrap();
 
-        // When there are two other_candidates and one other_minimum_count
-        {
-            let mut maker = PasswordMaker::default();
-            let args = Cli {
-                other_candidates: Some(vec![OsString::from("😀👨‍👩‍👦😂"), OsString::from("あいう")]),
-                other_minimum_count: Some(vec![6]),
-                ..Default::default()
-            };
+        // The file cannot be written, but the passwords are still output to standard output
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .arg("--tee")
+            .arg("--output")
+            .arg(dir.path())
+            .assert()
+            .failure();
+        let output = assert.get_output();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).lines().count(), 1);
+        assert!(String::from_utf8_lossy(&output.stderr)
+            .contains(&format!("Failed to write to {}", dir.path().display())));
 
-            set_classifiers(&mut maker, &args).unwrap();
+        // Both the file and standard output get the passwords
+        let path = dir.path().join("passwords.txt");
+        let args = Cli {
+            output: Some(path.clone()),
+            tee: true,
+            ..Default::default()
+        };
+        output_passwords("password1\npassword2\n", &args).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "password1\npassword2\n"
+        );
+    }
 
-            assert_eq!(maker.others.len(), 2);
-            assert_eq!(maker.others[0].candidates, vec!["😀", "👨‍👩‍👦", "😂"]);
-            assert_eq!(maker.others[0].minimum_count, 6);
+    #[test]
+    fn group_size() {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--length", "10", "--group-size", "4", "--count", "3"])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        for line in stdout.lines() {
+            // The separators do not count toward the length
+            let groups: Vec<&str> = line.split('-').collect();
+            assert!(groups.len() >= 3, "{}", line);
+            assert_eq!(groups.concat().len() + groups.len() - 1, line.len());
+        }
 
-            assert_eq!(maker.others[1].candidates, vec!["あ", "い", "う"]);
-            assert_eq!(maker.others[1].minimum_count, 0);
+        // Emoji are not split, and the separator is given
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args([
+                "--length",
+                "6",
+                "--uppercase-minimum-count",
+                "0",
+                "--lowercase-minimum-count",
+                "0",
+                "--number-minimum-count",
+                "0",
+                "--symbol-minimum-count",
+                "0",
+                "--uppercase-candidates",
+                "",
+                "--lowercase-candidates",
+                "",
+                "--number-candidates",
+                "",
+                "--symbol-candidates",
+                "",
+                "--other-candidates",
+                "👨‍👩‍👦🇯🇵",
+                "--group-size",
+                "2",
+                "--group-separator",
+                " ",
+            ])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        let groups: Vec<&str> = stdout.trim_end().split(' ').collect();
+        assert_eq!(groups.len(), 3);
+        for group in groups {
+            assert_eq!(group.graphemes(true).count(), 2);
         }
 
-        // When there are two other_candidates and two other_minimum_counts
-        {
-            let mut maker = PasswordMaker::default();
-            let args = Cli {
-                other_candidates: Some(vec![OsString::from("😀👨‍👩‍👦😂"), OsString::from("あいう")]),
-                other_minimum_count: Some(vec![1, 2]),
-                ..Default::default()
-            };
+        // JSON is not grouped
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args([
+                "--symbol-candidates",
+                "!",
+                "--format",
+                "json",
+                "--group-size",
+                "4",
+            ])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        let passwords = json_passwords(&stdout);
+        assert_eq!(passwords[0].len(), 16);
 
-            set_classifiers(&mut maker, &args).unwrap();
+        // The separator must survive the encoding
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        cmd.args([
+            "--group-size",
+            "4",
+            "--group-separator",
+            "😀",
+            "--encoding",
+            "shift_jis",
+        ]);
+        let assert = cmd.assert().failure();
+        let stderr = String::from_utf8_lossy(&assert.get_output().stderr).to_string();
+        assert!(
+            stderr.contains("cannot be represented in shift_jis"),
+            "{}",
+            stderr
+        );
 
-            assert_eq!(maker.others.len(), 2);
-            assert_eq!(maker.others[0].candidates, vec!["😀", "👨‍👩‍👦", "😂"]);
-            assert_eq!(maker.others[0].minimum_count, 1);
+        // The group size must be 1 or more
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        cmd.args(["--group-size", "0"]).assert().failure();
+    }
 
-            assert_eq!(maker.others[1].candidates, vec!["あ", "い", "う"]);
-            assert_eq!(maker.others[1].minimum_count, 2);
+    #[test]
+    fn token_base64() {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--token", "base64", "--bytes", "32", "--count", "3"])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        let tokens: Vec<&str> = stdout.lines().collect();
+        assert_eq!(tokens.len(), 3);
+        for token in &tokens {
+            assert_eq!(token.len(), 43);
+            assert!(token
+                .bytes()
+                .all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_'));
         }
+        assert_ne!(tokens[0], tokens[1]);
 
-        // When there are two other_candidates and three other_minimum_counts
-        {
-            let mut maker = PasswordMaker::default();
-            let args = Cli {
-                other_candidates: Some(vec![OsString::from("😀👨‍👩‍👦😂"), OsString::from("あいう")]),
-                other_minimum_count: Some(vec![1, 2, 3]),
-                ..Default::default()
-            };
-
-            set_classifiers(&mut maker, &args).unwrap();
-
-            assert_eq!(maker.others.len(), 3);
-            assert_eq!(maker.others[0].candidates, vec!["😀", "👨‍👩‍👦", "😂"]);
-            assert_eq!(maker.others[0].minimum_count, 1);
+        // The options of the characters are ignored
+        let args = Cli {
+            token: Some(TokenFormat::Base64),
+            bytes: 3,
+            length: 0,
+            uppercase_minimum_count: 100,
+            ..Default::default()
+        };
+        let passwords = generate_passwords(&args, &mut 0).unwrap();
+        assert_eq!(passwords[0].len(), 4);
 
-            assert_eq!(maker.others[1].candidates, vec!["あ", "い", "う"]);
-            assert_eq!(maker.others[1].minimum_count, 2);
+        // The number of bytes must be 1 or more
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        cmd.args(["--token", "base64", "--bytes", "0"])
+            .assert()
+            .failure();
+    }
 
-            assert_eq!(maker.others[2].candidates, Vec::<String>::new());
-            assert_eq!(maker.others[2].minimum_count, 3);
+    #[test]
+    fn token_hex() {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--token", "hex", "--bytes", "16", "--count", "2"])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        let tokens: Vec<&str> = stdout.lines().collect();
+        assert_eq!(tokens.len(), 2);
+        for token in &tokens {
+            assert_eq!(token.len(), 32);
+            assert!(token
+                .chars()
+                .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)));
         }
+        assert_ne!(tokens[0], tokens[1]);
+
+        // The number of bytes must be 1 or more
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        cmd.args(["--token", "hex", "--bytes", "0"])
+            .assert()
+            .failure();
     }
 
     #[test]
-    fn output_passwords_to_clipboard() {
-        // When testing in an environment where DISPLAY is not set,
-        // arboard::Clipboard::new() will fail, so test in an environment where DISPLAY is set.
-        // Also, since it copies to the clipboard, running this will change the clipboard contents.
-        // This is a major feature, so do not exclude it with ignore.
+    fn output_passwords_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("passwords.txt");
 
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .arg("--output")
+            .arg(&path)
+            .args(["--count", "3", "--null"])
+            .assert()
+            .success();
+
+        // The passwords are written only to the file
+        assert!(assert.get_output().stdout.is_empty());
+        let text = std::fs::read(&path).unwrap();
+        assert_eq!(text.len(), 3 * 17);
+        assert_eq!(text.iter().filter(|&&b| b == 0).count(), 3);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // An existing file is overwritten and restricted, with the candidates encoded as specified
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        }
         let args = Cli {
-            clipboard: true,
+            output: Some(path.clone()),
+            encoding: "shift_jis".to_string(),
             ..Default::default()
         };
+        output_passwords("あ\n", &args).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), vec![0x82, 0xA0, b'\n']);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
 
-        let text = "password1\npassword2\0password3";
-        output_passwords(text, &args).unwrap();
-
-        let mut clipboard = Clipboard::new().unwrap();
-        let clipboard_text = clipboard.get_text().unwrap();
-        assert_eq!(clipboard_text, text);
+        // A file in a missing directory is reported as an error
+        let args = Cli {
+            output: Some(dir.path().join("missing").join("passwords.txt")),
+            ..Default::default()
+        };
+        assert!(output_passwords("password\n", &args)
+            .unwrap_err()
+            .starts_with("Failed to write to"));
     }
 
     #[test]
@@ -807,6 +6515,478 @@ mod tests {
         }
     }
 
+    #[test]
+    fn print_phonetic() {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--phonetic", "--length", "3", "--symbol-candidates", ""])
+            .assert()
+            .success();
+        let output = assert.get_output();
+
+        // The password is output to standard output, and the spelled out password to standard error
+        let password = String::from_utf8(output.stdout.clone()).unwrap();
+        let spelled = String::from_utf8(output.stderr.clone()).unwrap();
+        assert_eq!(spelled, phonetic::spell(password.trim_end()) + "\n");
+        assert_eq!(spelled.split_whitespace().count(), 3);
+    }
+
+    #[test]
+    fn exclude_similar() {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--exclude-similar", "--length", "500"])
+            .assert()
+            .success();
+        let password = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+
+        assert_eq!(password.trim_end().len(), 500);
+        assert!(!password.contains(['i', 'l', '1', 'o', '0', 'O']));
+
+        // The minimum counts are met with the remaining characters
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args([
+                "--exclude-similar",
+                "--length",
+                "8",
+                "--count",
+                "50",
+                "--number-candidates",
+                "012",
+                "--number-minimum-count",
+                "4",
+            ])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        for password in stdout.lines() {
+            assert!(!password.contains(['i', 'l', '1', 'o', '0', 'O']));
+            assert!(password.matches('2').count() >= 4);
+        }
+    }
+
+    #[test]
+    fn exclude_similar_scope() {
+        let args = Cli::try_parse_from([
+            "mkpw",
+            "--exclude-similar",
+            "--exclude-similar-scope",
+            "standard-classes-only",
+            "--other-candidates",
+            "0O",
+            "--other-minimum-count",
+            "4",
+        ])
+        .unwrap();
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        assert_eq!(
+            maker.exclude_similar_scope,
+            SimilarScope::StandardClassesOnly
+        );
+        assert!(maker.to_string().contains("standard classes only"));
+        let candidates = maker.candidates();
+        assert!(candidates.contains(&"0".to_string()));
+        assert!(!candidates.contains(&"1".to_string()));
+        assert!(
+            command_line(&args, false).contains("--exclude-similar-scope=standard-classes-only")
+        );
+
+        // The similar characters are removed from all the character types by default
+        let args =
+            Cli::try_parse_from(["mkpw", "--exclude-similar", "--other-candidates", "0O"]).unwrap();
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        assert_eq!(maker.exclude_similar_scope, SimilarScope::AllClasses);
+        assert!(!maker.candidates().contains(&"0".to_string()));
+        assert!(!command_line(&args, false).contains("--exclude-similar-scope"));
+        assert!(Cli::try_parse_from(["mkpw", "--exclude-similar-scope", "others"]).is_err());
+    }
+
+    #[test]
+    fn seed() {
+        let run = |seed: &str| {
+            let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+            let assert = cmd
+                .args(["--seed", seed, "--count", "5"])
+                .assert()
+                .success();
+            String::from_utf8(assert.get_output().stdout.clone()).unwrap()
+        };
+
+        // The same seed generates the same passwords across runs
+        let first = run("42");
+        assert_eq!(first, run("42"));
+        assert_ne!(first, run("43"));
+
+        // The passwords within one run differ
+        let passwords: std::collections::HashSet<_> = first.lines().collect();
+        assert_eq!(passwords.len(), 5);
+    }
+
+    #[test]
+    fn daily() {
+        let run = |secret: &str, date: &str| {
+            let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+            let assert = cmd
+                .args(["--daily", "--secret", secret, "--date", date])
+                .assert()
+                .success();
+            String::from_utf8(assert.get_output().stdout.clone()).unwrap()
+        };
+
+        // The same secret and date generate the same password
+        let first = run("master", "2024-01-01");
+        assert_eq!(first, run("master", "2024-01-01"));
+        // Another date or secret generates another password
+        assert_ne!(first, run("master", "2024-01-02"));
+        assert_ne!(first, run("other", "2024-01-01"));
+
+        // The date is today in UTC by default
+        let args = |date: Option<&str>| Cli {
+            daily: true,
+            secret: Some("master".to_string()),
+            date: date.map(str::to_string),
+            ..Default::default()
+        };
+        let today = super::utc_date(std::time::SystemTime::now());
+        assert_eq!(super::seed(&args(None)), super::seed(&args(Some(&today))));
+        assert_eq!(
+            super::seed(&args(None)),
+            Some(daily::seed("master", &today))
+        );
+
+        // The tokens use the seed as well
+        let token = |date: &str| {
+            let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+            let assert = cmd
+                .args([
+                    "--token", "hex", "--daily", "--secret", "master", "--date", date,
+                ])
+                .assert()
+                .success();
+            assert.get_output().stdout.clone()
+        };
+        assert_eq!(token("2024-01-01"), token("2024-01-01"));
+        assert_ne!(token("2024-01-01"), token("2024-01-02"));
+
+        // The secret is required, and the options cannot be used alone or with "--seed"
+        for case in [
+            &["--daily"][..],
+            &["--secret", "master"],
+            &["--date", "2024-01-01"],
+            &["--daily", "--secret", "master", "--seed", "1"],
+            &["--daily", "--secret", "master", "--date", "2024-02-30"],
+        ] {
+            assert!(
+                Cli::try_parse_from(std::iter::once("mkpw").chain(case.iter().copied())).is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn parallel() {
+        let run = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+            let assert = cmd.args(args).assert().success();
+            assert.get_output().stdout.clone()
+        };
+
+        // The same seed generates the same passwords byte for byte on any number of threads
+        let serial = run(&["--seed", "42", "--count", "100"]);
+        for threads in ["1", "3", "8", "200"] {
+            assert_eq!(
+                run(&["--seed", "42", "--count", "100", "--parallel", threads]),
+                serial
+            );
+        }
+
+        // The rejected passwords are regenerated from the stream of the same index
+        let args = [
+            "--seed",
+            "7",
+            "--count",
+            "50",
+            "--reject-dates",
+            "--total-chars",
+            "500",
+        ];
+        assert_eq!(run(&[&args[..], &["--parallel", "4"]].concat()), run(&args));
+
+        // Without a seed, the passwords differ from each other
+        let stdout = run(&["--count", "100", "--parallel", "4"]);
+        let stdout = String::from_utf8(stdout).unwrap();
+        let passwords: std::collections::HashSet<_> = stdout.lines().collect();
+        assert_eq!(passwords.len(), 100);
+
+        // The options that depend on the passwords generated before cannot be combined
+        for option in ["--unique", "--batch-cover-classes"] {
+            assert!(Cli::try_parse_from(["mkpw", "--parallel", "2", option]).is_err());
+        }
+        assert!(Cli::try_parse_from(["mkpw", "--parallel", "0"]).is_err());
+    }
+
+    #[test]
+    fn generate_in_parallel_stops() {
+        // The threads stop soon after the output fails, without generating the remaining passwords
+        let args = Cli {
+            seed: Some(1),
+            ..Default::default()
+        };
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        let lengths = vec![16; 1_000_000];
+        let mut emitted = 0;
+        let error = generate_in_parallel(&maker, &args, &[], &lengths, 4, &mut 0, &mut |_| {
+            emitted += 1;
+            Err("Closed".to_string())
+        })
+        .unwrap_err();
+        assert_eq!(error, "Closed");
+        assert_eq!(emitted, 1);
+    }
+
+    #[test]
+    fn include_whitespace() {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--include-whitespace", "--length", "1000"])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+
+        // Leading and trailing spaces are kept, and only the separator follows the password
+        let password = stdout.strip_suffix('\n').unwrap();
+        assert_eq!(password.len(), 1000);
+        assert!(password.contains(' '));
+    }
+
+    #[test]
+    fn qr() {
+        let password = {
+            let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+            let assert = cmd.args(["--seed", "1"]).assert().success();
+            String::from_utf8(assert.get_output().stdout.clone()).unwrap()
+        };
+
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd.args(["--seed", "1", "--qr"]).assert().success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        assert!(stdout.contains(['▀', '▄', '█']), "{}", stdout);
+        assert!(!stdout.contains(password.trim_end()), "{}", stdout);
+
+        // Non-ASCII passwords are held in UTF-8
+        assert!(qr_code("あ😀").is_ok());
+        assert!(qr_code(&"a".repeat(10000)).is_err());
+
+        // Only one password can be output
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd.args(["--qr", "--count", "2"]).assert().failure();
+        let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+        assert!(stderr.contains("\"--qr\" outputs only one password"));
+
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        cmd.args(["--qr", "--clipboard"]).assert().failure();
+    }
+
+    #[test]
+    fn unmappable_output() {
+        // An emoji cannot be encoded in Shift_JIS, so the output fails instead of replacing it
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args([
+                "--encoding",
+                "shift_jis",
+                "--encoding-fallback",
+                "utf-8",
+                "--other-candidates",
+                "😀",
+                "--other-minimum-count",
+                "1",
+            ])
+            .assert()
+            .failure();
+        let output = assert.get_output();
+        assert!(output.stdout.is_empty());
+        let stderr = String::from_utf8(output.stderr.clone()).unwrap();
+        assert!(
+            stderr.contains("cannot be encoded in Shift_JIS: \"😀\" (U+1F600)"),
+            "{}",
+            stderr
+        );
+
+        // The file is not written either
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("passwords.txt");
+        let args = Cli {
+            encoding: "shift_jis".to_string(),
+            output: Some(path.clone()),
+            ..Default::default()
+        };
+        assert!(output_passwords("a😀", &args).is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn pool_encoding() {
+        let args = Cli {
+            other_candidates: Some(vec![OsString::from("あ😀い🍣")]),
+            pool_encoding: Some("shift_jis".to_string()),
+            ..Default::default()
+        };
+        let maker = PasswordMaker::try_from(&args).unwrap();
+        let pool = maker.effective_pool();
+        assert_eq!(pool[94..], ["あ", "い"].map(String::from));
+        assert_eq!(pool[..94], PasswordMaker::default().effective_pool()[..]);
+
+        // The password contains only the characters that can be represented in the encoding
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args([
+                "--pool-encoding",
+                "shift_jis",
+                "--uppercase-candidates",
+                "",
+                "--number-candidates",
+                "",
+                "--symbol-candidates",
+                "",
+                "--lowercase-candidates",
+                "ab",
+                "--other-candidates",
+                "😀",
+                "--length",
+                "100",
+            ])
+            .assert()
+            .success();
+        let password = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        assert!(!password.contains('😀'));
+        assert!(password.trim_end().chars().all(|c| c == 'a' || c == 'b'));
+
+        // An emptied character type with a minimum count is reported
+        let args = Cli {
+            other_candidates: Some(vec![OsString::from("😀")]),
+            other_minimum_count: Some(vec![1]),
+            pool_encoding: Some("shift_jis".to_string()),
+            ..Default::default()
+        };
+        assert!(generate_passwords(&args, &mut 0)
+            .unwrap_err()
+            .starts_with("Other characters at index 0 is empty"));
+
+        let args = Cli {
+            pool_encoding: Some("unknown".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            PasswordMaker::try_from(&args).unwrap_err(),
+            "Unsupported encoding: unknown"
+        );
+    }
+
+    #[test]
+    fn print_annotate() {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args([
+                "--annotate",
+                "--other-candidates",
+                "あ",
+                "--other-minimum-count",
+                "2",
+            ])
+            .assert()
+            .success();
+        let output = assert.get_output();
+
+        // The password is output to standard output, and the password and the markers to standard error
+        let password = String::from_utf8(output.stdout.clone()).unwrap();
+        let annotated = String::from_utf8(output.stderr.clone()).unwrap();
+        let mut lines = annotated.lines();
+        assert_eq!(lines.next(), Some(password.trim_end()));
+
+        let markers = lines.next().unwrap();
+        assert_eq!(
+            unicode_width::UnicodeWidthStr::width(markers),
+            unicode_width::UnicodeWidthStr::width(password.trim_end())
+        );
+        assert_eq!(markers.matches('o').count(), password.matches('あ').count());
+        assert!(markers.contains('U') && markers.contains('l'));
+        assert!(markers.contains('d') && markers.contains('s'));
+    }
+
+    #[test]
+    fn print_mask() {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args(["--mask", "--mask-reveal", "3"])
+            .assert()
+            .success();
+        let output = assert.get_output();
+        let password = String::from_utf8(output.stdout.clone()).unwrap();
+        let password = password.trim_end();
+        let stderr = String::from_utf8(output.stderr.clone()).unwrap();
+
+        // Only the last 3 characters are visible
+        assert_eq!(stderr, format!("{}{}\n", "*".repeat(13), &password[13..]));
+    }
+
+    #[test]
+    fn minimum_count_overflow() {
+        // Huge minimum counts are reported as an error instead of a panic
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        let assert = cmd
+            .args([
+                "--uppercase-minimum-count",
+                "4294967295",
+                "--lowercase-minimum-count",
+                "4294967295",
+            ])
+            .assert()
+            .failure();
+        let stderr = String::from_utf8_lossy(&assert.get_output().stderr).to_string();
+        assert!(stderr.contains("Minimum counts overflow"));
+        assert!(!stderr.contains("panicked"));
+    }
+
+    #[test]
+    fn errors_to() {
+        // Error messages are output to standard error by default
+        {
+            let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+            let assert = cmd.args(["--length", "0"]).assert().failure();
+            let output = assert.get_output();
+            assert!(output.stdout.is_empty());
+            assert!(
+                String::from_utf8_lossy(&output.stderr).contains("but the password length is 0")
+            );
+        }
+
+        // Error messages are output to standard output
+        {
+            let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+            let assert = cmd
+                .args(["--length", "0", "--errors-to", "stdout"])
+                .assert()
+                .failure();
+            let output = assert.get_output();
+            assert!(
+                String::from_utf8_lossy(&output.stdout).contains("but the password length is 0")
+            );
+            assert!(output.stderr.is_empty());
+        }
+    }
+
+    #[test]
+    fn list_encodings() {
+        let mut cmd = Command::cargo_bin("mkpw").unwrap();
+        let assert = cmd.args(["--list-encodings"]).assert().success();
+
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        let labels: Vec<&str> = stdout.lines().collect();
+        assert!(labels.contains(&"shift_jis"));
+        assert!(labels.contains(&"utf-8"));
+    }
+
     #[test]
     fn print_completions() {
         // It's easier to test with assert_cmd than to capture standard output.
@@ -0,0 +1,81 @@
+/// Return whether the password contains a digit sequence that looks like a date
+///
+/// The following patterns are checked in every run of consecutive ASCII digits:
+/// - YYYY: a year from 1900 to 2099
+/// - MMDD: a month (01-12) followed by a day (01-31)
+/// - DDMM: a day (01-31) followed by a month (01-12)
+///
+/// YYYYMMDD is covered by YYYY.
+///
+/// # Arguments
+///
+/// * `password` - Password to check
+///
+/// # Returns
+///
+/// Whether the password contains a date-like sequence
+pub fn contains_date(password: &str) -> bool {
+    password
+        .split(|c: char| !c.is_ascii_digit())
+        .any(|run| run.as_bytes().windows(4).any(is_date))
+}
+
+/// Return whether 4 ASCII digits look like a date
+///
+/// # Arguments
+///
+/// * `digits` - 4 ASCII digits
+fn is_date(digits: &[u8]) -> bool {
+    let value = |range: std::ops::Range<usize>| {
+        digits[range]
+            .iter()
+            .fold(0u32, |acc, d| acc * 10 + (d - b'0') as u32)
+    };
+    let is_month = |m: u32| (1..=12).contains(&m);
+    let is_day = |d: u32| (1..=31).contains(&d);
+
+    let year = value(0..4);
+    let (first, second) = (value(0..2), value(2..4));
+
+    (1900..=2099).contains(&year)
+        || (is_month(first) && is_day(second))
+        || (is_day(first) && is_month(second))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_date_years() {
+        assert!(contains_date("ab1999cd"));
+        assert!(contains_date("2024"));
+        assert!(contains_date("x20240131"));
+        assert!(!contains_date("1899"));
+        assert!(!contains_date("2100"));
+    }
+
+    #[test]
+    fn contains_date_month_and_day() {
+        // MMDD
+        assert!(contains_date("a1231b"));
+        // DDMM
+        assert!(contains_date("a3112b"));
+        // Neither a month nor a day
+        assert!(!contains_date("a9999b"));
+        assert!(!contains_date("a0000b"));
+        assert!(!contains_date("a3232b"));
+    }
+
+    #[test]
+    fn contains_date_digit_runs() {
+        // Digits separated by other characters are not one run
+        assert!(!contains_date("12a31"));
+        assert!(!contains_date("19-99"));
+        // Short runs are never dates
+        assert!(!contains_date("123"));
+        assert!(!contains_date(""));
+        // Non-ASCII digits are not checked
+        assert!(!contains_date("１２３１"));
+    }
+}
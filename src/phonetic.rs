@@ -0,0 +1,192 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Words of the NATO phonetic alphabet, in alphabetical order
+const LETTERS: [&str; 26] = [
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliett",
+    "kilo", "lima", "mike", "november", "oscar", "papa", "quebec", "romeo", "sierra", "tango",
+    "uniform", "victor", "whiskey", "xray", "yankee", "zulu",
+];
+
+/// Names of the digits, in ascending order
+const DIGITS: [&str; 10] = [
+    "Zero", "One", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine",
+];
+
+/// Names of the ASCII symbols and the whitespace
+///
+/// Names consisting of multiple words are joined with hyphens so that each character is one word
+const SYMBOLS: [(char, &str); 33] = [
+    (' ', "Space"),
+    ('!', "Exclamation"),
+    ('"', "Double-quote"),
+    ('#', "Hash"),
+    ('$', "Dollar"),
+    ('%', "Percent"),
+    ('&', "Ampersand"),
+    ('\'', "Apostrophe"),
+    ('(', "Open-parenthesis"),
+    (')', "Close-parenthesis"),
+    ('*', "Asterisk"),
+    ('+', "Plus"),
+    (',', "Comma"),
+    ('-', "Hyphen"),
+    ('.', "Period"),
+    ('/', "Slash"),
+    (':', "Colon"),
+    (';', "Semicolon"),
+    ('<', "Less-than"),
+    ('=', "Equals"),
+    ('>', "Greater-than"),
+    ('?', "Question"),
+    ('@', "At"),
+    ('[', "Open-bracket"),
+    ('\\', "Backslash"),
+    (']', "Close-bracket"),
+    ('^', "Caret"),
+    ('_', "Underscore"),
+    ('`', "Backtick"),
+    ('{', "Open-brace"),
+    ('|', "Pipe"),
+    ('}', "Close-brace"),
+    ('~', "Tilde"),
+];
+
+/// Spell out a password with the NATO phonetic alphabet
+///
+/// Each character is converted into one word, and the words are separated by spaces.
+/// - Uppercase letters are capitalized words (e.g. `A` -> `Alpha`)
+/// - Lowercase letters are lowercase words (e.g. `b` -> `bravo`)
+/// - Digits and symbols are capitalized names (e.g. `7` -> `Seven`, `!` -> `Exclamation`)
+/// - Other characters are output as they are
+///
+/// # Arguments
+///
+/// * `password` - Password to spell out
+///
+/// # Returns
+///
+/// The spelled out password
+pub fn spell(password: &str) -> String {
+    password
+        .graphemes(true)
+        .map(spell_grapheme)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Expand a password into one memorable word per character
+///
+/// The words are those of [`spell`], but each word starts with the original character,
+/// so the password can be read back from the first character of each word.
+/// - Letters are the words of [`spell`] as they are (e.g. `A` -> `Alpha`, `b` -> `bravo`)
+/// - Digits and symbols are the character and its name joined with a colon (e.g. `7` -> `7:Seven`, ` ` -> ` :Space`)
+/// - Other characters are output as they are
+///
+/// The names never contain a space, so a space in the password does not break the reading:
+/// take the first character, skip the rest of the word, and skip the space after it.
+///
+/// # Arguments
+///
+/// * `password` - Password to expand
+///
+/// # Returns
+///
+/// The words separated by spaces
+pub fn mnemonic(password: &str) -> String {
+    password
+        .graphemes(true)
+        .map(|grapheme| {
+            let word = spell_grapheme(grapheme);
+            if word.starts_with(grapheme) {
+                word
+            } else {
+                format!("{}:{}", grapheme, word)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Spell out a single grapheme
+///
+/// # Arguments
+///
+/// * `grapheme` - Grapheme to spell out
+///
+/// # Returns
+///
+/// The word for the grapheme, or the grapheme itself if there is no word for it
+fn spell_grapheme(grapheme: &str) -> String {
+    let mut chars = grapheme.chars();
+    let (Some(c), None) = (chars.next(), chars.next()) else {
+        return grapheme.to_string();
+    };
+
+    if c.is_ascii_lowercase() {
+        LETTERS[(c as u8 - b'a') as usize].to_string()
+    } else if c.is_ascii_uppercase() {
+        let word = LETTERS[(c as u8 - b'A') as usize];
+        word[..1].to_ascii_uppercase() + &word[1..]
+    } else if c.is_ascii_digit() {
+        DIGITS[(c as u8 - b'0') as usize].to_string()
+    } else if let Some((_, name)) = SYMBOLS.iter().find(|(symbol, _)| *symbol == c) {
+        name.to_string()
+    } else {
+        grapheme.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spell_letters_and_digits() {
+        assert_eq!(spell("Ab7"), "Alpha bravo Seven");
+        assert_eq!(spell("Zz0"), "Zulu zulu Zero");
+    }
+
+    #[test]
+    fn spell_symbols() {
+        assert_eq!(spell("!\\ ~"), "Exclamation Backslash Space Tilde");
+
+        // Every default symbol has a name
+        for c in "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~".chars() {
+            assert_ne!(spell(&c.to_string()), c.to_string());
+        }
+    }
+
+    #[test]
+    fn mnemonic_words() {
+        assert_eq!(mnemonic("Ab7!"), "Alpha bravo 7:Seven !:Exclamation");
+        assert_eq!(mnemonic("a b"), "alpha  :Space bravo");
+
+        // One word per character, and the first characters of the words are the original password
+        let password = "Zq9~x :あ👨‍👩‍👦M";
+        let expanded = mnemonic(password);
+        let mut restored = String::new();
+        let mut words = 0;
+        let mut rest = expanded.as_str();
+        while let Some(first) = rest.graphemes(true).next() {
+            restored.push_str(first);
+            words += 1;
+            // Skip the rest of the word and the space after it
+            rest = &rest[first.len()..];
+            rest = &rest[rest.find(' ').map_or(rest.len(), |index| index + 1)..];
+        }
+        assert_eq!(words, password.graphemes(true).count());
+        assert_eq!(restored, password);
+
+        // Every digit and symbol has a word
+        for c in "0123456789 !\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~".chars() {
+            assert!(mnemonic(&c.to_string()).len() > 1);
+        }
+    }
+
+    #[test]
+    fn spell_other_characters() {
+        // Characters without a word are output as they are, one word per grapheme
+        assert_eq!(spell("aあ👨‍👩‍👦"), "alpha あ 👨‍👩‍👦");
+        assert_eq!(spell(""), "");
+    }
+}
@@ -34,3 +34,19 @@ fn integration_error() {
     let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
     cmd.args(["--length", "0"]).assert().failure();
 }
+
+#[test]
+fn maximum_count() {
+    // Symbols are capped at 0, so a long password has no punctuation
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let assert = cmd
+        .args(["--length", "200"])
+        .args(["--symbol-minimum-count", "0", "--symbol-maximum-count", "0"])
+        .assert()
+        .success();
+    let password = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    let password = password.trim_end();
+
+    assert_eq!(password.len(), 200);
+    assert!(password.chars().all(|c| c.is_ascii_alphanumeric()));
+}